@@ -0,0 +1,109 @@
+//! Nested keys are split one level at a time, each level decoding only its own sub-key,
+//! so the work is linear in the length of the input regardless of how it's split between
+//! width and depth. The throughput of the deeper inputs below should stay roughly the same.
+//!
+//! Consecutive pairs sharing a sub key, like the fields of the nested structs below, are
+//! grouped as a run, so their sub key is decoded and looked up once per run at each level.
+
+use std::collections::HashMap;
+
+use _serde::Deserialize;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_querystring::BracketsQS;
+
+/// A recursive map, able to hold any depth of nesting, leaves are deserialized as empty maps
+#[derive(Deserialize)]
+#[serde(crate = "_serde", transparent)]
+#[allow(dead_code)]
+struct Tree(HashMap<String, Tree>);
+
+/// Builds `width` sibling keys each nested `depth` levels deep, ex. `k0[a][a]=v&k1[a][a]=v`
+fn wide_and_deep(width: usize, depth: usize) -> Vec<u8> {
+    let mut input = String::new();
+    for i in 0..width {
+        if i != 0 {
+            input.push('&');
+        }
+        input.push_str(&format!("k{}", i));
+        for level in 0..depth {
+            input.push_str(&format!("[l{}]", level));
+        }
+        input.push_str("=value");
+    }
+    input.into_bytes()
+}
+
+fn nesting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("brackets_nesting");
+
+    for (width, depth) in [(100, 1), (100, 8), (100, 32), (1000, 8), (10, 256)] {
+        let input = wide_and_deep(width, depth);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{}x{}", width, depth)),
+            &input,
+            |b, input| {
+                b.iter(|| {
+                    BracketsQS::parse(black_box(input))
+                        .max_depth(depth + 1)
+                        .deserialize::<Tree>()
+                        .unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+/// A linked list of structs, each level with its own fields
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Node {
+    id: u32,
+    name: String,
+    next: Option<Box<Node>>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct List {
+    list: Node,
+}
+
+/// Builds a list `depth` nodes long, ex. `list[id]=0&list[name]=n&list[next][id]=1&...`
+fn linked(depth: usize) -> Vec<u8> {
+    let mut input = String::new();
+    let mut key = String::from("list");
+    for level in 0..depth {
+        if level != 0 {
+            input.push('&');
+            key.push_str("[next]");
+        }
+        input.push_str(&format!("{0}[id]={1}&{0}[name]=node+{1}", key, level));
+    }
+    input.into_bytes()
+}
+
+fn structs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("brackets_structs");
+
+    for depth in [8, 32, 100] {
+        let input = linked(depth);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &input, |b, input| {
+            b.iter(|| {
+                BracketsQS::parse(black_box(input))
+                    .deserialize::<List>()
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, nesting, structs);
+criterion_main!(benches);
@@ -0,0 +1,192 @@
+//! Compares the four parse modes on the same kinds of querystrings, each written in the
+//! syntax of the mode, ex. sequences are `key=1&key=2` in duplicate mode and `key=1|2` in
+//! delimiter mode. Nested keys are only supported in brackets mode.
+
+use _serde::Deserialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use serde_querystring::{from_bytes, Deserializer, ParseMode};
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Flat<'a> {
+    id: u64,
+    #[serde(borrow)]
+    name: &'a str,
+    #[serde(borrow)]
+    category: &'a str,
+    page: u32,
+    per_page: u32,
+    active: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Sequences {
+    ids: Vec<u64>,
+    tags: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Encoded {
+    query: String,
+    redirect: String,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Nested {
+    user: User,
+    filters: Vec<Filter>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct User {
+    name: String,
+    address: Address,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Address {
+    city: String,
+    zip: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Filter {
+    field: String,
+    value: String,
+}
+
+const FLAT: &[u8] =
+    b"id=1234567&name=some_product_name&category=electronics&page=20&per_page=100&active=true";
+
+const ENCODED: &[u8] =
+    b"query=caf%C3%A9+au+lait+%26+croissant&redirect=https%3A%2F%2Fexample.com%2Fpath%3Fa%3D1";
+
+const NESTED: &[u8] = b"user[name]=pouya&user[address][city]=tehran&user[address][zip]=12345\
+&filters[0][field]=price&filters[0][value]=100&filters[1][field]=color&filters[1][value]=red";
+
+const MODES: [(&str, ParseMode); 4] = [
+    ("urlencoded", ParseMode::UrlEncoded),
+    ("duplicate", ParseMode::Duplicate),
+    ("delimiter", ParseMode::Delimiter(b'|')),
+    ("brackets", ParseMode::Brackets),
+];
+
+/// Builds a querystring with `len` ids and tags, in the syntax of the mode
+fn sequences(mode: ParseMode, len: usize) -> Vec<u8> {
+    let mut input = String::new();
+    for key in ["ids", "tags"] {
+        if !input.is_empty() {
+            input.push('&');
+        }
+
+        let values = (0..len).map(|i| format!("{}", i * 997));
+        match mode {
+            ParseMode::Delimiter(_) => {
+                input.push_str(key);
+                input.push('=');
+                input.push_str(&values.collect::<Vec<_>>().join("|"));
+            }
+            ParseMode::Brackets => {
+                let pairs = values.map(|v| format!("{}[]={}", key, v));
+                input.push_str(&pairs.collect::<Vec<_>>().join("&"));
+            }
+            _ => {
+                let pairs = values.map(|v| format!("{}={}", key, v));
+                input.push_str(&pairs.collect::<Vec<_>>().join("&"));
+            }
+        }
+    }
+    input.into_bytes()
+}
+
+fn flat(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes_flat");
+    group.throughput(Throughput::Bytes(FLAT.len() as u64));
+
+    for (name, mode) in MODES {
+        group.bench_function(name, |b| {
+            b.iter(|| from_bytes::<Flat>(black_box(FLAT), mode).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn encoded(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes_encoded");
+    group.throughput(Throughput::Bytes(ENCODED.len() as u64));
+
+    for (name, mode) in MODES {
+        group.bench_function(name, |b| {
+            b.iter(|| from_bytes::<Encoded>(black_box(ENCODED), mode).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn encoded_with_scratch(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes_encoded_with_scratch");
+    group.throughput(Throughput::Bytes(ENCODED.len() as u64));
+
+    for (name, mode) in MODES {
+        let mut scratch = Vec::new();
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let de = Deserializer::from_bytes(black_box(ENCODED), mode);
+                Encoded::deserialize(de.with_scratch(&mut scratch)).unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn sequence(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes_sequences");
+
+    // Urlencoded mode doesn't support sequences
+    for (name, mode) in &MODES[1..] {
+        let input = sequences(*mode, 100);
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_function(*name, |b| {
+            b.iter(|| from_bytes::<Sequences>(black_box(&input), *mode).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn nested(c: &mut Criterion) {
+    let mut group = c.benchmark_group("modes_nested");
+    group.throughput(Throughput::Bytes(NESTED.len() as u64));
+
+    group.bench_function("brackets", |b| {
+        b.iter(|| from_bytes::<Nested>(black_box(NESTED), ParseMode::Brackets).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    flat,
+    encoded,
+    encoded_with_scratch,
+    sequence,
+    nested
+);
+criterion_main!(benches);
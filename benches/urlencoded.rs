@@ -0,0 +1,45 @@
+use _serde::Deserialize;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use serde_querystring::UrlEncodedQS;
+
+#[derive(Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Params<'a> {
+    id: u64,
+    #[serde(borrow)]
+    name: &'a str,
+    #[serde(borrow)]
+    category: &'a str,
+    page: u32,
+    per_page: u32,
+    active: bool,
+}
+
+const INPUT: &[u8] =
+    b"id=1234567&name=some_product_name&category=electronics&page=20&per_page=100&active=true";
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("urlencoded");
+
+    group.bench_function("parse", |b| {
+        b.iter(|| {
+            UrlEncodedQS::parse(black_box(INPUT))
+                .deserialize::<Params>()
+                .unwrap()
+        })
+    });
+
+    group.bench_function("parse_no_decode", |b| {
+        b.iter(|| {
+            UrlEncodedQS::parse_no_decode(black_box(INPUT))
+                .deserialize::<Params>()
+                .unwrap()
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
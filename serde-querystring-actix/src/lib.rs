@@ -105,7 +105,17 @@ where
             .cloned()
             .unwrap_or_default();
 
-        serde_querystring::de::from_str::<T>(req.query_string(), config.mode)
+        let query = req.query_string();
+        let value = if config.require_non_empty && query.is_empty() {
+            Err(
+                serde_querystring::de::Error::new(serde_querystring::de::ErrorKind::Other)
+                    .message(String::from("the query string is empty")),
+            )
+        } else {
+            serde_querystring::de::from_str::<T>(query, config.mode)
+        };
+
+        value
             .map(|val| ready(Ok(QueryString(val))))
             .unwrap_or_else(move |e| {
                 let e = QueryStringPayloadError::Deserialize(e);
@@ -164,9 +174,12 @@ where
 #[derive(Clone)]
 pub struct QueryStringConfig {
     mode: serde_querystring::de::ParseMode,
-    ehandler: Option<Arc<dyn Fn(QueryStringPayloadError, &HttpRequest) -> Error + Send + Sync>>,
+    require_non_empty: bool,
+    ehandler: Option<ErrorHandler>,
 }
 
+type ErrorHandler = Arc<dyn Fn(QueryStringPayloadError, &HttpRequest) -> Error + Send + Sync>;
+
 impl QueryStringConfig {
     /// Set custom error handler
     pub fn error_handler<F>(mut self, f: F) -> Self
@@ -181,12 +194,22 @@ impl QueryStringConfig {
         self.mode = mode;
         self
     }
+
+    /// Rejects requests without a query string, or with an empty one, before deserializing.
+    ///
+    /// It is disabled by default, so an empty query string is deserialized like any other,
+    /// ex. into a struct with all its fields missing.
+    pub fn require_non_empty(mut self, enabled: bool) -> Self {
+        self.require_non_empty = enabled;
+        self
+    }
 }
 
 impl Default for QueryStringConfig {
     fn default() -> Self {
         QueryStringConfig {
             mode: serde_querystring::de::ParseMode::Duplicate,
+            require_non_empty: false,
             ehandler: None,
         }
     }
@@ -229,14 +252,14 @@ mod tests {
     async fn test_service_request_extract() {
         let req = TestRequest::with_uri("/name/user1/").to_srv_request();
         assert!(QueryString::<Id>::from_query(
-            &req.query_string(),
+            req.query_string(),
             serde_querystring::de::ParseMode::UrlEncoded
         )
         .is_err());
 
         let req = TestRequest::with_uri("/name/user1/?id=test").to_srv_request();
         let mut s = QueryString::<Id>::from_query(
-            &req.query_string(),
+            req.query_string(),
             serde_querystring::de::ParseMode::UrlEncoded,
         )
         .unwrap();
@@ -299,4 +322,31 @@ mod tests {
             StatusCode::UNPROCESSABLE_ENTITY
         );
     }
+
+    #[actix_rt::test]
+    async fn test_require_non_empty() {
+        #[derive(Deserialize)]
+        #[allow(dead_code)]
+        struct Params {
+            n: Option<i32>,
+        }
+
+        for (require_non_empty, uri, ok) in [
+            (false, "/name/user1/", true),
+            (false, "/name/user1/?", true),
+            (true, "/name/user1/", false),
+            (true, "/name/user1/?", false),
+            (true, "/name/user1/?n=1", true),
+            (true, "/name/user1/?m=1", true),
+        ] {
+            let req = TestRequest::with_uri(uri)
+                .app_data(QueryStringConfig::default().require_non_empty(require_non_empty))
+                .to_srv_request();
+
+            let (req, mut pl) = req.into_parts();
+            let query = QueryString::<Params>::from_request(&req, &mut pl).await;
+
+            assert_eq!(query.is_ok(), ok, "{}", uri);
+        }
+    }
 }
@@ -0,0 +1,55 @@
+use axum::extract::FromRequestParts;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use http::Request;
+use serde::Deserialize;
+use serde_querystring_axum::{ParseMode, QueryString, QueryStringConfig};
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Pagination {
+    page: usize,
+    per_page: usize,
+}
+
+fn extractor(c: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+
+    let (default_parts, _) = Request::builder()
+        .uri("/?page=2&per_page=30")
+        .body(())
+        .unwrap()
+        .into_parts();
+
+    // A config with an error handler in the extensions, like the `Extension` layer adds
+    let (mut config_parts, _) = Request::builder()
+        .uri("/?page=2&per_page=30")
+        .body(())
+        .unwrap()
+        .into_parts();
+    config_parts
+        .extensions
+        .insert(QueryStringConfig::new(ParseMode::Duplicate).ehandler(|err| err.to_string()));
+
+    let mut group = c.benchmark_group("extractor");
+
+    for (name, parts) in [("default", default_parts), ("config", config_parts)] {
+        let mut parts = parts;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                runtime
+                    .block_on(QueryString::<Pagination>::from_request_parts(
+                        black_box(&mut parts),
+                        &(),
+                    ))
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, extractor);
+criterion_main!(benches);
@@ -8,9 +8,9 @@ use axum_core::{
     extract::FromRequestParts,
     response::{IntoResponse, Response},
 };
-use http::{request::Parts, StatusCode};
+use http::{request::Parts, HeaderMap, HeaderName, StatusCode};
 use serde::de::DeserializeOwned;
-use serde_querystring::de::Error;
+use serde_querystring::de::{Error, ErrorKind};
 
 pub use serde_querystring::de::ParseMode;
 
@@ -79,15 +79,20 @@ where
     type Rejection = Response;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        let QueryStringConfig { mode, ehandler } = parts
-            .extensions
-            .get::<QueryStringConfig>()
-            .cloned()
-            .unwrap_or_default();
+        // Borrowed instead of cloned, as it's only needed for the mode unless there is an error
+        let config = parts.extensions.get::<QueryStringConfig>();
+        let require_non_empty = config.map_or(false, |c| c.require_non_empty);
 
         let query = parts.uri.query().unwrap_or_default();
-        let value = serde_querystring::from_str(query, mode).map_err(|e| {
-            if let Some(ehandler) = ehandler {
+        let value = request_mode(&parts.headers, config).and_then(|mode| {
+            if require_non_empty && query.is_empty() {
+                Err(Error::new(ErrorKind::Other).message(String::from("the query string is empty")))
+            } else {
+                serde_querystring::from_str(query, mode)
+            }
+        });
+        let value = value.map_err(|e| {
+            if let Some(ehandler) = config.and_then(|c| c.ehandler.as_ref()) {
                 ehandler(e)
             } else {
                 QueryStringError::default().into_response()
@@ -97,6 +102,29 @@ where
     }
 }
 
+/// The mode of a request, read from the mode header when the config allows it
+fn request_mode(
+    headers: &HeaderMap,
+    config: Option<&QueryStringConfig>,
+) -> Result<ParseMode, Error> {
+    let mode = config.map_or_else(|| QueryStringConfig::default().mode, |c| c.mode);
+
+    let header = config
+        .and_then(|c| c.mode_header.as_ref())
+        .and_then(|name| headers.get(name));
+
+    match header {
+        Some(header) => std::str::from_utf8(header.as_bytes())
+            .map_err(|_| {
+                Error::new(ErrorKind::Other)
+                    .message(String::from("unknown query string mode"))
+                    .value(header.as_bytes())
+            })?
+            .parse(),
+        None => Ok(mode),
+    }
+}
+
 impl<T> Deref for QueryString<T> {
     type Target = T;
 
@@ -125,6 +153,8 @@ impl<T> Deref for QueryString<T> {
 #[derive(Clone)]
 pub struct QueryStringConfig {
     mode: ParseMode,
+    mode_header: Option<HeaderName>,
+    require_non_empty: bool,
     ehandler: Option<Arc<dyn Fn(Error) -> Response + Send + Sync>>,
 }
 
@@ -132,6 +162,8 @@ impl Default for QueryStringConfig {
     fn default() -> Self {
         Self {
             mode: ParseMode::Duplicate,
+            mode_header: None,
+            require_non_empty: false,
             ehandler: None,
         }
     }
@@ -141,6 +173,8 @@ impl QueryStringConfig {
     pub fn new(mode: ParseMode) -> Self {
         Self {
             mode,
+            mode_header: None,
+            require_non_empty: false,
             ehandler: None,
         }
     }
@@ -150,6 +184,40 @@ impl QueryStringConfig {
         self
     }
 
+    /// Reads the mode of each request from the given header, falling back to the configured mode
+    /// when the header is missing. It is disabled by default, as it lets the clients choose
+    /// how their query strings are parsed.
+    ///
+    /// The header holds the name of the mode as parsed by `ParseMode`'s `FromStr`, ex.
+    /// `brackets`, `delimiter:,` or `delimiter_map:,:`. Requests with an unknown mode are
+    /// rejected like the ones failing to deserialize.
+    ///
+    /// ```rust,no_run
+    /// use axum::{http::HeaderName, Extension, Router};
+    /// use serde_querystring_axum::{ParseMode, QueryStringConfig};
+    ///
+    /// let app = Router::new().layer(Extension(
+    ///     QueryStringConfig::new(ParseMode::Duplicate)
+    ///         .allow_mode_header(HeaderName::from_static("x-qs-mode")),
+    /// ));
+    /// # async {
+    /// # axum::Server::bind(&"".parse().unwrap()).serve(app.into_make_service()).await.unwrap();
+    /// # };
+    /// ```
+    pub fn allow_mode_header(mut self, header_name: HeaderName) -> Self {
+        self.mode_header = Some(header_name);
+        self
+    }
+
+    /// Rejects requests without a query string, or with an empty one, before deserializing.
+    ///
+    /// It is disabled by default, so an empty query string is deserialized like any other,
+    /// ex. into a struct with all its fields missing.
+    pub fn require_non_empty(mut self, enabled: bool) -> Self {
+        self.require_non_empty = enabled;
+        self
+    }
+
     pub fn ehandler<F, R>(mut self, ehandler: F) -> Self
     where
         F: Fn(Error) -> R + Send + Sync + 'static,
@@ -191,7 +259,7 @@ mod tests {
         routing::get,
         Extension, Router,
     };
-    use http::{Request, StatusCode};
+    use http::{HeaderName, Request, StatusCode};
     use serde::Deserialize;
     use tower::ServiceExt;
 
@@ -262,7 +330,7 @@ mod tests {
         }
 
         async fn handler(q: QueryString<Params>) -> String {
-            format!("{}-{}", q.n.get(0).unwrap(), q.n.get(2).unwrap())
+            format!("{}-{}", q.n.first().unwrap(), q.n.get(2).unwrap())
         }
 
         let app = Router::new()
@@ -348,4 +416,196 @@ mod tests {
         assert_eq!(parts.status, StatusCode::BAD_GATEWAY);
         assert_eq!(body.data().await.unwrap().unwrap(), "Something went wrong");
     }
+
+    #[tokio::test]
+    async fn flattened_tagged_enum() {
+        #[derive(Debug, PartialEq, Deserialize)]
+        #[serde(tag = "kind", rename_all = "lowercase")]
+        enum Filter {
+            User {
+                name: String,
+            },
+            Tag {
+                label: String,
+                exact: Option<String>,
+            },
+        }
+
+        #[derive(Debug, PartialEq, Deserialize)]
+        struct Search {
+            q: String,
+            #[serde(flatten)]
+            filter: Filter,
+        }
+
+        async fn handler(search: QueryString<Search>) -> String {
+            match search.0.filter {
+                Filter::User { name } => format!("{}: user {}", search.0.q, name),
+                Filter::Tag { label, exact } => {
+                    format!("{}: tag {} {:?}", search.0.q, label, exact)
+                }
+            }
+        }
+
+        check(
+            "http://example.com/test?q=rust&kind=user&name=pooya",
+            Search {
+                q: String::from("rust"),
+                filter: Filter::User {
+                    name: String::from("pooya"),
+                },
+            },
+        )
+        .await;
+
+        for (mode, uri, expected) in [
+            (
+                ParseMode::UrlEncoded,
+                "/?kind=tag&label=web+dev&q=rust&exact=yes",
+                "rust: tag web dev Some(\"yes\")",
+            ),
+            (
+                ParseMode::Duplicate,
+                "/?label=web&q=rust&kind=tag",
+                "rust: tag web None",
+            ),
+            (
+                ParseMode::Brackets,
+                "/?q=rust&kind=user&name=a%26b",
+                "rust: user a&b",
+            ),
+        ] {
+            let app = Router::new()
+                .route("/", get(handler))
+                .layer(Extension(QueryStringConfig::new(mode)));
+            let res = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            let (parts, mut body) = res.into_parts();
+
+            assert_eq!(parts.status, StatusCode::OK);
+            assert_eq!(body.data().await.unwrap().unwrap(), expected);
+        }
+
+        // An unknown tag is rejected like any other invalid value
+        let app = Router::new().route("/", get(handler));
+        let res = app
+            .oneshot(
+                Request::builder()
+                    .uri("/?q=rust&kind=team&name=a")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn require_non_empty() {
+        #[derive(Deserialize)]
+        struct Params {
+            n: Option<i32>,
+        }
+
+        async fn handler(q: QueryString<Params>) -> String {
+            format!("{:?}", q.n)
+        }
+
+        for (require_non_empty, uri, status) in [
+            (false, "/", StatusCode::OK),
+            (false, "/?", StatusCode::OK),
+            (true, "/", StatusCode::BAD_REQUEST),
+            (true, "/?", StatusCode::BAD_REQUEST),
+            (true, "/?n=1", StatusCode::OK),
+            (true, "/?m=1", StatusCode::OK),
+        ] {
+            let app = Router::new().route("/", get(handler)).layer(Extension(
+                QueryStringConfig::default().require_non_empty(require_non_empty),
+            ));
+            let res = app
+                .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+                .await
+                .unwrap();
+
+            assert_eq!(res.status(), status, "{}", uri);
+        }
+
+        // The error goes through the custom handler
+        let app = Router::new().route("/", get(handler)).layer(Extension(
+            QueryStringConfig::default()
+                .require_non_empty(true)
+                .ehandler(|err| (StatusCode::UNPROCESSABLE_ENTITY, err.message)),
+        ));
+        let res = app
+            .oneshot(Request::builder().uri("/").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let (parts, mut body) = res.into_parts();
+
+        assert_eq!(parts.status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(
+            body.data().await.unwrap().unwrap(),
+            "the query string is empty"
+        );
+    }
+
+    #[tokio::test]
+    async fn mode_header() {
+        #[derive(Deserialize)]
+        struct Params {
+            n: Vec<i32>,
+        }
+
+        async fn handler(q: QueryString<Params>) -> String {
+            format!("{:?}", q.n)
+        }
+
+        let header = HeaderName::from_static("x-qs-mode");
+        for (allowed, mode, uri, expected) in [
+            // The header is ignored unless it's allowed
+            (false, Some("brackets"), "/?n=1&n=2", Some("[1, 2]")),
+            (false, Some("brackets"), "/?n[1]=1&n[0]=2", None),
+            (true, None, "/?n=1&n=2", Some("[1, 2]")),
+            (true, Some("brackets"), "/?n[1]=1&n[0]=2", Some("[2, 1]")),
+            (true, Some("delimiter:,"), "/?n=1,2", Some("[1, 2]")),
+            (true, Some("delimiter:|"), "/?n=1,2", None),
+            // UrlEncoded mode doesn't support sequences
+            (true, Some("urlencoded"), "/?n=1&n=2", None),
+            (true, Some("delimiter_map:,:"), "/?n=1,2", Some("[1, 2]")),
+            // The names are the same as the deserialized ones
+            (true, Some("Brackets"), "/?n=1&n=2", None),
+            (true, Some("duplicate"), "/?n=1&n=2", Some("[1, 2]")),
+            (true, Some("unknown"), "/?n=1&n=2", None),
+            (true, Some("delimiter:"), "/?n=1&n=2", None),
+        ] {
+            let mut config = QueryStringConfig::default();
+            if allowed {
+                config = config.allow_mode_header(header.clone());
+            }
+            let app = Router::new()
+                .route("/", get(handler))
+                .layer(Extension(config));
+
+            let mut req = Request::builder().uri(uri);
+            if let Some(mode) = mode {
+                req = req.header(&header, mode);
+            }
+            let res = app.oneshot(req.body(Body::empty()).unwrap()).await.unwrap();
+
+            let (parts, mut body) = res.into_parts();
+
+            match expected {
+                Some(expected) => {
+                    assert_eq!(parts.status, StatusCode::OK, "{:?} {}", mode, uri);
+                    assert_eq!(body.data().await.unwrap().unwrap(), expected);
+                }
+                None => assert_eq!(parts.status, StatusCode::BAD_REQUEST, "{:?} {}", mode, uri),
+            }
+        }
+    }
 }
@@ -0,0 +1,168 @@
+use std::{fmt, time::Duration};
+
+use _serde::de::{self, Deserialize};
+
+const SECS_PER_MINUTE: u64 = 60;
+const SECS_PER_HOUR: u64 = 60 * SECS_PER_MINUTE;
+const SECS_PER_DAY: u64 = 24 * SECS_PER_HOUR;
+const SECS_PER_WEEK: u64 = 7 * SECS_PER_DAY;
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// A `Duration` deserialized from an ISO 8601 duration, ex `PT1H30M` or `P1DT0.5S`.
+///
+/// Weeks(`W`), days(`D`), hours(`H`), minutes(`M`) and seconds(`S`) are supported, in this
+/// order, and the last component can have a fraction, ex `PT1.5H`. Years and months are
+/// rejected as their length depends on the calendar, and so are negative durations.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+///
+/// use _serde::Deserialize;
+/// use serde_querystring::{from_str, Iso8601Duration, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Query {
+///     timeout: Iso8601Duration,
+/// }
+///
+/// let query: Query = from_str("timeout=PT1M30S", ParseMode::UrlEncoded).unwrap();
+/// assert_eq!(query.timeout.0, Duration::from_secs(90));
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Iso8601Duration(pub Duration);
+
+impl From<Iso8601Duration> for Duration {
+    fn from(duration: Iso8601Duration) -> Self {
+        duration.0
+    }
+}
+
+/// The length of a component in seconds, for its designator
+fn component_secs(designator: u8, in_time: bool) -> Result<u64, &'static str> {
+    match (designator, in_time) {
+        (b'W', false) => Ok(SECS_PER_WEEK),
+        (b'D', false) => Ok(SECS_PER_DAY),
+        (b'H', true) => Ok(SECS_PER_HOUR),
+        (b'M', true) => Ok(SECS_PER_MINUTE),
+        (b'S', true) => Ok(1),
+        (b'Y', false) | (b'M', false) => Err("years and months are not supported"),
+        _ => Err("unexpected designator"),
+    }
+}
+
+fn parse_duration(input: &[u8]) -> Result<Duration, &'static str> {
+    let mut rest = match input.split_first() {
+        Some((b'P', rest)) if !rest.is_empty() => rest,
+        _ => return Err("it should start with `P` followed by components"),
+    };
+
+    let mut nanos: u128 = 0;
+    let mut in_time = false;
+    // The length of the last parsed component, components should get shorter
+    let mut last_secs = u64::MAX;
+    let mut has_fraction = false;
+
+    while let Some((&first, tail)) = rest.split_first() {
+        if first == b'T' {
+            if in_time || tail.is_empty() {
+                return Err("misplaced time designator `T`");
+            }
+            in_time = true;
+            rest = tail;
+            continue;
+        }
+
+        if has_fraction {
+            return Err("only the last component can have a fraction");
+        }
+
+        let digits = rest.iter().take_while(|c| c.is_ascii_digit()).count();
+        if digits == 0 {
+            return Err("expected a number");
+        }
+        let mut number: u128 = 0;
+        for &c in &rest[..digits] {
+            number = number
+                .checked_mul(10)
+                .and_then(|n| n.checked_add((c - b'0') as u128))
+                .ok_or("the duration is too long")?;
+        }
+        rest = &rest[digits..];
+
+        // The fraction is kept with nanosecond precision of the component
+        let mut fraction: u128 = 0;
+        let mut scale: u128 = 1;
+        if let Some((b'.' | b',', tail)) = rest.split_first() {
+            let digits = tail.iter().take_while(|c| c.is_ascii_digit()).count();
+            if digits == 0 {
+                return Err("expected digits after the decimal separator");
+            }
+            for &c in tail[..digits].iter().take(9) {
+                fraction = fraction * 10 + (c - b'0') as u128;
+                scale *= 10;
+            }
+            has_fraction = true;
+            rest = &tail[digits..];
+        }
+
+        let (&designator, tail) = rest.split_first().ok_or("missing designator")?;
+        let secs = component_secs(designator, in_time)?;
+        if secs >= last_secs {
+            return Err("components are out of order or repeated");
+        }
+        last_secs = secs;
+        rest = tail;
+
+        let secs = secs as u128;
+        nanos = number
+            .checked_mul(secs * NANOS_PER_SEC)
+            .and_then(|n| n.checked_add(fraction * secs * NANOS_PER_SEC / scale))
+            .and_then(|n| n.checked_add(nanos))
+            .ok_or("the duration is too long")?;
+    }
+
+    let secs = u64::try_from(nanos / NANOS_PER_SEC).map_err(|_| "the duration is too long")?;
+    Ok(Duration::new(secs, (nanos % NANOS_PER_SEC) as u32))
+}
+
+struct DurationVisitor;
+
+impl<'de> de::Visitor<'de> for DurationVisitor {
+    type Value = Iso8601Duration;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an ISO 8601 duration")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(v.as_bytes())
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        parse_duration(v).map(Iso8601Duration).map_err(|reason| {
+            E::custom(format_args!(
+                "invalid ISO 8601 duration `{}`: {}",
+                String::from_utf8_lossy(v),
+                reason
+            ))
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for Iso8601Duration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(DurationVisitor)
+    }
+}
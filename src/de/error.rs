@@ -1,12 +1,15 @@
 use std::fmt;
 
 #[derive(Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum ErrorKind {
     InvalidType,
     InvalidLength,
     InvalidEncoding,
     InvalidNumber,
     InvalidBoolean,
+    LimitExceeded,
+    Io,
     Other,
 }
 
@@ -22,7 +25,28 @@ pub struct Error {
 }
 
 impl Error {
-    pub(crate) fn new(kind: ErrorKind) -> Self {
+    /// Creates an error of the given kind, with an empty message and value.
+    ///
+    /// The other builder methods can be used to fill the details, so deserializers wrapping
+    /// this crate can report errors consistent with it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::de::{Error, ErrorKind};
+    ///
+    /// let error = Error::new(ErrorKind::InvalidNumber)
+    ///     .message("invalid page number".to_string())
+    ///     .value(b"page=x")
+    ///     .index(5);
+    ///
+    /// assert_eq!(error.kind, ErrorKind::InvalidNumber);
+    /// assert_eq!(error.index, Some(5));
+    /// assert_eq!(
+    ///     error.to_string(),
+    ///     "Error InvalidNumber: invalid page number in `page=x`"
+    /// );
+    /// ```
+    pub fn new(kind: ErrorKind) -> Self {
         Error {
             kind,
             message: String::new(),
@@ -31,22 +55,62 @@ impl Error {
         }
     }
 
-    pub(crate) fn message(mut self, message: String) -> Self {
+    /// Sets the message describing the error
+    pub fn message(mut self, message: String) -> Self {
         self.message = message;
         self
     }
 
-    pub(crate) fn value(mut self, slice: &[u8]) -> Self {
+    /// Sets the slice causing the error, invalid utf8 is replaced lossily
+    pub fn value(mut self, slice: &[u8]) -> Self {
         self.value = String::from_utf8_lossy(slice).to_string();
         self
     }
 
-    pub(crate) fn index(mut self, index: usize) -> Self {
+    /// Sets the index of the byte causing the error, in the value slice
+    pub fn index(mut self, index: usize) -> Self {
         self.index = Some(index);
         self
     }
 }
 
+impl Error {
+    /// Adds the index of the element causing the error in a sequence, and the element itself
+    /// if the error doesn't have a value yet
+    pub(crate) fn element(mut self, index: usize, slice: &[u8]) -> Self {
+        self.message = format!("{} at index {} of the sequence", self.message, index);
+        if self.value.is_empty() {
+            self = self.value(slice);
+        }
+        self
+    }
+
+    /// An `InvalidEncoding` error for the `%` at `index` of the slice, which is not followed by
+    /// two hex digits
+    pub(crate) fn invalid_percent(slice: &[u8], index: usize) -> Self {
+        Error::new(ErrorKind::InvalidEncoding)
+            .message(
+                "invalid percent encoding, `%` should be followed by two hex digits".to_string(),
+            )
+            .value(slice)
+            .index(index)
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Error {
+    /// Emits a debug event with the details of the error
+    pub(crate) fn trace(&self) {
+        tracing::debug!(
+            kind = ?self.kind,
+            message = %self.message,
+            value = %self.value,
+            index = ?self.index,
+            "failed to deserialize the querystring"
+        );
+    }
+}
+
 impl _serde::de::Error for Error {
     fn custom<T>(msg: T) -> Self
     where
@@ -61,6 +125,15 @@ impl _serde::de::Error for Error {
     }
 }
 
+impl _serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error::new(ErrorKind::Other).message(msg.to_string())
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
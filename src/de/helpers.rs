@@ -0,0 +1,236 @@
+use std::{borrow::Cow, fmt, marker::PhantomData};
+
+use _serde::de::{self, Deserialize};
+
+use super::{
+    __implementors::IntoDeserializer, scratch::Scratch, slices::DecodedSlice, Deserializer, Error,
+    ParseMode,
+};
+
+/// Collects the pairs of a map into a `Vec`, to be used with
+/// `#[serde(flatten, deserialize_with)]` as a catch-all for the unknown keys.
+///
+/// Unlike a flattened `HashMap`, each value of a repeated key (in `Duplicate` mode) becomes
+/// a pair of its own instead of keeping only the last one.
+///
+/// # Note
+/// The pairs are ordered by their decoded keys and not as they appear in the querystring,
+/// since the parsers group the values of each key. The values of a key keep their order, so
+/// `b=1&a=2&b=3` gives `[("a", "2"), ("b", "1"), ("b", "3")]`. Use
+/// [UrlEncodedQS::pairs_borrowed](crate::UrlEncodedQS::pairs_borrowed) to keep the exact
+/// order of a clean querystring.
+///
+/// # Example
+/// ```rust
+/// use _serde::Deserialize;
+/// use serde_querystring::{from_str, ParseMode};
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Request {
+///     page: u32,
+///     #[serde(flatten, deserialize_with = "serde_querystring::de::pairs")]
+///     extra: Vec<(String, String)>,
+/// }
+///
+/// let request: Request = from_str("page=2&tag=a&tag=b", ParseMode::Duplicate).unwrap();
+/// assert_eq!(
+///     request.extra,
+///     vec![
+///         ("tag".to_string(), "a".to_string()),
+///         ("tag".to_string(), "b".to_string())
+///     ]
+/// );
+/// ```
+pub fn pairs<'de, D, K, V>(deserializer: D) -> Result<Vec<(K, V)>, D::Error>
+where
+    D: de::Deserializer<'de>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    deserializer.deserialize_map(PairsVisitor(PhantomData))
+}
+
+struct PairsVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K, V> de::Visitor<'de> for PairsVisitor<K, V>
+where
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = Vec<(K, V)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut pairs = Vec::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            for value in map.next_value::<OneOrMany>()?.0 {
+                pairs.push((from_string(key.clone())?, from_string(value)?));
+            }
+        }
+
+        Ok(pairs)
+    }
+}
+
+/// A single value, or the values of a repeated key
+struct OneOrMany(Vec<String>);
+
+impl<'de> Deserialize<'de> for OneOrMany {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        struct OneOrManyVisitor;
+
+        impl<'de> de::Visitor<'de> for OneOrManyVisitor {
+            type Value = OneOrMany;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a string or a sequence of strings")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Ok(OneOrMany(vec![v.to_string()]))
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::SeqAccess<'de>,
+            {
+                let mut values = Vec::new();
+                while let Some(value) = seq.next_element()? {
+                    values.push(value);
+                }
+                Ok(OneOrMany(values))
+            }
+        }
+
+        deserializer.deserialize_any(OneOrManyVisitor)
+    }
+}
+
+/// Deserializes `T` from an already decoded string
+fn from_string<'de, T, E>(value: String) -> Result<T, E>
+where
+    T: Deserialize<'de>,
+    E: de::Error,
+{
+    let mut scratch = Scratch::default();
+    T::deserialize(DecodedSlice(Cow::Owned(value.into_bytes())).into_deserializer(&mut scratch))
+        .map_err(de::Error::custom)
+}
+
+/// Deserializes the value of a single key into `T`, without a struct for the whole query string.
+///
+/// The key is compared after percent decoding, and `None` is returned when it's missing. The
+/// other keys are skipped without being deserialized, so they can't cause an error unless the
+/// query string itself is malformed.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::{field, ParseMode};
+///
+/// let page = field::<u32>(b"page=2&tags=a&tags=b", ParseMode::Duplicate, "page").unwrap();
+/// assert_eq!(page, Some(2));
+///
+/// let tags = field::<Vec<String>>(b"page=2&tags=a&tags=b", ParseMode::Duplicate, "tags");
+/// assert_eq!(tags.unwrap(), Some(vec!["a".to_string(), "b".to_string()]));
+///
+/// assert_eq!(field::<u32>(b"page=2", ParseMode::Duplicate, "size"), Ok(None));
+/// ```
+pub fn field<'de, T>(input: &'de [u8], config: ParseMode, key: &str) -> Result<Option<T>, Error>
+where
+    T: Deserialize<'de>,
+{
+    let result = de::Deserializer::deserialize_map(
+        Deserializer::from_bytes(input, config),
+        FieldVisitor {
+            key: key.as_bytes(),
+            marker: PhantomData,
+        },
+    );
+
+    #[cfg(feature = "tracing")]
+    if let Err(error) = &result {
+        error.trace();
+    }
+
+    result
+}
+
+struct FieldVisitor<'k, T> {
+    key: &'k [u8],
+    marker: PhantomData<T>,
+}
+
+impl<'de, 'k, T> de::Visitor<'de> for FieldVisitor<'k, T>
+where
+    T: Deserialize<'de>,
+{
+    type Value = Option<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        while let Some(found) = map.next_key_seed(KeyMatches(self.key))? {
+            if found {
+                return map.next_value().map(Some);
+            }
+            map.next_value::<de::IgnoredAny>()?;
+        }
+
+        Ok(None)
+    }
+}
+
+/// Compares a key with the expected one, without allocating for it
+struct KeyMatches<'k>(&'k [u8]);
+
+impl<'de, 'k> de::DeserializeSeed<'de> for KeyMatches<'k> {
+    type Value = bool;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(self)
+    }
+}
+
+impl<'de, 'k> de::Visitor<'de> for KeyMatches<'k> {
+    type Value = bool;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a key")
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(v == self.0)
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.visit_bytes(v.as_bytes())
+    }
+}
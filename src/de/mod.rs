@@ -1,39 +1,63 @@
+mod duration;
 mod error;
+mod helpers;
+mod mode;
+mod raw;
+mod scratch;
 mod slices;
 mod traits;
+mod utf8;
+
+use std::borrow::BorrowMut;
 
 use _serde::{de, forward_to_deserialize_any};
 
+pub use duration::Iso8601Duration;
 pub use error::{Error, ErrorKind};
+pub use helpers::{field, pairs};
+pub use raw::RawQuery;
+pub use utf8::MaybeInvalidUtf8;
 
 pub(crate) mod __implementors {
+    pub(crate) use super::scratch::Scratch;
     pub(crate) use super::slices::{DecodedSlice, RawSlice};
-    pub(crate) use super::traits::{IntoDeserializer, IntoRawSlices};
+    pub(crate) use super::traits::{
+        IntoDeserializer, IntoRawSlices, SingleValue, SomeDeserializer,
+    };
 }
 
-use crate::parsers::{BracketsQS, DelimiterQS, DuplicateQS, UrlEncodedQS};
+use crate::parsers::{BracketsQS, DelimiterQS, DuplicateQS, ParseOptions, UrlEncodedQS};
+use scratch::{BorrowedScratch, CollectingScratch, Scratch};
 
-pub(crate) struct QSDeserializer<I, T> {
+/// The scratch buffer used for decoding values can be owned, or borrowed to be reused
+/// between calls
+pub(crate) struct QSDeserializer<I, T, S = Scratch> {
     iter: I,
     value: Option<T>,
-    scratch: Vec<u8>,
+    scratch: S,
 }
 
 impl<I, T> QSDeserializer<I, T> {
     pub fn new(iter: I) -> Self {
+        Self::with_scratch(iter, Scratch::default())
+    }
+}
+
+impl<I, T, S> QSDeserializer<I, T, S> {
+    pub fn with_scratch(iter: I, scratch: S) -> Self {
         Self {
             iter,
             value: None,
-            scratch: Vec::new(),
+            scratch,
         }
     }
 }
 
-impl<'de, I, E, A> de::Deserializer<'de> for QSDeserializer<I, A>
+impl<'de, I, A, S> de::Deserializer<'de> for QSDeserializer<I, A, S>
 where
-    I: Iterator<Item = (E, A)>,
-    for<'s> E: __implementors::IntoDeserializer<'de, 's>,
+    I: Iterator<Item = (__implementors::DecodedSlice<'de>, A)>,
     for<'s> A: __implementors::IntoDeserializer<'de, 's>,
+    S: BorrowMut<Scratch>,
 {
     type Error = Error;
 
@@ -44,18 +68,68 @@ where
         visitor.visit_map(self)
     }
 
+    /// An empty querystring is accepted as a unit value
+    fn deserialize_unit<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.iter.next().is_none() {
+            visitor.visit_unit()
+        } else {
+            visitor.visit_map(self)
+        }
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    /// An empty querystring is `None`, anything else is `Some`, even if it has no matching keys
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut iter = self.iter.peekable();
+        if iter.peek().is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(QSDeserializer::with_scratch(iter, self.scratch))
+        }
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let iter = TagFirst::new(self.iter, fields);
+        visitor.visit_map(QSDeserializer::with_scratch(iter, self.scratch))
+    }
+
     forward_to_deserialize_any! {
         bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
-        bytes byte_buf option unit unit_struct newtype_struct seq tuple
-        tuple_struct map struct enum identifier ignored_any
+        bytes byte_buf newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
     }
 }
 
-impl<'de, I, E, A> de::MapAccess<'de> for QSDeserializer<I, A>
+impl<'de, I, E, A, S> de::MapAccess<'de> for QSDeserializer<I, A, S>
 where
     I: Iterator<Item = (E, A)>,
     for<'s> E: __implementors::IntoDeserializer<'de, 's>,
     for<'s> A: __implementors::IntoDeserializer<'de, 's>,
+    S: BorrowMut<Scratch>,
 {
     type Error = Error;
 
@@ -63,11 +137,9 @@ where
     where
         K: de::DeserializeSeed<'de>,
     {
-        let mut scratch = Vec::new();
-
         if let Some((k, v)) = self.iter.next() {
             self.value = Some(v);
-            seed.deserialize(k.into_deserializer(&mut scratch))
+            seed.deserialize(k.into_deserializer(self.scratch.borrow_mut()))
                 .map(Some)
         } else {
             Ok(None)
@@ -82,7 +154,7 @@ where
             .value
             .take()
             .expect("Method next_value called before next_key");
-        seed.deserialize(value.into_deserializer(&mut self.scratch))
+        seed.deserialize(value.into_deserializer(self.scratch.borrow_mut()))
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -91,7 +163,12 @@ where
 }
 
 /// An enum used to choose the parsing method for deserialization
-#[derive(Clone, Copy)]
+///
+/// It can be loaded from a config file, as an externally tagged enum with snake case names and
+/// the delimiters as strings of a single ascii character, ex. `"brackets"`, `{"delimiter": "|"}`
+/// or `{"delimiter_map": {"entry": ",", "kv": ":"}}` in json.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum ParseMode {
     /// The simplest parser for querystring.
     /// It parses the whole querystring, and overwrites each repeated key’s value.
@@ -112,6 +189,13 @@ pub enum ParseMode {
     /// More description at ([DelimiterQs](crate::DelimiterQS))
     Delimiter(u8),
 
+    /// The `Delimiter` mode, which can also deserialize maps and structs from a single value.
+    /// (ex. `"key=a:1,b:2"` with `DelimiterMap { entry: b',', kv: b':' }`). Sequences are split by
+    /// the `entry` delimiter, like `Delimiter(entry)` does.
+    ///
+    /// More description at ([DelimiterQs::map_delimiter](crate::DelimiterQS::map_delimiter))
+    DelimiterMap { entry: u8, kv: u8 },
+
     /// A querystring parser with support for vectors/lists, maps and enums
     /// by the use of brackets(like qs or PHP).(ex. `key[2]=value2&key[1]=value1"`)
     ///
@@ -119,33 +203,805 @@ pub enum ParseMode {
     Brackets,
 }
 
-/// Deserialize an instance of type `T` from bytes of query string.
-pub fn from_bytes<'de, T>(input: &'de [u8], config: ParseMode) -> Result<T, Error>
+/// A querystring deserializer, parsing the input with the chosen `ParseMode` when deserializing.
+///
+/// It implements serde's `IntoDeserializer`, so it can be used with generic serde adapters.
+///
+/// # Example
+/// ```rust
+///# use std::collections::HashMap;
+/// use _serde::de::{value, Deserialize, Error, IntoDeserializer};
+/// use serde_querystring::de::{Deserializer, ParseMode};
+///
+/// fn load<'de, T, E, D>(input: D) -> Result<T, E>
+/// where
+///     T: Deserialize<'de>,
+///     E: Error,
+///     D: IntoDeserializer<'de, E>,
+/// {
+///     T::deserialize(input.into_deserializer())
+/// }
+///
+/// // Using serde's own value deserializers
+/// let map: HashMap<String, String> =
+///     load::<_, value::Error, _>(vec![(String::from("key"), String::from("value"))]
+///         .into_iter()
+///         .collect::<HashMap<_, _>>())
+///     .unwrap();
+///
+/// // Or a querystring
+/// let parsed: HashMap<String, String> =
+///     load(Deserializer::from_str("key=value", ParseMode::UrlEncoded)).unwrap();
+///
+/// assert_eq!(map, parsed);
+/// ```
+#[derive(Clone, Copy)]
+pub struct Deserializer<'de> {
+    input: &'de [u8],
+    mode: ParseMode,
+    sort_values: bool,
+    deny_mixed_values: bool,
+    trim_indices: bool,
+    max_depth: Option<usize>,
+    max_total_decoded_bytes: Option<usize>,
+    ignored_keys: &'de [&'de [u8]],
+    key_delimiters: &'de [(&'de [u8], u8)],
+    collapse_delimiters: bool,
+    delimiter_escape: Option<u8>,
+    case_insensitive_bool: bool,
+    bare_keys_as_some: bool,
+    empty_as_none: bool,
+    options: ParseOptions<'de>,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer from bytes of query string
+    pub fn from_bytes(input: &'de [u8], mode: ParseMode) -> Self {
+        Self {
+            input,
+            mode,
+            sort_values: false,
+            deny_mixed_values: false,
+            trim_indices: false,
+            max_depth: None,
+            max_total_decoded_bytes: None,
+            ignored_keys: &[],
+            key_delimiters: &[],
+            collapse_delimiters: false,
+            delimiter_escape: None,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            empty_as_none: false,
+            options: ParseOptions::new(),
+        }
+    }
+
+    /// Creates a deserializer from a query string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(input: &'de str, mode: ParseMode) -> Self {
+        Self::from_bytes(input.as_bytes(), mode)
+    }
+
+    /// Rejects a `%` which is not followed by two hex digits with an `InvalidEncoding` error,
+    /// instead of keeping it as is. It is disabled by default.
+    ///
+    /// # Note
+    /// All the keys are checked, but the values are checked as they're deserialized, so the
+    /// values of ignored or unknown fields are not rejected.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ErrorKind, ParseMode};
+    ///
+    /// let de = Deserializer::from_str("key=100%", ParseMode::UrlEncoded);
+    /// assert!(HashMap::<String, String>::deserialize(de).is_ok());
+    ///
+    /// let error = HashMap::<String, String>::deserialize(de.strict_percent_decoding(true))
+    ///     .unwrap_err();
+    /// assert_eq!(error.kind, ErrorKind::InvalidEncoding);
+    /// ```
+    pub fn strict_percent_decoding(mut self, strict: bool) -> Self {
+        self.options = self.options.strict_percent_decoding(strict);
+        self
+    }
+
+    /// Sorts the repeated values of each key in `Duplicate` mode, it has no effect in other modes.
+    ///
+    /// More description at ([DuplicateQS::sort_values](crate::DuplicateQS::sort_values))
+    pub fn sort_values(mut self, sort: bool) -> Self {
+        self.sort_values = sort;
+        self
+    }
+
+    /// Rejects keys which are assigned both direct values and sub keys in `Brackets` mode,
+    /// it has no effect in other modes.
+    ///
+    /// More description at ([BracketsQS::deny_mixed_values](crate::BracketsQS::deny_mixed_values))
+    pub fn deny_mixed_values(mut self, deny: bool) -> Self {
+        self.deny_mixed_values = deny;
+        self
+    }
+
+    /// Trims the whitespace around the indices of sequences in `Brackets` mode, it has no effect
+    /// in other modes.
+    ///
+    /// More description at ([BracketsQS::trim_indices](crate::BracketsQS::trim_indices))
+    pub fn trim_indices(mut self, enabled: bool) -> Self {
+        self.trim_indices = enabled;
+        self
+    }
+
+    /// Limits the nesting of sub keys in `Brackets` mode, it has no effect in other modes.
+    ///
+    /// More description at ([BracketsQS::max_depth](crate::BracketsQS::max_depth))
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ErrorKind, ParseMode};
+    ///
+    /// type Nested = HashMap<String, HashMap<String, HashMap<String, u32>>>;
+    ///
+    /// let de = Deserializer::from_str("a[b][c]=1", ParseMode::Brackets);
+    /// assert!(Nested::deserialize(de.max_depth(2)).is_ok());
+    ///
+    /// let error = Nested::deserialize(de.max_depth(1)).unwrap_err();
+    /// assert_eq!(error.kind, ErrorKind::LimitExceeded);
+    /// ```
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Limits the total number of pairs in the querystring, like PHP's `max_input_vars`,
+    /// returning a `LimitExceeded` error for querystrings with more pairs. There is no limit by default.
+    ///
+    /// More description at ([ParseOptions::max_input_vars](crate::ParseOptions::max_input_vars))
+    pub fn max_input_vars(mut self, limit: usize) -> Self {
+        self.options = self.options.max_input_vars(limit);
+        self
+    }
+
+    /// Limits the length of each key in bytes, returning a `LimitExceeded` error for
+    /// querystrings with a longer key. There is no limit by default.
+    ///
+    /// More description at ([ParseOptions::max_key_len](crate::ParseOptions::max_key_len))
+    pub fn max_key_len(mut self, limit: usize) -> Self {
+        self.options = self.options.max_key_len(limit);
+        self
+    }
+
+    /// Limits the total length of the keys and values after percent decoding, returning a
+    /// `LimitExceeded` error for querystrings decoding to more bytes. There is no limit by default.
+    ///
+    /// # Note
+    /// The keys and values are counted as they're decoded while deserializing, so the ignored
+    /// keys, the values of unknown fields and the values overwritten by a repeated key are not
+    /// counted. The separators, delimiters and brackets are not counted either.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ErrorKind, ParseMode};
+    ///
+    /// let de = Deserializer::from_str("a=%41%41&b=cd", ParseMode::UrlEncoded);
+    /// assert!(HashMap::<String, String>::deserialize(de.max_total_decoded_bytes(6)).is_ok());
+    ///
+    /// let error = HashMap::<String, String>::deserialize(de.max_total_decoded_bytes(5)).unwrap_err();
+    /// assert_eq!(error.kind, ErrorKind::LimitExceeded);
+    /// ```
+    pub fn max_total_decoded_bytes(mut self, limit: usize) -> Self {
+        self.max_total_decoded_bytes = Some(limit);
+        self
+    }
+
+    /// Drops the pairs with the given top-level keys before deserializing, so they don't reach
+    /// the deserialized type, even if it uses `deny_unknown_fields`.
+    ///
+    /// The keys are compared after percent decoding, nested keys are not affected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// #[derive(Deserialize)]
+    /// #[serde(crate = "_serde", deny_unknown_fields)]
+    /// struct Search {
+    ///     query: String,
+    /// }
+    ///
+    /// let de = Deserializer::from_str("query=rust&utm_source=news", ParseMode::UrlEncoded);
+    /// assert!(Search::deserialize(de).is_err());
+    ///
+    /// let search = Search::deserialize(de.ignore_keys(&[b"utm_source"])).unwrap();
+    /// assert_eq!(search.query, "rust");
+    /// ```
+    pub fn ignore_keys(mut self, keys: &'de [&'de [u8]]) -> Self {
+        self.ignored_keys = keys;
+        self
+    }
+
+    /// Uses a different delimiter for some keys in `Delimiter` mode, it has no effect in other modes.
+    ///
+    /// More description at ([DelimiterQS::key_delimiters](crate::DelimiterQS::key_delimiters))
+    pub fn key_delimiters(mut self, delimiters: &'de [(&'de [u8], u8)]) -> Self {
+        self.key_delimiters = delimiters;
+        self
+    }
+
+    /// Skips the empty values between consecutive delimiters in `Delimiter` mode, so `a,,b,` is
+    /// deserialized as `["a", "b"]`. It is disabled by default, and has no effect in other modes.
+    ///
+    /// More description at ([DelimiterQS::collapse_delimiters](crate::DelimiterQS::collapse_delimiters))
+    pub fn collapse_delimiters(mut self, enabled: bool) -> Self {
+        self.collapse_delimiters = enabled;
+        self
+    }
+
+    /// Uses `escape` to escape the delimiters inside the values in `Delimiter` mode, so
+    /// `a\,b,c` with `b'\\'` is deserialized as `["a,b", "c"]`. It is disabled by default, and has
+    /// no effect in other modes.
+    ///
+    /// More description at ([DelimiterQS::delimiter_escape](crate::DelimiterQS::delimiter_escape))
+    pub fn delimiter_escape(mut self, escape: u8) -> Self {
+        self.delimiter_escape = Some(escape);
+        self
+    }
+
+    /// Parses the boolean values ignoring their case, so `True`, `FALSE`, `On` and `oFF` are
+    /// accepted along with the lowercase words. It is disabled by default.
+    pub fn case_insensitive_bool(mut self, enabled: bool) -> Self {
+        self.case_insensitive_bool = enabled;
+        self
+    }
+
+    /// Deserializes the empty values of the optional fields as `None`, so `side=` means no
+    /// selection for an `Option<Side>` enum instead of failing with an unknown variant. It is
+    /// disabled by default, when an empty value is `Some` if the type accepts it.
+    ///
+    /// # Note
+    /// It applies to all the optional values, so an `Option<String>` is `None` for `name=` too.
+    ///
+    /// # Example
+    /// ```rust
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// enum Side {
+    ///     Left,
+    ///     Right,
+    /// }
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Filter {
+    ///     side: Option<Side>,
+    /// }
+    ///
+    /// let de = Deserializer::from_str("side=", ParseMode::Duplicate);
+    /// assert_eq!(Filter::deserialize(de.empty_as_none(true)), Ok(Filter { side: None }));
+    /// ```
+    pub fn empty_as_none(mut self, enabled: bool) -> Self {
+        self.empty_as_none = enabled;
+        self
+    }
+
+    /// Deserializes the keys without a value as `Some` of an empty value for the optional fields,
+    /// as if they're written like `verbose=`, instead of `None`. It is disabled by default.
+    ///
+    /// It's meant for bare flags, ex. `verbose` in `?verbose&page=2` is `Some(true)` for an
+    /// `Option<bool>`.
+    ///
+    /// # Note
+    /// The rule is the same for every type, so a bare key is `Some("")` for an `Option<String>`
+    /// and an `InvalidNumber` error for an `Option<u32>`, like an empty value is.
+    ///
+    /// # Example
+    /// ```rust
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// #[derive(Debug, PartialEq, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Flags {
+    ///     verbose: Option<bool>,
+    ///     quiet: Option<bool>,
+    /// }
+    ///
+    /// let de = Deserializer::from_str("verbose&page=2", ParseMode::UrlEncoded);
+    /// assert_eq!(
+    ///     Flags::deserialize(de.bare_keys_as_some(true)),
+    ///     Ok(Flags { verbose: Some(true), quiet: None })
+    /// );
+    /// ```
+    pub fn bare_keys_as_some(mut self, enabled: bool) -> Self {
+        self.bare_keys_as_some = enabled;
+        self
+    }
+
+    /// Uses another separator than `=` between the keys and the values in all the modes, ex.
+    /// `a:1&b:2` with `b':'` for legacy systems. It is `=` by default.
+    ///
+    /// More description at ([ParseOptions::kv_separator](crate::ParseOptions::kv_separator))
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// let de = Deserializer::from_str("a:1&b:x=y", ParseMode::UrlEncoded);
+    ///
+    /// let map = HashMap::<String, String>::deserialize(de.kv_separator(b':')).unwrap();
+    /// assert_eq!(map["a"], "1");
+    /// assert_eq!(map["b"], "x=y");
+    /// ```
+    pub fn kv_separator(mut self, separator: u8) -> Self {
+        self.options = self.options.kv_separator(separator);
+        self
+    }
+
+    /// Decodes a `+` in the keys as a space, like form encoding does. It is enabled by default,
+    /// when disabled `+` is kept as is and only `%20` is decoded as a space.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// let de = Deserializer::from_str("a+b=c+d", ParseMode::UrlEncoded);
+    ///
+    /// let map = HashMap::<String, String>::deserialize(de.plus_as_space_in_keys(false)).unwrap();
+    /// assert_eq!(map["a+b"], "c d");
+    /// ```
+    pub fn plus_as_space_in_keys(mut self, enabled: bool) -> Self {
+        self.options = self.options.plus_as_space_in_keys(enabled);
+        self
+    }
+
+    /// Decodes a `+` in the values as a space, like form encoding does. It is enabled by default,
+    /// when disabled `+` is kept as is and only `%20` is decoded as a space.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// let de = Deserializer::from_str("a+b=c+d", ParseMode::UrlEncoded);
+    ///
+    /// let map = HashMap::<String, String>::deserialize(de.plus_as_space_in_values(false)).unwrap();
+    /// assert_eq!(map["a b"], "c+d");
+    /// ```
+    pub fn plus_as_space_in_values(mut self, enabled: bool) -> Self {
+        self.options = self.options.plus_as_space_in_values(enabled);
+        self
+    }
+
+    /// Uses the given buffer for decoding the values which need to be unescaped, instead of
+    /// allocating a new one. The buffer can be reused for deserializing many querystrings,
+    /// its content is overwritten.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ParseMode};
+    ///
+    /// let mut scratch = Vec::new();
+    /// for input in ["a=x%20y", "a=x+%26+y"] {
+    ///     let de = Deserializer::from_str(input, ParseMode::UrlEncoded);
+    ///     let map = HashMap::<&str, String>::deserialize(de.with_scratch(&mut scratch)).unwrap();
+    ///     assert!(map["a"].starts_with("x "));
+    /// }
+    /// ```
+    pub fn with_scratch<'s>(self, scratch: &'s mut Vec<u8>) -> ScratchDeserializer<'de, 's> {
+        ScratchDeserializer { de: self, scratch }
+    }
+
+    /// Deserializes an instance of type `T`, collecting the errors of all the values instead of
+    /// stopping at the first one.
+    ///
+    /// A value which fails to parse is recorded and replaced with a placeholder, so the rest of
+    /// the querystring is still visited in the same pass. Errors which can't be replaced, like
+    /// a missing field or an unknown key rejected by `deny_unknown_fields`, stop the
+    /// deserialization and are reported after the ones collected before them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use _serde::Deserialize;
+    /// use serde_querystring::de::{Deserializer, ErrorKind, ParseMode};
+    ///
+    /// #[derive(Debug, Deserialize)]
+    ///# #[serde(crate = "_serde")]
+    /// struct Filter {
+    ///     page: u32,
+    ///     active: bool,
+    /// }
+    ///
+    /// let de = Deserializer::from_str("page=first&active=maybe", ParseMode::UrlEncoded);
+    ///
+    /// let errors = de.collect_errors::<Filter>().unwrap_err();
+    /// let kinds: Vec<_> = errors.into_iter().map(|error| error.kind).collect();
+    /// assert_eq!(kinds, [ErrorKind::InvalidNumber, ErrorKind::InvalidBoolean]);
+    /// ```
+    pub fn collect_errors<T>(self) -> Result<T, Vec<Error>>
+    where
+        T: de::Deserialize<'de>,
+    {
+        let mut errors = Vec::new();
+        let result = T::deserialize(CollectingDeserializer {
+            de: self,
+            errors: &mut errors,
+        });
+
+        match result {
+            Ok(value) if errors.is_empty() => Ok(value),
+            Ok(_) => Err(errors),
+            Err(error) => {
+                errors.push(error);
+                Err(errors)
+            }
+        }
+    }
+
+    fn split(self) -> (Self, Scratch) {
+        (self, Scratch::default())
+    }
+}
+
+/// A [Deserializer] using a borrowed scratch buffer, created by
+/// ([with_scratch](Deserializer::with_scratch))
+pub struct ScratchDeserializer<'de, 's> {
+    de: Deserializer<'de>,
+    scratch: &'s mut Vec<u8>,
+}
+
+impl<'de, 's> ScratchDeserializer<'de, 's> {
+    fn split(self) -> (Deserializer<'de>, BorrowedScratch<'s>) {
+        (self.de, BorrowedScratch::new(self.scratch))
+    }
+}
+
+/// A [Deserializer] recording the errors of the values in `errors`, created by
+/// ([collect_errors](Deserializer::collect_errors))
+struct CollectingDeserializer<'de, 'e> {
+    de: Deserializer<'de>,
+    errors: &'e mut Vec<Error>,
+}
+
+impl<'de, 'e> CollectingDeserializer<'de, 'e> {
+    fn split(self) -> (Deserializer<'de>, CollectingScratch<'e>) {
+        (self.de, CollectingScratch::new(self.errors))
+    }
+}
+
+/// Visits the tag of an adjacently tagged enum before its content, keeping the order of the
+/// other pairs.
+///
+/// Serde asks for an adjacently tagged enum as a struct of two fields, the tag and the content.
+/// The content would have to be buffered by serde if it's visited first, and couldn't be parsed
+/// from a string anymore, so it's held back until the tag is visited. Structs with any other
+/// number of fields are visited as they are, without looking at their keys.
+pub(crate) struct TagFirst<'f, I, T> {
+    iter: I,
+    // The tag and the content fields, until the tag is visited
+    fields: Option<(&'f str, &'f str)>,
+    held: Vec<T>,
+}
+
+impl<'f, I, T> TagFirst<'f, I, T> {
+    pub(crate) fn new(iter: I, fields: &'f [&'f str]) -> Self {
+        let fields = match fields {
+            [tag, content] => Some((*tag, *content)),
+            _ => None,
+        };
+
+        Self {
+            iter,
+            fields,
+            held: Vec::new(),
+        }
+    }
+}
+
+impl<'de, 'f, I, A> Iterator for TagFirst<'f, I, (__implementors::DecodedSlice<'de>, A)>
 where
-    T: de::Deserialize<'de>,
+    I: Iterator<Item = (__implementors::DecodedSlice<'de>, A)>,
 {
-    match config {
-        ParseMode::UrlEncoded => {
-            // A simple key=value parser
-            T::deserialize(QSDeserializer::new(UrlEncodedQS::parse(input).into_iter()))
-        }
-        ParseMode::Duplicate => {
-            // A parser with duplicated keys interpreted as sequence
-            T::deserialize(QSDeserializer::new(DuplicateQS::parse(input).into_iter()))
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((tag, content)) = self.fields {
+            for pair in self.iter.by_ref() {
+                let key = pair.0 .0.as_ref();
+                if key == content.as_bytes() {
+                    self.held.push(pair);
+                    continue;
+                }
+
+                if key == tag.as_bytes() {
+                    self.fields = None;
+                }
+                return Some(pair);
+            }
+            self.fields = None;
         }
-        ParseMode::Delimiter(s) => {
-            // A parser with sequences of values seperated by one character
-            T::deserialize(QSDeserializer::new(
-                DelimiterQS::parse(input, s).into_iter(),
-            ))
+
+        if self.held.is_empty() {
+            self.iter.next()
+        } else {
+            Some(self.held.remove(0))
         }
-        ParseMode::Brackets => {
-            // A PHP like interpretation of querystrings
-            T::deserialize(QSDeserializer::new(BracketsQS::parse(input).into_iter()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, upper) = self.iter.size_hint();
+        let held = self.held.len();
+        (lower + held, upper.map(|upper| upper + held))
+    }
+}
+
+/// Skips the pairs with the given keys
+struct SkipKeys<'k, I> {
+    iter: I,
+    keys: &'k [&'k [u8]],
+}
+
+impl<'de, 'k, I, T> Iterator for SkipKeys<'k, I>
+where
+    I: Iterator<Item = (__implementors::DecodedSlice<'de>, T)>,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let keys = self.keys;
+        self.iter
+            .find(|(key, _)| !keys.iter().any(|k| *k == key.0.as_ref()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.keys.is_empty() {
+            self.iter.size_hint()
+        } else {
+            (0, self.iter.size_hint().1)
         }
     }
 }
 
+macro_rules! forward_to_parser {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                let (de, mut scratch) = self.split();
+                let scratch: &mut Scratch = scratch.borrow_mut();
+                scratch.empty_as_none = de.empty_as_none;
+                scratch.strict_percent_decoding = de.options.strict_percent_decoding;
+                scratch.case_insensitive_bool = de.case_insensitive_bool;
+                scratch.bare_keys_as_some = de.bare_keys_as_some;
+                scratch.max_total_decoded_bytes = de.max_total_decoded_bytes;
+                let input = de.input;
+
+                match de.mode {
+                    ParseMode::UrlEncoded => {
+                        // A simple key=value parser
+                        let parser = UrlEncodedQS::parse_with(input, de.options);
+                        parser.check_truncated()?;
+
+                        let pairs = SkipKeys {
+                            iter: parser.into_iter(),
+                            keys: de.ignored_keys,
+                        };
+                        QSDeserializer::with_scratch(pairs, scratch).$method($($arg,)* visitor)
+                    }
+                    ParseMode::Duplicate => {
+                        // A parser with duplicated keys interpreted as sequence
+                        let parser = DuplicateQS::parse_with(input, de.options)
+                            .sort_values(de.sort_values);
+                        parser.check_truncated()?;
+
+                        let pairs = SkipKeys {
+                            iter: parser.into_iter(),
+                            keys: de.ignored_keys,
+                        };
+                        QSDeserializer::with_scratch(pairs, scratch).$method($($arg,)* visitor)
+                    }
+                    ParseMode::Delimiter(s) | ParseMode::DelimiterMap { entry: s, .. } => {
+                        // A parser with sequences of values seperated by one character
+                        let mut parser = DelimiterQS::parse_with(input, s, de.options)
+                            .key_delimiters(de.key_delimiters)
+                            .collapse_delimiters(de.collapse_delimiters);
+                        if let ParseMode::DelimiterMap { kv, .. } = de.mode {
+                            parser = parser.map_delimiter(kv);
+                        }
+                        if let Some(escape) = de.delimiter_escape {
+                            parser = parser.delimiter_escape(escape);
+                        }
+                        parser.check_truncated()?;
+
+                        let pairs = SkipKeys {
+                            iter: parser.into_iter(),
+                            keys: de.ignored_keys,
+                        };
+                        QSDeserializer::with_scratch(pairs, scratch).$method($($arg,)* visitor)
+                    }
+                    ParseMode::Brackets => {
+                        // A PHP like interpretation of querystrings
+                        let mut parsed = BracketsQS::parse_with(input, de.options)
+                            .deny_mixed_values(de.deny_mixed_values);
+                        if de.trim_indices {
+                            parsed = parsed.trim_indices(true);
+                        }
+                        if let Some(depth) = de.max_depth {
+                            parsed = parsed.max_depth(depth);
+                        }
+                        parsed.check_truncated()?;
+                        parsed.check_mixed_values()?;
+                        scratch.gap_budget = parsed.gap_budget();
+
+                        let pairs = SkipKeys {
+                            iter: parsed.into_iter(),
+                            keys: de.ignored_keys,
+                        };
+                        QSDeserializer::with_scratch(pairs, scratch).$method($($arg,)* visitor)
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    forward_to_parser! {
+        deserialize_any()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_option()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, 's> de::Deserializer<'de> for ScratchDeserializer<'de, 's> {
+    type Error = Error;
+
+    forward_to_parser! {
+        deserialize_any()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_option()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de, 'e> de::Deserializer<'de> for CollectingDeserializer<'de, 'e> {
+    type Error = Error;
+
+    forward_to_parser! {
+        deserialize_any()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_option()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+impl<'de> de::IntoDeserializer<'de, Error> for Deserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Deserialize an instance of type `T` from bytes of query string.
+pub fn from_bytes<'de, T>(input: &'de [u8], config: ParseMode) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let result = T::deserialize(Deserializer::from_bytes(input, config));
+
+    #[cfg(feature = "tracing")]
+    if let Err(error) = &result {
+        error.trace();
+    }
+
+    result
+}
+
+/// Deserialize bytes of query string with a `DeserializeSeed`, ex. to fill a buffer which is
+/// reused across calls instead of allocating a new value each time.
+///
+/// # Example
+/// ```rust
+/// use std::fmt;
+///
+/// use _serde::de::{DeserializeSeed, Deserializer, MapAccess, Visitor};
+/// use serde_querystring::{from_bytes_seed, ParseMode};
+///
+/// /// Collects the values into the buffer, keeping its allocation
+/// struct Values<'b>(&'b mut Vec<u32>);
+///
+/// impl<'de, 'b> DeserializeSeed<'de> for Values<'b> {
+///     type Value = ();
+///
+///     fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+///         deserializer.deserialize_map(self)
+///     }
+/// }
+///
+/// impl<'de, 'b> Visitor<'de> for Values<'b> {
+///     type Value = ();
+///
+///     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+///         formatter.write_str("a map of numbers")
+///     }
+///
+///     fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+///         self.0.clear();
+///         while let Some((_, value)) = map.next_entry::<&str, u32>()? {
+///             self.0.push(value);
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// let mut buffer = Vec::with_capacity(8);
+/// from_bytes_seed(b"a=1&b=2", ParseMode::UrlEncoded, Values(&mut buffer)).unwrap();
+/// assert_eq!(buffer, [1, 2]);
+///
+/// from_bytes_seed(b"c=3", ParseMode::UrlEncoded, Values(&mut buffer)).unwrap();
+/// assert_eq!(buffer, [3]);
+/// assert!(buffer.capacity() >= 8);
+/// ```
+pub fn from_bytes_seed<'de, S>(
+    input: &'de [u8],
+    config: ParseMode,
+    seed: S,
+) -> Result<S::Value, Error>
+where
+    S: de::DeserializeSeed<'de>,
+{
+    let result = seed.deserialize(Deserializer::from_bytes(input, config));
+
+    #[cfg(feature = "tracing")]
+    if let Err(error) = &result {
+        error.trace();
+    }
+
+    result
+}
+
 /// Deserialize an instance of type `T` from a query string.
 pub fn from_str<'de, T>(input: &'de str, config: ParseMode) -> Result<T, Error>
 where
@@ -153,3 +1009,186 @@ where
 {
     from_bytes(input.as_bytes(), config)
 }
+
+/// Deserialize an instance of type `T` from bytes of query string, returning the whole
+/// query string along with it.
+///
+/// More description at ([RawQuery](crate::de::RawQuery))
+pub fn from_bytes_with_raw<'de, T>(
+    input: &'de [u8],
+    config: ParseMode,
+) -> Result<(T, RawQuery<'de>), Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes(input, config).map(|value| (value, RawQuery::from(input)))
+}
+
+/// Deserialize an instance of type `T` from a query string, returning the whole query string
+/// along with it.
+///
+/// More description at ([RawQuery](crate::de::RawQuery))
+pub fn from_str_with_raw<'de, T>(
+    input: &'de str,
+    config: ParseMode,
+) -> Result<(T, RawQuery<'de>), Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes_with_raw(input.as_bytes(), config)
+}
+
+/// Deserialize an instance of type `T` from the bytes of query string before the first
+/// `boundary` byte, returning the rest of the input after the boundary along with it.
+///
+/// The rest is empty if there is no boundary in the input. Choose a boundary which can't
+/// appear in the querystring itself, a percent encoded boundary is not matched.
+pub fn from_bytes_partial<'de, T>(
+    input: &'de [u8],
+    config: ParseMode,
+    boundary: u8,
+) -> Result<(T, &'de [u8]), Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let (query, rest) = match input.iter().position(|b| *b == boundary) {
+        Some(index) => (&input[..index], &input[index + 1..]),
+        None => (input, &input[input.len()..]),
+    };
+
+    from_bytes(query, config).map(|value| (value, rest))
+}
+
+/// Deserialize an instance of type `T` from the query string before the first `boundary`
+/// character, returning the rest of the input after the boundary along with it.
+///
+/// More description at ([from_bytes_partial](crate::de::from_bytes_partial))
+///
+/// # Example
+/// ```rust
+///# use std::collections::HashMap;
+/// use serde_querystring::de::{from_str_partial, ParseMode};
+///
+/// let (map, rest): (HashMap<String, u32>, _) =
+///     from_str_partial("a=1&b=2|trailing", ParseMode::UrlEncoded, '|').unwrap();
+///
+/// assert_eq!(map, HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]));
+/// assert_eq!(rest, "trailing");
+/// ```
+pub fn from_str_partial<'de, T>(
+    input: &'de str,
+    config: ParseMode,
+    boundary: char,
+) -> Result<(T, &'de str), Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let (query, rest) = input.split_once(boundary).unwrap_or((input, ""));
+
+    from_str(query, config).map(|value| (value, rest))
+}
+
+/// Deserialize an instance of type `T` from the bytes of matrix parameters, ex. `a=1;b=2`.
+///
+/// Matrix parameters are a part of a path segment instead of the query, ex. `color=red;year=2012`
+/// in `/cars;color=red;year=2012`, and the leading `;` is optional. They are parsed like
+/// `UrlEncoded` mode, with `;` separating the pairs instead of `&`, and a `+` is not decoded
+/// as a space as it's a plain character in paths.
+///
+/// More description at ([UrlEncodedQS::parse_matrix](crate::UrlEncodedQS::parse_matrix))
+pub fn from_matrix_bytes<'de, T>(input: &'de [u8]) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    let result = UrlEncodedQS::parse_matrix(input).deserialize();
+
+    #[cfg(feature = "tracing")]
+    if let Err(error) = &result {
+        error.trace();
+    }
+
+    result
+}
+
+/// Deserialize an instance of type `T` from matrix parameters, ex. `a=1;b=2`.
+///
+/// More description at ([from_matrix_bytes](crate::de::from_matrix_bytes))
+///
+/// # Example
+/// ```rust
+/// use _serde::Deserialize;
+/// use serde_querystring::from_matrix_str;
+///
+/// #[derive(Debug, PartialEq, Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Car {
+///     color: String,
+///     year: u32,
+/// }
+///
+/// let segment = "cars;color=dark+red;year=2012";
+/// let (_, params) = segment.split_once(';').unwrap();
+///
+/// assert_eq!(
+///     from_matrix_str::<Car>(params),
+///     Ok(Car {
+///         color: "dark+red".to_string(),
+///         year: 2012
+///     })
+/// );
+/// ```
+pub fn from_matrix_str<'de, T>(input: &'de str) -> Result<T, Error>
+where
+    T: de::Deserialize<'de>,
+{
+    from_matrix_bytes(input.as_bytes())
+}
+
+/// Deserialize an instance of type `T` from a reader, ex. a form encoded request body.
+///
+/// The whole input is read into a buffer before deserializing, so `T` can't borrow from it.
+/// Errors while reading are returned with the `Io` kind.
+pub fn from_reader<R, T>(mut reader: R, config: ParseMode) -> Result<T, Error>
+where
+    R: std::io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut input = Vec::new();
+    reader
+        .read_to_end(&mut input)
+        .map_err(|error| Error::new(ErrorKind::Io).message(error.to_string()))?;
+
+    from_bytes(&input, config)
+}
+
+/// Deserialize an instance of type `T` from bytes of query string, collecting the errors
+/// of all the values instead of stopping at the first one.
+///
+/// More description at ([collect_errors](Deserializer::collect_errors))
+pub fn from_bytes_collect_errors<'de, T>(
+    input: &'de [u8],
+    config: ParseMode,
+) -> Result<T, Vec<Error>>
+where
+    T: de::Deserialize<'de>,
+{
+    let result = Deserializer::from_bytes(input, config).collect_errors();
+
+    #[cfg(feature = "tracing")]
+    if let Err(errors) = &result {
+        errors.iter().for_each(Error::trace);
+    }
+
+    result
+}
+
+/// Deserialize an instance of type `T` from a query string, collecting the errors
+/// of all the values instead of stopping at the first one.
+///
+/// More description at ([from_bytes_collect_errors](crate::de::from_bytes_collect_errors))
+pub fn from_str_collect_errors<'de, T>(input: &'de str, config: ParseMode) -> Result<T, Vec<Error>>
+where
+    T: de::Deserialize<'de>,
+{
+    from_bytes_collect_errors(input.as_bytes(), config)
+}
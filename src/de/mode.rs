@@ -0,0 +1,318 @@
+use std::{fmt, str::FromStr};
+
+use _serde::{
+    de::{self, Deserialize, Deserializer},
+    ser::{Serialize, SerializeStructVariant, Serializer},
+};
+
+use super::{Error, ErrorKind, ParseMode};
+
+const VARIANTS: &[&str] = &[
+    "urlencoded",
+    "duplicate",
+    "delimiter",
+    "delimiter_map",
+    "brackets",
+];
+const DELIMITER_MAP_FIELDS: &[&str] = &["entry", "kv"];
+
+/// A delimiter byte, represented as a string of one character if it's ascii, or as a number
+struct Delimiter(u8);
+
+impl Serialize for Delimiter {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        if self.0.is_ascii() {
+            serializer.serialize_char(self.0 as char)
+        } else {
+            serializer.serialize_u8(self.0)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Delimiter {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct DelimiterVisitor;
+
+        impl<'de> de::Visitor<'de> for DelimiterVisitor {
+            type Value = Delimiter;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a delimiter as a single ascii character or a byte")
+            }
+
+            fn visit_u64<E>(self, v: u64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                u8::try_from(v)
+                    .map(Delimiter)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_i64<E>(self, v: i64) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                u8::try_from(v)
+                    .map(Delimiter)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(v), &self))
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v.as_bytes() {
+                    [byte] => Ok(Delimiter(*byte)),
+                    _ => Err(E::invalid_value(de::Unexpected::Str(v), &self)),
+                }
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match v {
+                    [byte] => Ok(Delimiter(*byte)),
+                    _ => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_any(DelimiterVisitor)
+    }
+}
+
+/// Serializes the mode as an externally tagged enum, ex. `"brackets"` or `{"delimiter": "|"}`
+/// in json.
+impl Serialize for ParseMode {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match *self {
+            ParseMode::UrlEncoded => serializer.serialize_unit_variant("ParseMode", 0, VARIANTS[0]),
+            ParseMode::Duplicate => serializer.serialize_unit_variant("ParseMode", 1, VARIANTS[1]),
+            ParseMode::Delimiter(delimiter) => serializer.serialize_newtype_variant(
+                "ParseMode",
+                2,
+                VARIANTS[2],
+                &Delimiter(delimiter),
+            ),
+            ParseMode::DelimiterMap { entry, kv } => {
+                let mut variant =
+                    serializer.serialize_struct_variant("ParseMode", 3, VARIANTS[3], 2)?;
+                variant.serialize_field("entry", &Delimiter(entry))?;
+                variant.serialize_field("kv", &Delimiter(kv))?;
+                variant.end()
+            }
+            ParseMode::Brackets => serializer.serialize_unit_variant("ParseMode", 4, VARIANTS[4]),
+        }
+    }
+}
+
+enum Variant {
+    UrlEncoded,
+    Duplicate,
+    Delimiter,
+    DelimiterMap,
+    Brackets,
+}
+
+impl Variant {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "urlencoded" => Some(Variant::UrlEncoded),
+            "duplicate" => Some(Variant::Duplicate),
+            "delimiter" => Some(Variant::Delimiter),
+            "delimiter_map" => Some(Variant::DelimiterMap),
+            "brackets" => Some(Variant::Brackets),
+            _ => None,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VariantVisitor;
+
+        impl<'de> de::Visitor<'de> for VariantVisitor {
+            type Value = Variant;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a parse mode")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                Variant::from_name(v).ok_or_else(|| E::unknown_variant(v, VARIANTS))
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+            where
+                E: de::Error,
+            {
+                match std::str::from_utf8(v) {
+                    Ok(v) => self.visit_str(v),
+                    Err(_) => Err(E::invalid_value(de::Unexpected::Bytes(v), &self)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(VariantVisitor)
+    }
+}
+
+/// The fields of the `delimiter_map` variant, both are required
+struct DelimiterMapVisitor;
+
+impl<'de> de::Visitor<'de> for DelimiterMapVisitor {
+    type Value = ParseMode;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("the `entry` and `kv` delimiters")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut entry = None;
+        let mut kv = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            let field = match key.as_str() {
+                "entry" => &mut entry,
+                "kv" => &mut kv,
+                _ => return Err(de::Error::unknown_field(&key, DELIMITER_MAP_FIELDS)),
+            };
+            if field.is_some() {
+                return Err(de::Error::custom(format_args!("duplicate field `{}`", key)));
+            }
+            *field = Some(map.next_value::<Delimiter>()?.0);
+        }
+
+        Ok(ParseMode::DelimiterMap {
+            entry: entry.ok_or_else(|| de::Error::missing_field("entry"))?,
+            kv: kv.ok_or_else(|| de::Error::missing_field("kv"))?,
+        })
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let entry = seq
+            .next_element::<Delimiter>()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let kv = seq
+            .next_element::<Delimiter>()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+
+        Ok(ParseMode::DelimiterMap {
+            entry: entry.0,
+            kv: kv.0,
+        })
+    }
+}
+
+/// Deserializes the mode from an externally tagged enum, ex. `"brackets"` or
+/// `{"delimiter": "|"}` in json. The delimiters are strings of a single ascii character, or
+/// numbers for the other bytes.
+impl<'de> Deserialize<'de> for ParseMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ParseModeVisitor;
+
+        impl<'de> de::Visitor<'de> for ParseModeVisitor {
+            type Value = ParseMode;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a parse mode")
+            }
+
+            fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+            where
+                A: de::EnumAccess<'de>,
+            {
+                use de::VariantAccess;
+
+                let (variant, access) = data.variant::<Variant>()?;
+                match variant {
+                    Variant::UrlEncoded => access.unit_variant().map(|_| ParseMode::UrlEncoded),
+                    Variant::Duplicate => access.unit_variant().map(|_| ParseMode::Duplicate),
+                    Variant::Delimiter => access
+                        .newtype_variant::<Delimiter>()
+                        .map(|delimiter| ParseMode::Delimiter(delimiter.0)),
+                    Variant::DelimiterMap => {
+                        access.struct_variant(DELIMITER_MAP_FIELDS, DelimiterMapVisitor)
+                    }
+                    Variant::Brackets => access.unit_variant().map(|_| ParseMode::Brackets),
+                }
+            }
+        }
+
+        deserializer.deserialize_enum("ParseMode", VARIANTS, ParseModeVisitor)
+    }
+}
+
+/// Parses the mode from its name, the same as the deserialized one, with the delimiters after
+/// a colon, ex. `brackets`, `delimiter:|` or `delimiter_map:,:` for the entry and kv delimiters.
+/// It's meant for short settings like a header or a command line argument.
+///
+/// # Example
+/// ```rust
+/// use serde_querystring::de::ParseMode;
+///
+/// assert_eq!("brackets".parse(), Ok(ParseMode::Brackets));
+/// assert_eq!("delimiter:|".parse(), Ok(ParseMode::Delimiter(b'|')));
+/// assert_eq!(
+///     "delimiter_map:,:".parse(),
+///     Ok(ParseMode::DelimiterMap { entry: b',', kv: b':' })
+/// );
+/// assert!("delimiter".parse::<ParseMode>().is_err());
+/// ```
+impl FromStr for ParseMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, delimiters) = match s.find(':') {
+            Some(index) => (&s[..index], Some(&s.as_bytes()[index + 1..])),
+            None => (s, None),
+        };
+
+        let mode = match (Variant::from_name(name), delimiters) {
+            (Some(Variant::UrlEncoded), None) => Some(ParseMode::UrlEncoded),
+            (Some(Variant::Duplicate), None) => Some(ParseMode::Duplicate),
+            (Some(Variant::Brackets), None) => Some(ParseMode::Brackets),
+            (Some(Variant::Delimiter), Some([delimiter])) => Some(ParseMode::Delimiter(*delimiter)),
+            (Some(Variant::DelimiterMap), Some([entry, kv])) => Some(ParseMode::DelimiterMap {
+                entry: *entry,
+                kv: *kv,
+            }),
+            _ => None,
+        };
+
+        mode.ok_or_else(|| {
+            Error::new(ErrorKind::Other)
+                .message(format!(
+                    "unknown parse mode, expected one of {}, with the delimiters after a colon",
+                    VARIANTS.join(", ")
+                ))
+                .value(s.as_bytes())
+        })
+    }
+}
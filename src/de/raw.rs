@@ -0,0 +1,57 @@
+use std::str;
+
+use super::{Error, ErrorKind};
+
+/// The whole query string as it was given, before parsing and percent decoding.
+///
+/// It's returned by [from_bytes_with_raw](crate::de::from_bytes_with_raw) along with the
+/// deserialized value, for the query strings which are also an opaque payload as a whole, ex.
+/// a signed webhook call.
+///
+/// # Example
+/// ```rust
+/// use _serde::Deserialize;
+/// use serde_querystring::{from_str_with_raw, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Webhook {
+///     event: String,
+/// }
+///
+/// let (webhook, raw) =
+///     from_str_with_raw::<Webhook>("event=push&sig=a%2Bb", ParseMode::UrlEncoded).unwrap();
+/// assert_eq!(webhook.event, "push");
+/// assert_eq!(raw.as_bytes(), b"event=push&sig=a%2Bb");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawQuery<'de>(&'de [u8]);
+
+impl<'de> RawQuery<'de> {
+    /// Returns the bytes of the query string
+    pub fn as_bytes(&self) -> &'de [u8] {
+        self.0
+    }
+
+    /// Returns the query string as a `str`, or an `InvalidEncoding` error if it's not valid utf8
+    pub fn as_str(&self) -> Result<&'de str, Error> {
+        str::from_utf8(self.0).map_err(|error| {
+            Error::new(ErrorKind::InvalidEncoding)
+                .message(error.to_string())
+                .value(self.0)
+                .index(error.valid_up_to())
+        })
+    }
+}
+
+impl<'de> From<&'de [u8]> for RawQuery<'de> {
+    fn from(input: &'de [u8]) -> Self {
+        RawQuery(input)
+    }
+}
+
+impl<'de> AsRef<[u8]> for RawQuery<'de> {
+    fn as_ref(&self) -> &[u8] {
+        self.0
+    }
+}
@@ -0,0 +1,178 @@
+use std::borrow::{Borrow, BorrowMut};
+use std::ops::{Deref, DerefMut};
+
+use crate::decode::{parse_bytes, Reference};
+
+use super::{Error, ErrorKind};
+
+/// The buffer the values are decoded into, shared by all the deserializers of a querystring
+///
+/// It also collects the errors of the values when enabled, so the deserialization goes on with
+/// a placeholder value instead of stopping at the first invalid one.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    buf: Vec<u8>,
+    errors: Option<Vec<Error>>,
+    /// Whether the empty values are `None` for the optional values
+    pub(crate) empty_as_none: bool,
+    /// Whether a `%` not followed by two hex digits is rejected in the values
+    pub(crate) strict_percent_decoding: bool,
+    /// Whether the boolean words are parsed ignoring their case
+    pub(crate) case_insensitive_bool: bool,
+    /// Whether the keys without a value are `Some` of an empty value for the optional values
+    pub(crate) bare_keys_as_some: bool,
+    /// The limit of the total length of the decoded keys and values
+    pub(crate) max_total_decoded_bytes: Option<usize>,
+    decoded_bytes: usize,
+    /// The number of `None` elements the gaps of the sequences can still be filled with
+    pub(crate) gap_budget: usize,
+}
+
+impl Scratch {
+    /// Decodes a value into the buffer, checking its percent encoding in strict mode and
+    /// counting its length against `max_total_decoded_bytes`
+    pub(crate) fn decode<'de>(
+        &mut self,
+        slice: &'de [u8],
+        plus_as_space: bool,
+        escape: Option<u8>,
+    ) -> Result<Reference<'de, '_, [u8]>, Error> {
+        let strict = self.strict_percent_decoding;
+        let decoded = parse_bytes(slice, &mut self.buf, plus_as_space, escape, strict)
+            .map_err(|index| Error::invalid_percent(slice, index))?;
+
+        self.decoded_bytes = self.decoded_bytes.saturating_add(decoded.len());
+        check_decoded_bytes(self.decoded_bytes, self.max_total_decoded_bytes)?;
+        Ok(decoded)
+    }
+
+    /// Counts the length of a key or value used as is against `max_total_decoded_bytes`
+    pub(crate) fn count_decoded(&mut self, len: usize) -> Result<(), Error> {
+        self.decoded_bytes = self.decoded_bytes.saturating_add(len);
+        check_decoded_bytes(self.decoded_bytes, self.max_total_decoded_bytes)
+    }
+
+    /// Returns the value, or a placeholder if the error is collected
+    ///
+    /// Exceeding a limit is not collected, it stops the deserialization.
+    pub(crate) fn recover<T: Default>(&mut self, result: Result<T, Error>) -> Result<T, Error> {
+        match (result, &mut self.errors) {
+            (Err(error), Some(errors)) if error.kind != ErrorKind::LimitExceeded => {
+                errors.push(error);
+                Ok(T::default())
+            }
+            (result, _) => result,
+        }
+    }
+
+    /// The number of errors collected so far
+    pub(crate) fn collected(&self) -> usize {
+        self.errors.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Updates the errors collected since `start`
+    pub(crate) fn map_collected<F>(&mut self, start: usize, f: F)
+    where
+        F: Fn(Error) -> Error,
+    {
+        if let Some(errors) = &mut self.errors {
+            for error in &mut errors[start..] {
+                let taken = std::mem::replace(error, Error::new(ErrorKind::Other));
+                *error = f(taken);
+            }
+        }
+    }
+}
+
+fn check_decoded_bytes(total: usize, limit: Option<usize>) -> Result<(), Error> {
+    match limit {
+        Some(limit) if total > limit => Err(Error::new(ErrorKind::LimitExceeded).message(format!(
+            "the decoded keys and values are longer than {} bytes in total",
+            limit
+        ))),
+        _ => Ok(()),
+    }
+}
+
+impl Deref for Scratch {
+    type Target = Vec<u8>;
+
+    fn deref(&self) -> &Vec<u8> {
+        &self.buf
+    }
+}
+
+impl DerefMut for Scratch {
+    fn deref_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.buf
+    }
+}
+
+/// A scratch using a borrowed buffer, which is given back when it's dropped
+pub(crate) struct BorrowedScratch<'b> {
+    scratch: Scratch,
+    buf: &'b mut Vec<u8>,
+}
+
+impl<'b> BorrowedScratch<'b> {
+    pub(crate) fn new(buf: &'b mut Vec<u8>) -> Self {
+        Self {
+            scratch: Scratch {
+                buf: std::mem::take(buf),
+                ..Scratch::default()
+            },
+            buf,
+        }
+    }
+}
+
+impl<'b> Drop for BorrowedScratch<'b> {
+    fn drop(&mut self) {
+        *self.buf = std::mem::take(&mut self.scratch.buf);
+    }
+}
+
+/// A scratch collecting the errors of the values, which are moved to `errors` when it's dropped
+pub(crate) struct CollectingScratch<'e> {
+    scratch: Scratch,
+    errors: &'e mut Vec<Error>,
+}
+
+impl<'e> CollectingScratch<'e> {
+    pub(crate) fn new(errors: &'e mut Vec<Error>) -> Self {
+        Self {
+            scratch: Scratch {
+                errors: Some(Vec::new()),
+                ..Scratch::default()
+            },
+            errors,
+        }
+    }
+}
+
+impl<'e> Drop for CollectingScratch<'e> {
+    fn drop(&mut self) {
+        self.errors
+            .extend(self.scratch.errors.take().unwrap_or_default());
+    }
+}
+
+macro_rules! borrow_scratch {
+    ($($ty:ident)*) => {
+        $(
+            impl<'a> Borrow<Scratch> for $ty<'a> {
+                fn borrow(&self) -> &Scratch {
+                    &self.scratch
+                }
+            }
+
+            impl<'a> BorrowMut<Scratch> for $ty<'a> {
+                fn borrow_mut(&mut self) -> &mut Scratch {
+                    &mut self.scratch
+                }
+            }
+        )*
+    };
+}
+
+borrow_scratch! { BorrowedScratch CollectingScratch }
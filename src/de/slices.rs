@@ -7,13 +7,13 @@ use atoi::FromRadix10SignedChecked;
 use atoi::MaxNumDigits;
 use num_traits::{CheckedAdd, CheckedMul, CheckedSub, One, Zero};
 
-use crate::decode::parse_bytes;
 use crate::decode::Reference;
 
+use super::scratch::Scratch;
 use super::{Error, ErrorKind};
 
 pub trait Value<'de> {
-    fn parse_int<T>(&self, scratch: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_int<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: Zero
             + One
@@ -25,16 +25,22 @@ pub trait Value<'de> {
             + CheckedMul
             + MaxNumDigits;
 
-    fn parse_float<T>(&self, scratch: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_float<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: str::FromStr;
 
-    fn parse_bool(&self, scratch: &mut Vec<u8>) -> Result<bool, Error>;
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error>;
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]>;
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error>;
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error>;
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error>;
 
     fn is_none(&self) -> bool;
+    fn is_empty(&self) -> bool;
+
+    /// Whether the key is given without a value, ex. a bare `key` without `=`
+    fn is_bare(&self) -> bool {
+        false
+    }
 }
 
 #[inline]
@@ -46,6 +52,32 @@ fn invalid_boolean_error(slice: &[u8]) -> Error {
     )
 }
 
+/// Parses a boolean token, ignoring the case of the words if `case_insensitive` is set
+#[inline]
+fn parse_bool_token(slice: &[u8], case_insensitive: bool) -> Result<bool, Error> {
+    let is = |word: &[u8]| {
+        if case_insensitive {
+            slice.eq_ignore_ascii_case(word)
+        } else {
+            slice == word
+        }
+    };
+
+    match slice.len() {
+        0 => Ok(true),
+        1 => match slice[0] {
+            b'1' => Ok(true),
+            b'0' => Ok(false),
+            _ => Err(invalid_boolean_error(slice)),
+        },
+        2 if is(b"on") => Ok(true),
+        3 if is(b"off") => Ok(false),
+        4 if is(b"true") => Ok(true),
+        5 if is(b"false") => Ok(false),
+        _ => Err(invalid_boolean_error(slice)),
+    }
+}
+
 /// Holds a slice of bytes that is already percent decoded
 #[derive(Debug)]
 pub struct DecodedSlice<'de>(pub Cow<'de, [u8]>);
@@ -57,7 +89,7 @@ impl<'de> fmt::Display for DecodedSlice<'de> {
 }
 
 impl<'de> Value<'de> for DecodedSlice<'de> {
-    fn parse_int<T>(&self, _: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_int<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: Zero
             + One
@@ -69,10 +101,12 @@ impl<'de> Value<'de> for DecodedSlice<'de> {
             + CheckedMul
             + MaxNumDigits,
     {
-        if self.0.len() == 0 {
+        scratch.count_decoded(self.0.len())?;
+
+        if self.0.is_empty() {
             return Err(Error::new(ErrorKind::InvalidNumber)
                 .value(&self.0)
-                .message(format!("invalid index: the key has no value")));
+                .message("invalid index: the key has no value".to_string()));
         }
 
         let (value, len) = T::from_radix_10_signed_checked(&self.0);
@@ -81,14 +115,16 @@ impl<'de> Value<'de> for DecodedSlice<'de> {
             .ok_or_else(|| {
                 Error::new(ErrorKind::InvalidNumber)
                     .value(&self.0)
-                    .message(format!("invalid index: the key has non-numeric characters"))
+                    .message("invalid index: the key has non-numeric characters".to_string())
             })
     }
 
-    fn parse_float<T>(&self, _: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_float<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: str::FromStr,
     {
+        scratch.count_decoded(self.0.len())?;
+
         // TODO: Maybe just check is_ascii and use the unsafe version
         str::from_utf8(&self.0)
             .map_err(|_err| {
@@ -105,30 +141,24 @@ impl<'de> Value<'de> for DecodedSlice<'de> {
             })
     }
 
-    fn parse_bool(&self, _: &mut Vec<u8>) -> Result<bool, Error> {
-        match self.0.len() {
-            0 => Ok(true),
-            1 => match self.0[0] {
-                b'1' => Ok(true),
-                b'0' => Ok(false),
-                _ => Err(invalid_boolean_error(&self.0)),
-            },
-            2 if self.0.as_ref() == b"on" => Ok(true),
-            3 if self.0.as_ref() == b"off" => Ok(false),
-            4 if self.0.as_ref() == b"true" => Ok(true),
-            5 if self.0.as_ref() == b"false" => Ok(false),
-            _ => Err(invalid_boolean_error(&self.0)),
-        }
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
+        scratch.count_decoded(self.0.len())?;
+
+        parse_bool_token(&self.0, false)
     }
 
-    fn parse_bytes<'s>(self, _: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error> {
+        scratch.count_decoded(self.0.len())?;
+
         match self.0 {
-            Cow::Borrowed(b) => Reference::Borrowed(b),
-            Cow::Owned(o) => Reference::Owned(o),
+            Cow::Borrowed(b) => Ok(Reference::Borrowed(b)),
+            Cow::Owned(o) => Ok(Reference::Owned(o)),
         }
     }
 
-    fn parse_str<'s>(self, _: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
+        scratch.count_decoded(self.0.len())?;
+
         let res = match self.0 {
             Cow::Borrowed(b) => str::from_utf8(b)
                 .map(Reference::Borrowed)
@@ -149,20 +179,46 @@ impl<'de> Value<'de> for DecodedSlice<'de> {
     fn is_none(&self) -> bool {
         self.0.is_empty()
     }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
 }
 
-/// Holds a slice of bytes that is not percent decoded yet
+/// Holds a slice of bytes that is not percent decoded yet, along with how to decode it
 #[derive(Default, Clone, Copy)]
-pub struct RawSlice<'de>(pub &'de [u8]);
+pub struct RawSlice<'de> {
+    pub slice: &'de [u8],
+    /// Whether a `+` is decoded as a space
+    pub plus_as_space: bool,
+    /// The byte escaping the delimiters in the slice, if any
+    pub escape: Option<u8>,
+}
+
+impl<'de> RawSlice<'de> {
+    #[inline]
+    pub fn new(slice: &'de [u8], plus_as_space: bool) -> Self {
+        Self {
+            slice,
+            plus_as_space,
+            escape: None,
+        }
+    }
+
+    #[inline]
+    fn decode<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error> {
+        scratch.decode(self.slice, self.plus_as_space, self.escape)
+    }
+}
 
 impl<'de> fmt::Display for RawSlice<'de> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&String::from_utf8_lossy(self.0))
+        f.write_str(&String::from_utf8_lossy(self.slice))
     }
 }
 
 impl<'de> Value<'de> for RawSlice<'de> {
-    fn parse_int<T>(&self, _: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_int<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: Zero
             + One
@@ -174,66 +230,66 @@ impl<'de> Value<'de> for RawSlice<'de> {
             + CheckedMul
             + MaxNumDigits,
     {
-        if self.0.len() == 0 {
+        scratch.count_decoded(self.slice.len())?;
+
+        if self.slice.is_empty() {
             return Err(Error::new(ErrorKind::InvalidNumber)
-                .value(&self.0)
-                .message(format!("invalid index: the key has no value")));
+                .value(self.slice)
+                .message("invalid index: the key has no value".to_string()));
         }
 
-        let (value, len) = T::from_radix_10_signed_checked(&self.0);
+        let (value, len) = T::from_radix_10_signed_checked(self.slice);
         value
-            .and_then(|v| if len == self.0.len() { Some(v) } else { None })
+            .and_then(|v| {
+                if len == self.slice.len() {
+                    Some(v)
+                } else {
+                    None
+                }
+            })
             .ok_or_else(|| {
                 Error::new(ErrorKind::InvalidNumber)
-                    .value(self.0)
-                    .message(format!("invalid index: the key has non-numeric characters"))
+                    .value(self.slice)
+                    .message("invalid index: the key has non-numeric characters".to_string())
             })
     }
 
-    fn parse_float<T>(&self, _: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_float<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: str::FromStr,
     {
+        scratch.count_decoded(self.slice.len())?;
+
         // TODO: Maybe just check is_ascii and use the unsafe version
-        str::from_utf8(&self.0)
+        str::from_utf8(self.slice)
             .map_err(|_err| {
                 Error::new(ErrorKind::InvalidNumber)
-                    .value(&self.0)
+                    .value(self.slice)
                     .message("invalid index: the key has invalid characters".to_owned())
             })
             .and_then(|v| {
                 v.parse().map_err(|_err| {
                     Error::new(ErrorKind::InvalidNumber)
-                        .value(&self.0)
+                        .value(self.slice)
                         .message("invalid index: the key has non-numeric characters".to_owned())
                 })
             })
     }
 
-    fn parse_bool(&self, _: &mut Vec<u8>) -> Result<bool, Error> {
-        match self.0.len() {
-            0 => Ok(true),
-            1 => match self.0[0] {
-                b'1' => Ok(true),
-                b'0' => Ok(false),
-                _ => Err(invalid_boolean_error(self.0)),
-            },
-            2 if self.0 == b"on" => Ok(true),
-            3 if self.0 == b"off" => Ok(false),
-            4 if self.0 == b"true" => Ok(true),
-            5 if self.0 == b"false" => Ok(false),
-            _ => Err(invalid_boolean_error(self.0)),
-        }
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
+        scratch.count_decoded(self.slice.len())?;
+
+        parse_bool_token(self.slice, scratch.case_insensitive_bool)
     }
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error> {
+        self.decode(scratch)
     }
 
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
-        let slice = self.0;
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
+        let slice = self.slice;
 
-        parse_bytes(slice, scratch)
+        self.decode(scratch)?
             .try_map(str::from_utf8)
             .map_err(|error| {
                 Error::new(ErrorKind::InvalidEncoding)
@@ -246,12 +302,16 @@ impl<'de> Value<'de> for RawSlice<'de> {
     }
 
     fn is_none(&self) -> bool {
-        self.0.is_empty()
+        self.slice.is_empty()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.slice.is_empty()
     }
 }
 
 impl<'de> Value<'de> for Option<RawSlice<'de>> {
-    fn parse_int<T>(&self, scratch: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_int<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: Zero
             + One
@@ -266,26 +326,93 @@ impl<'de> Value<'de> for Option<RawSlice<'de>> {
         self.unwrap_or_default().parse_int(scratch)
     }
 
-    fn parse_float<T>(&self, scratch: &mut Vec<u8>) -> Result<T, Error>
+    fn parse_float<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
     where
         T: str::FromStr,
     {
         self.unwrap_or_default().parse_float(scratch)
     }
 
-    fn parse_bool(&self, scratch: &mut Vec<u8>) -> Result<bool, Error> {
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
         self.unwrap_or_default().parse_bool(scratch)
     }
 
-    fn parse_bytes<'s>(self, scratch: &'s mut Vec<u8>) -> Reference<'de, 's, [u8]> {
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error> {
         self.unwrap_or_default().parse_bytes(scratch)
     }
 
-    fn parse_str<'s>(self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>, Error> {
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
         self.unwrap_or_default().parse_str(scratch)
     }
 
     fn is_none(&self) -> bool {
         self.is_none()
     }
+
+    fn is_empty(&self) -> bool {
+        self.as_ref().map_or(true, |v| v.is_empty())
+    }
+
+    fn is_bare(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<'de> Value<'de> for Option<DecodedSlice<'de>> {
+    fn parse_int<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
+    where
+        T: Zero
+            + One
+            + AddAssign
+            + MulAssign
+            + SubAssign
+            + CheckedAdd
+            + CheckedSub
+            + CheckedMul
+            + MaxNumDigits,
+    {
+        match self {
+            Some(v) => v.parse_int(scratch),
+            None => DecodedSlice(Cow::Borrowed(&[])).parse_int(scratch),
+        }
+    }
+
+    fn parse_float<T>(&self, scratch: &mut Scratch) -> Result<T, Error>
+    where
+        T: str::FromStr,
+    {
+        match self {
+            Some(v) => v.parse_float(scratch),
+            None => DecodedSlice(Cow::Borrowed(&[])).parse_float(scratch),
+        }
+    }
+
+    fn parse_bool(&self, scratch: &mut Scratch) -> Result<bool, Error> {
+        match self {
+            Some(v) => v.parse_bool(scratch),
+            None => DecodedSlice(Cow::Borrowed(&[])).parse_bool(scratch),
+        }
+    }
+
+    fn parse_bytes<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, [u8]>, Error> {
+        self.unwrap_or(DecodedSlice(Cow::Borrowed(&[])))
+            .parse_bytes(scratch)
+    }
+
+    fn parse_str<'s>(self, scratch: &'s mut Scratch) -> Result<Reference<'de, 's, str>, Error> {
+        self.unwrap_or(DecodedSlice(Cow::Borrowed(&[])))
+            .parse_str(scratch)
+    }
+
+    fn is_none(&self) -> bool {
+        self.is_none()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.as_ref().map_or(true, |v| v.is_empty())
+    }
+
+    fn is_bare(&self) -> bool {
+        self.is_none()
+    }
 }
@@ -8,6 +8,7 @@ use crate::decode::Reference;
 
 use super::{
     error::{Error, ErrorKind},
+    scratch::Scratch,
     slices::{DecodedSlice, RawSlice, Value},
 };
 
@@ -16,7 +17,7 @@ pub trait IntoDeserializer<'de, 's> {
     type Deserializer: de::Deserializer<'de, Error = Error>;
 
     /// Convert this value into a deserializer.
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer;
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer;
 }
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
@@ -24,7 +25,7 @@ pub trait IntoDeserializer<'de, 's> {
 impl<'de, 's> IntoDeserializer<'de, 's> for DecodedSlice<'de> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
@@ -32,7 +33,7 @@ impl<'de, 's> IntoDeserializer<'de, 's> for DecodedSlice<'de> {
 impl<'de, 's> IntoDeserializer<'de, 's> for RawSlice<'de> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
@@ -40,7 +41,15 @@ impl<'de, 's> IntoDeserializer<'de, 's> for RawSlice<'de> {
 impl<'de, 's> IntoDeserializer<'de, 's> for Option<RawSlice<'de>> {
     type Deserializer = ValueDeserializer<'s, Self>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
+        ValueDeserializer(self, scratch)
+    }
+}
+
+impl<'de, 's> IntoDeserializer<'de, 's> for Option<DecodedSlice<'de>> {
+    type Deserializer = ValueDeserializer<'s, Self>;
+
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         ValueDeserializer(self, scratch)
     }
 }
@@ -77,7 +86,7 @@ macro_rules! deserialize_float {
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub struct ValueDeserializer<'s, T>(T, &'s mut Vec<u8>);
+pub struct ValueDeserializer<'s, T>(T, &'s mut Scratch);
 
 impl<'de, 's, T> ValueDeserializer<'s, T>
 where
@@ -94,17 +103,27 @@ where
             + CheckedAdd
             + CheckedSub
             + CheckedMul
-            + MaxNumDigits,
+            + MaxNumDigits
+            + Default,
     {
-        self.0.parse_int(self.1)
+        let result = self.0.parse_int(self.1);
+        self.1.recover(result)
     }
 
     #[inline(always)]
     fn parse_float<U>(self) -> Result<U, Error>
     where
-        U: std::str::FromStr,
+        U: std::str::FromStr + Default,
     {
-        self.0.parse_float(self.1)
+        let result = self.0.parse_float(self.1);
+        self.1.recover(result)
+    }
+
+    /// Whether the value is `None` when it's optional
+    #[inline]
+    fn is_none_option(&self) -> bool {
+        let bare_as_some = self.0.is_bare() && self.1.bare_keys_as_some;
+        (self.0.is_none() && !bare_as_some) || (self.0.is_empty() && self.1.empty_as_none)
     }
 }
 
@@ -119,10 +138,14 @@ where
     where
         V: de::Visitor<'de>,
     {
-        match self.0.parse_str(self.1)? {
-            Reference::Borrowed(b) => visitor.visit_borrowed_str(b),
-            Reference::Copied(o) => visitor.visit_str(o),
-            Reference::Owned(o) => visitor.visit_string(o),
+        match self.0.parse_str(self.1) {
+            Ok(Reference::Borrowed(b)) => visitor.visit_borrowed_str(b),
+            Ok(Reference::Copied(o)) => visitor.visit_str(o),
+            Ok(Reference::Owned(o)) => visitor.visit_string(o),
+            Err(error) => {
+                self.1.recover::<()>(Err(error))?;
+                visitor.visit_str("")
+            }
         }
     }
 
@@ -139,7 +162,8 @@ where
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_bool(self.0.parse_bool(self.1)?)
+        let result = self.0.parse_bool(self.1);
+        visitor.visit_bool(self.1.recover(result)?)
     }
 
     #[inline]
@@ -160,10 +184,11 @@ where
     where
         V: de::Visitor<'de>,
     {
-        if self.0.is_none() {
+        let empty = self.0.is_empty();
+        if self.is_none_option() {
             visitor.visit_none()
         } else {
-            visitor.visit_some(self)
+            visitor.visit_some(SomeDeserializer::new(self, empty))
         }
     }
 
@@ -182,9 +207,13 @@ where
         V: de::Visitor<'de>,
     {
         match self.0.parse_bytes(self.1) {
-            Reference::Borrowed(b) => visitor.visit_borrowed_bytes(b),
-            Reference::Copied(c) => visitor.visit_bytes(c),
-            Reference::Owned(o) => visitor.visit_byte_buf(o),
+            Ok(Reference::Borrowed(b)) => visitor.visit_borrowed_bytes(b),
+            Ok(Reference::Copied(c)) => visitor.visit_bytes(c),
+            Ok(Reference::Owned(o)) => visitor.visit_byte_buf(o),
+            Err(error) => {
+                self.1.recover::<()>(Err(error))?;
+                visitor.visit_bytes(&[])
+            }
         }
     }
 
@@ -244,13 +273,31 @@ where
 
 ///////////////////////////////////////////////////////////////////////////////////////////////////
 
-pub trait IntoRawSlices<'de> {
+pub trait IntoRawSlices<'de>: Sized {
     type SizedIterator: Iterator<Item = RawSlice<'de>>;
     type UnSizedIterator: Iterator<Item = RawSlice<'de>>;
+    type Entries: Iterator<Item = (RawSlice<'de>, RawSlice<'de>)>;
 
     fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, Error>;
     fn into_unsized_iterator(self) -> Self::UnSizedIterator;
     fn into_single_slice(self) -> RawSlice<'de>;
+
+    /// Splits the value into key-value entries to be visited as a map, if the mode supports it.
+    /// Otherwise the value is given back and deserialized as a single slice.
+    fn into_entries(self) -> Result<Self::Entries, Self>;
+
+    /// Whether the slice returned by `into_single_slice` would be empty
+    fn is_empty(&self) -> bool;
+
+    /// Whether the key is given without a value, ex. a bare `key` without `=`
+    fn is_bare(&self) -> bool {
+        false
+    }
+
+    /// Whether there are several values which should be visited as a sequence by `deserialize_any`
+    fn is_repeated(&self) -> bool {
+        false
+    }
 }
 
 impl<'de, 's, I> IntoDeserializer<'de, 's> for I
@@ -259,12 +306,26 @@ where
 {
     type Deserializer = IterDeserializer<'s, I>;
 
-    fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
         IterDeserializer(self, scratch)
     }
 }
 
-pub struct IterDeserializer<'s, I>(I, &'s mut Vec<u8>);
+macro_rules! forward_to_slice_deserializer {
+    ($($method:ident)*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.into_slice_deserializer().$method(visitor)
+            }
+        )*
+    };
+}
+
+pub struct IterDeserializer<'s, I>(I, &'s mut Scratch);
 
 impl<'de, 's, I> IterDeserializer<'s, I>
 where
@@ -280,22 +341,36 @@ where
             + CheckedAdd
             + CheckedSub
             + CheckedMul
-            + MaxNumDigits,
+            + MaxNumDigits
+            + Default,
     {
-        self.0.into_single_slice().parse_int(self.1)
+        self.into_slice_deserializer().parse_int()
     }
 
     fn parse_float<T>(self) -> Result<T, Error>
     where
-        T: std::str::FromStr,
+        T: std::str::FromStr + Default,
     {
-        self.0.into_single_slice().parse_float(self.1)
+        self.into_slice_deserializer().parse_float()
     }
 
     #[inline]
     fn into_slice_deserializer(self) -> ValueDeserializer<'s, RawSlice<'de>> {
         ValueDeserializer(self.0.into_single_slice(), self.1)
     }
+
+    /// The entries of the value as a map, or the value itself if it can't be split into entries
+    #[inline]
+    fn into_entries_deserializer(self) -> Result<EntriesDeserializer<'de, 's, I::Entries>, Self> {
+        match self.0.into_entries() {
+            Ok(entries) => Ok(EntriesDeserializer {
+                iter: entries,
+                value: None,
+                scratch: self.1,
+            }),
+            Err(values) => Err(Self(values, self.1)),
+        }
+    }
 }
 
 impl<'de, 's, I> de::Deserializer<'de> for IterDeserializer<'s, I>
@@ -309,7 +384,11 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.into_slice_deserializer().deserialize_any(visitor)
+        if self.0.is_repeated() {
+            self.deserialize_seq(visitor)
+        } else {
+            self.into_slice_deserializer().deserialize_any(visitor)
+        }
     }
 
     #[inline]
@@ -347,7 +426,13 @@ where
     where
         V: de::Visitor<'de>,
     {
-        visitor.visit_some(self)
+        let empty = self.0.is_empty();
+        let bare = self.0.is_bare() && !self.1.bare_keys_as_some;
+        if bare || (empty && self.1.empty_as_none) {
+            return visitor.visit_none();
+        }
+
+        visitor.visit_some(SomeDeserializer::new(self, empty))
     }
 
     #[inline]
@@ -382,6 +467,7 @@ where
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_unsized_iterator(),
             self.1,
+            0,
         ))
     }
 
@@ -392,6 +478,7 @@ where
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_sized_iterator(len)?,
             self.1,
+            0,
         ))
     }
 
@@ -407,12 +494,52 @@ where
         visitor.visit_seq(SizedIterDeserializer(
             self.0.into_sized_iterator(len)?,
             self.1,
+            0,
         ))
     }
 
-    forward_to_deserialize_any! {
-        <W: Visitor<'de>>
-        char str string unit unit_struct map struct identifier
+    #[inline]
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.into_slice_deserializer()
+            .deserialize_unit_struct(name, visitor)
+    }
+
+    #[inline]
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.into_entries_deserializer() {
+            Ok(entries) => visitor.visit_map(entries),
+            Err(de) => de.into_slice_deserializer().deserialize_map(visitor),
+        }
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.into_entries_deserializer() {
+            Ok(entries) => visitor.visit_map(entries),
+            Err(de) => de
+                .into_slice_deserializer()
+                .deserialize_struct(name, fields, visitor),
+        }
+    }
+
+    // Only `deserialize_any` visits repeated values as a sequence
+    forward_to_slice_deserializer! {
+        deserialize_char deserialize_str deserialize_string deserialize_unit
+        deserialize_identifier
     }
 
     deserialize_int!(
@@ -433,7 +560,47 @@ where
     );
 }
 
-struct SizedIterDeserializer<'s, I>(I, &'s mut Vec<u8>);
+/// Visits the key-value entries of a single value as a map
+pub struct EntriesDeserializer<'de, 's, I> {
+    iter: I,
+    value: Option<RawSlice<'de>>,
+    scratch: &'s mut Scratch,
+}
+
+impl<'de, 's, I> de::MapAccess<'de> for EntriesDeserializer<'de, 's, I>
+where
+    I: Iterator<Item = (RawSlice<'de>, RawSlice<'de>)>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer(self.scratch))
+                    .map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = self
+            .value
+            .take()
+            .expect("Method next_value called before next_key");
+        seed.deserialize(value.into_deserializer(self.scratch))
+    }
+}
+
+/// Deserializes a sequence of values, keeping the index of the next element for the errors
+struct SizedIterDeserializer<'s, I>(I, &'s mut Scratch, usize);
 
 impl<'de, 's, I> de::SeqAccess<'de> for SizedIterDeserializer<'s, I>
 where
@@ -445,13 +612,301 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
-        self.0
-            .next()
-            .map(|v| seed.deserialize(v.into_deserializer(self.1)))
-            .transpose()
+        let index = self.2;
+        self.2 += 1;
+
+        let v = match self.0.next() {
+            Some(v) => v,
+            None => return Ok(None),
+        };
+
+        let collected = self.1.collected();
+        let result = seed
+            .deserialize(ElementDeserializer {
+                de: v.into_deserializer(self.1),
+            })
+            .map_err(|error| error.element(index, v.slice));
+        self.1
+            .map_collected(collected, |error| error.element(index, v.slice));
+        result.map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        match self.0.size_hint() {
+            (lower, Some(upper)) if lower == upper => Some(upper),
+            _ => None,
+        }
     }
 }
 
+macro_rules! forward_to_inner {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            #[inline]
+            fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.de.$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+/// The deserializer given to `visit_some` for a present value
+///
+/// A nested option is deserialized as `None` if the value is empty, so `Option<Option<T>>`
+/// can tell apart a missing key(`None`), an empty value(`Some(None)`) and a value(`Some(Some(T))`).
+pub struct SomeDeserializer<D> {
+    de: D,
+    empty: bool,
+}
+
+impl<D> SomeDeserializer<D> {
+    pub fn new(de: D, empty: bool) -> Self {
+        Self { de, empty }
+    }
+}
+
+impl<'de, D> de::Deserializer<'de> for SomeDeserializer<D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.empty {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self.de)
+        }
+    }
+
+    forward_to_inner! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+/// The deserializer of the elements of a sequence of values
+///
+/// Each element is a single value, so nested structs and maps can't be expressed. They fail
+/// with a hint to use brackets mode instead of a plain type mismatch.
+struct ElementDeserializer<'de, 's> {
+    de: ValueDeserializer<'s, RawSlice<'de>>,
+}
+
+impl<'de, 's> ElementDeserializer<'de, 's> {
+    #[cold]
+    fn nested_error(&self) -> Error {
+        Error::new(ErrorKind::InvalidType)
+            .message(String::from(
+                "sequences of structs or maps are not supported in this mode, use brackets mode",
+            ))
+            .value(self.de.0.slice)
+    }
+}
+
+impl<'de, 's> de::Deserializer<'de> for ElementDeserializer<'de, 's> {
+    type Error = Error;
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.nested_error())
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.nested_error())
+    }
+
+    forward_to_inner! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
+/// The value of a key in a mode which doesn't repeat keys, like urlencoded mode
+pub struct SingleValue<T>(pub T);
+
+impl<'de, 's, T> IntoDeserializer<'de, 's> for SingleValue<T>
+where
+    T: Value<'de>,
+{
+    type Deserializer = SingleValueDeserializer<'s, T>;
+
+    fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
+        SingleValueDeserializer {
+            de: ValueDeserializer(self.0, scratch),
+        }
+    }
+}
+
+/// The deserializer of a single value
+///
+/// Sequences can't be expressed with a single value, they fail with a hint to use another mode
+/// instead of a plain type mismatch.
+pub struct SingleValueDeserializer<'s, T> {
+    de: ValueDeserializer<'s, T>,
+}
+
+impl<'s, T> SingleValueDeserializer<'s, T> {
+    #[cold]
+    fn sequence_error(&self) -> Error {
+        Error::new(ErrorKind::InvalidType).message(String::from(
+            "sequences are not supported in urlencoded mode, use duplicate, delimiter or brackets mode",
+        ))
+    }
+}
+
+impl<'de, 's, T> de::Deserializer<'de> for SingleValueDeserializer<'s, T>
+where
+    T: Value<'de>,
+{
+    type Error = Error;
+
+    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.sequence_error())
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.sequence_error())
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(self.sequence_error())
+    }
+
+    /// Keeps the hint for optional sequences
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let empty = self.de.0.is_empty();
+        if self.de.is_none_option() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(SomeDeserializer::new(self, empty))
+        }
+    }
+
+    forward_to_inner! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+///////////////////////////////////////////////////////////////////////////////////////////////////
+
 pub struct UnitOnly;
 
 impl<'de> de::VariantAccess<'de> for UnitOnly {
@@ -0,0 +1,89 @@
+use std::{borrow::Cow, fmt};
+
+use _serde::de::{self, Deserialize};
+
+/// A string which accepts invalid utf8, replacing the invalid sequences with `U+FFFD`, along
+/// with whether any replacement happened.
+///
+/// It's decoded from the percent decoded bytes of the value, so unlike `String` it doesn't fail
+/// on invalid utf8, ex. for logging the values sent by misbehaving clients.
+///
+/// # Example
+/// ```rust
+/// use _serde::Deserialize;
+/// use serde_querystring::{from_str, MaybeInvalidUtf8, ParseMode};
+///
+/// #[derive(Deserialize)]
+///# #[serde(crate = "_serde")]
+/// struct Event {
+///     name: MaybeInvalidUtf8,
+/// }
+///
+/// let event: Event = from_str("name=caf%C3%A9", ParseMode::UrlEncoded).unwrap();
+/// assert_eq!(event.name, MaybeInvalidUtf8("café".to_string(), false));
+///
+/// let event: Event = from_str("name=caf%E9", ParseMode::UrlEncoded).unwrap();
+/// assert_eq!(event.name, MaybeInvalidUtf8("caf\u{FFFD}".to_string(), true));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MaybeInvalidUtf8(pub String, pub bool);
+
+impl From<MaybeInvalidUtf8> for String {
+    fn from(value: MaybeInvalidUtf8) -> Self {
+        value.0
+    }
+}
+
+struct MaybeInvalidUtf8Visitor;
+
+impl<'de> de::Visitor<'de> for MaybeInvalidUtf8Visitor {
+    type Value = MaybeInvalidUtf8;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(MaybeInvalidUtf8(v.to_string(), false))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(MaybeInvalidUtf8(v, false))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        // A lossy conversion only allocates when something is replaced
+        Ok(match String::from_utf8_lossy(v) {
+            Cow::Borrowed(v) => MaybeInvalidUtf8(v.to_string(), false),
+            Cow::Owned(v) => MaybeInvalidUtf8(v, true),
+        })
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match String::from_utf8(v) {
+            Ok(v) => Ok(MaybeInvalidUtf8(v, false)),
+            Err(error) => self.visit_bytes(error.as_bytes()),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MaybeInvalidUtf8 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(MaybeInvalidUtf8Visitor)
+    }
+}
@@ -7,10 +7,20 @@ pub fn parse_char(h: u8, l: u8) -> Option<u8> {
 }
 
 /// Decodes a slice and return a Reference pointer
+///
+/// `+` is decoded as a space only if `plus_as_space` is true, otherwise it's kept as is. The byte
+/// after each `escape` is kept as is, without the escape, so it's not percent decoded nor used as
+/// a delimiter by the parsers. An `escape` at the end of the slice is kept as is.
+///
+/// A `%` which is not followed by two hex digits is kept as is, unless `strict` is true, then
+/// its index is returned as the error.
 pub fn parse_bytes<'de, 's>(
     slice: &'de [u8],
     scratch: &'s mut Vec<u8>,
-) -> Reference<'de, 's, [u8]> {
+    plus_as_space: bool,
+    escape: Option<u8>,
+    strict: bool,
+) -> Result<Reference<'de, 's, [u8]>, usize> {
     scratch.clear();
 
     // Index of the last byte we copied to scratch
@@ -21,33 +31,38 @@ pub fn parse_bytes<'de, 's>(
 
     while let Some(v) = slice.get(cursor) {
         match v {
-            b'+' => {
+            v if Some(*v) == escape && slice.len() > cursor + 1 => {
+                scratch.extend_from_slice(&slice[index..cursor]);
+                scratch.push(slice[cursor + 1]);
+
+                cursor += 2;
+                index = cursor;
+            }
+            b'+' if plus_as_space => {
                 scratch.extend_from_slice(&slice[index..cursor]);
                 scratch.push(b' ');
 
                 cursor += 1;
                 index = cursor;
             }
-            b'%' => {
-                // we saw percentage
-                if slice.len() > cursor + 2 {
-                    match parse_char(slice[cursor + 1], slice[cursor + 2]) {
-                        Some(b) => {
-                            scratch.extend_from_slice(&slice[index..cursor]);
-                            scratch.push(b);
-
-                            cursor += 3;
-                            index = cursor;
-                        }
-                        None => {
-                            // If it wasn't valid, go to the next byte
-                            cursor += 1;
-                        }
+            // we saw percentage
+            b'%' if slice.len() > cursor + 2 => {
+                match parse_char(slice[cursor + 1], slice[cursor + 2]) {
+                    Some(b) => {
+                        scratch.extend_from_slice(&slice[index..cursor]);
+                        scratch.push(b);
+
+                        cursor += 3;
+                        index = cursor;
+                    }
+                    None if strict => return Err(cursor),
+                    None => {
+                        // If it wasn't valid, go to the next byte
+                        cursor += 1;
                     }
-                } else {
-                    cursor += 1;
                 }
             }
+            b'%' if strict => return Err(cursor),
             _ => {
                 cursor += 1;
             }
@@ -55,10 +70,46 @@ pub fn parse_bytes<'de, 's>(
     }
 
     if scratch.is_empty() {
-        Reference::Borrowed(&slice[index..cursor])
+        Ok(Reference::Borrowed(&slice[index..cursor]))
     } else {
         scratch.extend_from_slice(&slice[index..cursor]);
-        Reference::Copied(scratch)
+        Ok(Reference::Copied(scratch))
+    }
+}
+
+/// Decodes a slice like `parse_bytes`, keeping an invalid `%` as is
+#[inline]
+pub fn parse_bytes_lenient<'de, 's>(
+    slice: &'de [u8],
+    scratch: &'s mut Vec<u8>,
+    plus_as_space: bool,
+    escape: Option<u8>,
+) -> Reference<'de, 's, [u8]> {
+    match parse_bytes(slice, scratch, plus_as_space, escape, false) {
+        Ok(decoded) => decoded,
+        Err(_) => unreachable!("only the strict decoding fails"),
+    }
+}
+
+/// Percent encodes a slice into `out`, the reverse of `parse_bytes`
+///
+/// Everything beside the unreserved characters of RFC 3986 is encoded, a space is written as
+/// `+` if `space_as_plus` is true, otherwise as `%20`.
+pub fn encode_bytes(slice: &[u8], out: &mut String, space_as_plus: bool) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+
+    for &c in slice {
+        match c {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(c as char)
+            }
+            b' ' if space_as_plus => out.push('+'),
+            _ => {
+                out.push('%');
+                out.push(HEX[(c >> 4) as usize] as char);
+                out.push(HEX[(c & 0xf) as usize] as char);
+            }
+        }
     }
 }
 
@@ -9,8 +9,26 @@ pub mod parsers;
 #[doc(hidden)]
 pub mod de;
 
-pub use parsers::{BracketsQS, DelimiterQS, DuplicateQS, UrlEncodedQS};
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod ser;
+
+pub use parsers::{
+    BorrowedPairsError, BracketsQS, DelimiterQS, DuplicateQS, ParseOptions, UrlEncodedQS,
+};
+
+#[cfg(feature = "serde")]
+#[doc(inline)]
+pub use de::{
+    field, from_bytes, from_bytes_collect_errors, from_bytes_partial, from_bytes_seed,
+    from_bytes_with_raw, from_matrix_bytes, from_matrix_str, from_reader, from_str,
+    from_str_collect_errors, from_str_partial, from_str_with_raw, Deserializer, Error, ErrorKind,
+    Iso8601Duration, MaybeInvalidUtf8, ParseMode, RawQuery,
+};
 
 #[cfg(feature = "serde")]
 #[doc(inline)]
-pub use de::{from_bytes, from_str, Error, ErrorKind, ParseMode};
+pub use ser::{
+    to_bytes, to_bytes_brackets, to_bytes_delimiter, to_bytes_duplicate, to_string,
+    to_string_brackets, to_string_delimiter, to_string_duplicate,
+};
@@ -1,6 +1,13 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
-use crate::decode::{parse_bytes, parse_char, Reference};
+use crate::decode::{parse_bytes, parse_bytes_lenient, parse_char, Reference};
+
+use super::{bound_key, fmt_summary, with_separators, ParseOptions, Separators, Stopped};
 
 /// A `Key` in brackets mode represents some state of a parsed key
 ///
@@ -13,16 +20,32 @@ use crate::decode::{parse_bytes, parse_char, Reference};
 /// For this pair `key[key1][key2]=value`, the first Key would be (`key`, `key1][key2]`).
 /// the first time we call the `sub_key` method we get (`key1`, `key2]`).
 /// and by calling `sub_key` again on the result we get (`key2`, None)
-#[derive(Clone, Copy)]
-struct Key<'a>(&'a [u8], Option<&'a [u8]>);
+///
+/// The third field is whether a `+` in the key is decoded as a space, and the fourth whether the
+/// whitespace around an index is trimmed, they're kept for the sub keys.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct Key<'a>(&'a [u8], Option<&'a [u8]>, bool, bool);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> (Self, usize) {
+    fn parse<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        kv: u8,
+        plus_as_space: bool,
+        max_len: usize,
+    ) -> (Self, usize) {
+        let slice = bound_key(slice, max_len);
         let mut index = 0;
         while index < slice.len() {
             match slice[index] {
                 b'[' => {
-                    let res = Key::parse_remains(&slice[..index], &slice[(index + 1)..]);
+                    let res = Key::parse_remains(
+                        &slice[..index],
+                        &slice[(index + 1)..],
+                        separators,
+                        kv,
+                        plus_as_space,
+                    );
                     return (res.0, res.1 + index + 1);
                 }
                 b'%' => {
@@ -30,29 +53,44 @@ impl<'a> Key<'a> {
                     if index + 2 < slice.len()
                         && parse_char(slice[index + 1], slice[index + 2]) == Some(b'[')
                     {
-                        let res = Key::parse_remains(&slice[..index], &slice[(index + 3)..]);
+                        let res = Key::parse_remains(
+                            &slice[..index],
+                            &slice[(index + 3)..],
+                            separators,
+                            kv,
+                            plus_as_space,
+                        );
                         return (res.0, res.1 + index + 3);
                     };
                     index += 1;
                 }
-                b'&' | b'=' => break,
+                c if c == kv || separators.matches(c) => break,
                 _ => index += 1,
             }
         }
 
-        (Self(&slice[..index], None), index)
+        (Self(&slice[..index], None, plus_as_space, false), index)
     }
 
-    fn parse_remains(key: &'a [u8], slice: &'a [u8]) -> (Self, usize) {
+    fn parse_remains<S: Separators>(
+        key: &'a [u8],
+        slice: &'a [u8],
+        separators: S,
+        kv: u8,
+        plus_as_space: bool,
+    ) -> (Self, usize) {
         let mut index = 0;
         while index < slice.len() {
             match slice[index] {
-                b'&' | b'=' => break,
+                c if c == kv || separators.matches(c) => break,
                 _ => index += 1,
             }
         }
 
-        (Self(key, Some(&slice[..index])), index)
+        (
+            Self(key, Some(&slice[..index]), plus_as_space, false),
+            index,
+        )
     }
 
     fn subkey(self) -> Option<Self> {
@@ -83,14 +121,24 @@ impl<'a> Key<'a> {
         }
 
         if index + 1 < remains.len() && remains[index + 1] == b'[' {
-            Some(Self(&remains[..key_end_index], Some(&remains[index + 2..])))
+            Some(Self(
+                &remains[..key_end_index],
+                Some(&remains[index + 2..]),
+                self.2,
+                self.3,
+            ))
         } else if index + 3 < remains.len()
             && remains[index + 1] == b'%'
             && parse_char(remains[index + 2], remains[index + 3]) == Some(b'[')
         {
-            Some(Self(&remains[..key_end_index], Some(&remains[index + 4..])))
+            Some(Self(
+                &remains[..key_end_index],
+                Some(&remains[index + 4..]),
+                self.2,
+                self.3,
+            ))
         } else {
-            Some(Self(&remains[..key_end_index], None))
+            Some(Self(&remains[..key_end_index], None, self.2, self.3))
         }
     }
 
@@ -127,35 +175,38 @@ impl<'a> Key<'a> {
     }
 
     fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+        parse_bytes_lenient(self.0, scratch, self.2, None)
     }
 }
 
-#[derive(Default, Clone, Copy)]
-struct Value<'a>(&'a [u8]);
+/// The second field is whether a `+` in the value is decoded as a space
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash)]
+struct Value<'a>(&'a [u8], bool);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> (Option<Self>, usize) {
+    fn parse<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        plus_as_space: bool,
+    ) -> (Option<Self>, usize) {
         match slice.first() {
-            Some(b'&') | None => {
-                return (None, 0);
-            }
-            _ => {}
+            Some(c) if !separators.matches(*c) => {}
+            _ => return (None, 0),
         }
 
         let mut index = 1;
         while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
+            if separators.matches(slice[index]) {
+                break;
             }
+            index += 1;
         }
 
-        (Some(Self(&slice[1..index])), index)
+        (Some(Self(&slice[1..index], plus_as_space)), index)
     }
 
     fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+        parse_bytes_lenient(self.0, scratch, self.1, None)
     }
 
     fn slice(&self) -> &'a [u8] {
@@ -163,20 +214,47 @@ impl<'a> Value<'a> {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
 struct Pair<'a>(Key<'a>, Option<Value<'a>>);
 
 impl<'a> Pair<'a> {
-    /// Parses a pair of key-value and return a `Pair` and a skip len
+    /// Parses a pair of key-value and return a `Pair` and a skip len, or why the parsing stops
     ///
     /// Unlike other parser methods, we directly return the `skip_len` here
     /// since there are many exceptions to take into account in this method
     /// and it helps avoid some recalculations.
-    fn parse(slice: &'a [u8]) -> (Self, usize) {
-        let (key, key_len) = Key::parse(slice);
-        let (value, value_len) = Value::parse(&slice[key_len..]);
+    fn parse<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        options: ParseOptions<'_>,
+        scratch: &mut Vec<u8>,
+    ) -> (Result<Self, Stopped<'a>>, usize) {
+        let (key, key_len) = Key::parse(
+            slice,
+            separators,
+            options.kv_separator,
+            options.plus_as_space_in_keys,
+            options.max_key_len,
+        );
+        if key_len > options.max_key_len {
+            return (
+                Err(Stopped::KeyLen(options.max_key_len, &slice[..key_len])),
+                key_len,
+            );
+        }
+        // The sub keys are decoded lazily, so the whole key is checked here
+        if options.strict_percent_decoding {
+            if let Err(at) = parse_bytes(&slice[..key_len], scratch, false, None, true) {
+                return (Err(Stopped::InvalidKey(&slice[..key_len], at)), key_len);
+            }
+        }
+        let (value, value_len) = Value::parse(
+            &slice[key_len..],
+            separators,
+            options.plus_as_space_in_values,
+        );
 
-        (Self(key, value), key_len + value_len + 1)
+        (Ok(Self(key, value)), key_len + value_len + 1)
     }
 
     fn new(k: Key<'a>, v: Option<Value<'a>>) -> Pair<'a> {
@@ -193,6 +271,17 @@ impl<'a> Pair<'a> {
 /// Sub keys/Sub values(The part of the key after bracket opening) is visited when calling the `sub_values`
 /// method, to limit unnecessary allocations and parsing(and stack overflows from too many levels).
 ///
+/// A key can be assigned both direct values and sub keys, ex `foo=bar&foo[bar]=baz`, and both are
+/// kept. `values` and `value` only return the direct values and `sub_values` only the sub keys.
+/// When deserializing, maps and structs only use the sub keys, sequences use the direct values
+/// as if they had the index 0, and other values use the last assignment whether it has sub keys
+/// or not. Use `deny_mixed_values` to reject such querystrings instead.
+///
+/// Two parsers are equal when they have the same keys, each assigned the same values and sub keys
+/// in the same order. The order of different keys doesn't matter, and pairs are compared as they
+/// appear in the querystring, so `key=%41` and `key=A` are not equal. The options are not
+/// compared, as they only change how the pairs are decoded and deserialized.
+///
 /// # Example
 /// ```rust
 ///# use std::borrow::Cow;
@@ -227,53 +316,219 @@ impl<'a> Pair<'a> {
 /// ```
 pub struct BracketsQS<'a> {
     pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    deny_mixed_values: bool,
+    max_depth: usize,
+    #[cfg(feature = "serde")]
+    strict_percent_decoding: bool,
+    case_insensitive_bool: bool,
+    bare_keys_as_some: bool,
+    stopped: Option<Stopped<'a>>,
 }
 
+/// The default maximum nesting of sub keys, ex `a[b][c]` has a depth of 2
+const MAX_DEPTH: usize = 128;
+
 impl<'a> BracketsQS<'a> {
     /// Parse a slice of bytes into a `BracketsQS`
     pub fn parse(slice: &'a [u8]) -> Self {
-        let mut pairs: BTreeMap<_, Vec<Pair<'a>>> = BTreeMap::new();
-        let mut scratch = Vec::new();
+        Self::parse_with(slice, ParseOptions::new())
+    }
 
-        let mut index = 0;
+    /// Parse a slice of bytes into a `BracketsQS`, choosing whether a `+` is decoded as a space
+    /// in keys and in values separately.
+    ///
+    /// `parse` decodes it as a space in both, like form encoding does. Otherwise `+` is kept
+    /// as is, and only `%20` is decoded as a space.
+    pub fn parse_with_plus_as_space(slice: &'a [u8], keys: bool, values: bool) -> Self {
+        let options = ParseOptions::new()
+            .plus_as_space_in_keys(keys)
+            .plus_as_space_in_values(values);
+        Self::parse_with(slice, options)
+    }
 
-        while index < slice.len() {
-            let (pair, pair_len) = Pair::parse(&slice[index..]);
-            index += pair_len;
+    /// Parse a slice of bytes into a `BracketsQS` with the given options.
+    ///
+    /// More description at ([UrlEncodedQS::parse_with](crate::UrlEncodedQS::parse_with))
+    pub fn parse_with(slice: &'a [u8], options: ParseOptions<'_>) -> Self {
+        with_separators!(options, |separators| Self::parse_separated(
+            slice, separators, options
+        ))
+    }
 
-            let decoded_key = pair.0.decode(&mut scratch);
+    fn parse_separated<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        options: ParseOptions<'_>,
+    ) -> Self {
+        let mut pairs = BTreeMap::new();
+        let mut stopped = None;
+        let mut count = 0;
+        let mut scratch = Vec::new();
 
-            if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
-                values.push(pair);
+        let mut index = 0;
+        let iter = std::iter::from_fn(|| {
+            if index < slice.len() && stopped.is_none() {
+                let (pair, pair_len) =
+                    Pair::parse(&slice[index..], separators, options, &mut scratch);
+                let pair = match pair {
+                    Ok(pair) => pair,
+                    Err(reason) => {
+                        stopped = Some(reason);
+                        return None;
+                    }
+                };
+                // Empty parts like `&&` are not counted
+                if pair_len > 1 {
+                    count += 1;
+                    if count > options.max_input_vars {
+                        stopped = Some(Stopped::InputVars(options.max_input_vars));
+                        return None;
+                    }
+                }
+                index += pair_len;
+                Some(pair)
             } else {
-                pairs.insert(decoded_key.into_cow(), vec![pair]);
+                None
             }
+        });
+        Self::group_pairs(&mut pairs, iter, &mut Vec::new());
+
+        Self {
+            pairs,
+            deny_mixed_values: false,
+            max_depth: MAX_DEPTH,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: options.strict_percent_decoding,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            stopped,
+        }
+    }
+
+    /// Whether the parsing stopped before the end of the slice, for a limit of the options or
+    /// an invalid key with `strict_percent_decoding`. The pairs after it are not parsed and
+    /// deserializing fails.
+    pub fn is_truncated(&self) -> bool {
+        self.stopped.is_some()
+    }
+
+    /// Rejects keys which are assigned both direct values and sub keys when deserializing,
+    /// ex `foo=bar&foo[bar]=baz`, at any level of nesting.
+    ///
+    /// It is disabled by default.
+    pub fn deny_mixed_values(mut self, deny: bool) -> Self {
+        self.deny_mixed_values = deny;
+        self
+    }
+
+    /// Limits the nesting of sub keys when deserializing, ex `a[b][c]` has a depth of 2, deeper
+    /// keys are a `LimitExceeded` error. It bounds the recursion of the deserializer, so a
+    /// hostile querystring can't overflow the stack.
+    ///
+    /// It is 128 by default.
+    ///
+    /// # Note
+    /// Sequences count as a level too, and very large limits defeat the purpose of the check.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Parses the boolean values ignoring their case when deserializing, so `True`, `FALSE`,
+    /// `On` and `oFF` are accepted too. It is disabled by default.
+    pub fn case_insensitive_bool(mut self, enabled: bool) -> Self {
+        self.case_insensitive_bool = enabled;
+        self
+    }
+
+    /// Trims the whitespace around the indices of sequences when deserializing, so the
+    /// hand-written `value[ 1 ]=x` is read like `value[1]=x`. The indices are percent decoded
+    /// first, so `value[%201]` is trimmed too. It is disabled by default.
+    pub fn trim_indices(mut self, enabled: bool) -> Self {
+        for pair in self.pairs.values_mut().flatten() {
+            (pair.0).3 = enabled;
         }
+        self
+    }
 
-        Self { pairs }
+    /// Deserializes the keys without a value, ex. `verbose` in `verbose&page=2`, as `Some` of an
+    /// empty value for the optional fields, instead of `None`. It is disabled by default.
+    ///
+    /// The rule is the same for every type, so a bare key is `Some(true)` for an `Option<bool>`
+    /// but an `InvalidNumber` error for an `Option<u32>`, like an empty value is.
+    pub fn bare_keys_as_some(mut self, enabled: bool) -> Self {
+        self.bare_keys_as_some = enabled;
+        self
     }
 
     fn from_pairs<I>(iter: I) -> Self
     where
         I: Iterator<Item = Pair<'a>>,
     {
-        let mut pairs: BTreeMap<_, Vec<Pair<'a>>> = BTreeMap::new();
+        let mut pairs = BTreeMap::new();
+        Self::group_subkeys(&mut pairs, iter, &mut Vec::new());
+
+        Self {
+            pairs,
+            deny_mixed_values: false,
+            max_depth: MAX_DEPTH,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: false,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            stopped: None,
+        }
+    }
 
-        let mut scratch = Vec::new();
-        let subpairs = iter.filter_map(|p| Some((p.0.subkey()?, p.1)));
+    /// Adds the pairs with a sub key to the groups of their decoded sub key
+    fn group_subkeys<I>(
+        groups: &mut BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+        iter: I,
+        scratch: &mut Vec<u8>,
+    ) where
+        I: Iterator<Item = Pair<'a>>,
+    {
+        let subpairs = iter.filter_map(|p| Some(Pair::new(p.0.subkey()?, p.1)));
+        Self::group_pairs(groups, subpairs, scratch);
+    }
 
-        for (k, v) in subpairs {
-            let decoded_key = k.decode(&mut scratch);
-            let pair = Pair::new(k, v);
+    /// Adds the pairs to the groups of their decoded key, keeping their order.
+    ///
+    /// Consecutive pairs with the same key, like the fields of a nested struct, are added as a
+    /// run, so their key is decoded and looked up once for the run instead of once per pair.
+    fn group_pairs<I>(
+        groups: &mut BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+        iter: I,
+        scratch: &mut Vec<u8>,
+    ) where
+        I: Iterator<Item = Pair<'a>>,
+    {
+        let mut run: Vec<Pair<'a>> = Vec::new();
 
-            if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
-                values.push(pair);
-            } else {
-                pairs.insert(decoded_key.into_cow(), vec![pair]);
+        for pair in iter {
+            if run.last().map_or(false, |last| last.0 .0 != pair.0 .0) {
+                Self::insert_run(groups, &mut run, scratch);
             }
+            run.push(pair);
         }
 
-        Self { pairs }
+        if !run.is_empty() {
+            Self::insert_run(groups, &mut run, scratch);
+        }
+    }
+
+    fn insert_run(
+        groups: &mut BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+        run: &mut Vec<Pair<'a>>,
+        scratch: &mut Vec<u8>,
+    ) {
+        let decoded_key = run[0].0.decode(scratch);
+
+        if let Some(values) = groups.get_mut(decoded_key.as_ref()) {
+            values.append(run);
+        } else {
+            groups.insert(decoded_key.into_cow(), std::mem::take(run));
+        }
     }
 
     /// Returns a vector containing all the keys in querystring.
@@ -282,7 +537,7 @@ impl<'a> BracketsQS<'a> {
     }
 
     /// Parses all the subkeys for this key and optionally returns a new `BracketsQS` if the key exists
-    pub fn sub_values(&self, key: &'a [u8]) -> Option<BracketsQS> {
+    pub fn sub_values(&self, key: &'a [u8]) -> Option<BracketsQS<'_>> {
         Some(Self::from_pairs(self.pairs.get(key)?.iter().copied()))
     }
 
@@ -319,77 +574,439 @@ impl<'a> BracketsQS<'a> {
         self.pairs
             .get(key)?
             .iter()
-            .filter(|p| !p.0.has_subkey())
-            .last()
+            .rfind(|p| !p.0.has_subkey())
             .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
     }
+
+    /// Returns the last direct value, as owned bytes.
+    ///
+    /// It works like the `value` method, but the result doesn't borrow from the input
+    /// so it can be stored independently.
+    pub fn value_owned(&self, key: &'a [u8]) -> Option<Option<Vec<u8>>> {
+        self.value(key).map(|v| v.map(Cow::into_owned))
+    }
+
+    /// Returns the last direct value assigned to a key, without percent decoding it.
+    ///
+    /// It works like the `value` method, but returns the bytes exactly as they appear in the
+    /// querystring, ex for signing or caching.
+    pub fn raw_value(&self, key: &'a [u8]) -> Option<Option<&'a [u8]>> {
+        self.pairs
+            .get(key)?
+            .iter()
+            .rfind(|p| !p.0.has_subkey())
+            .map(|p| p.1.as_ref().map(Value::slice))
+    }
+
+    /// Returns the number of direct values assigned to a key, ignoring the ones with subkeys.
+    ///
+    /// None of the values are decoded, so it is a cheap way to validate the input before
+    /// deserializing it. Returns `None` if the key doesn't exist at all.
+    pub fn value_count(&self, key: &[u8]) -> Option<usize> {
+        Some(
+            self.pairs
+                .get(key)?
+                .iter()
+                .filter(|p| !p.0.has_subkey())
+                .count(),
+        )
+    }
+}
+
+impl<'a> PartialEq for BracketsQS<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl<'a> Eq for BracketsQS<'a> {}
+
+impl<'a> Hash for BracketsQS<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pairs.hash(state);
+    }
+}
+
+impl<'a> fmt::Display for BracketsQS<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(f, "BracketsQS", self.pairs.keys())
+    }
 }
 
 #[cfg(feature = "serde")]
 mod de {
+    use std::{
+        borrow::Cow,
+        collections::{btree_map, BTreeMap, HashSet},
+    };
+
     use _serde::{de, forward_to_deserialize_any, Deserialize, Deserializer};
     use atoi::FromRadix10Checked;
 
     use crate::de::{
-        Error, ErrorKind, QSDeserializer,
-        __implementors::{DecodedSlice, IntoDeserializer, RawSlice},
+        __implementors::{DecodedSlice, IntoDeserializer, RawSlice, Scratch, SomeDeserializer},
+        Error, ErrorKind, QSDeserializer, TagFirst,
     };
+    use crate::decode::Reference;
+
+    use super::{BracketsQS, Key, Pair, Value};
+
+    /// The pairs of a key, and the maximum depth of their sub keys
+    pub struct Pairs<'a>(Vec<Pair<'a>>, usize);
+
+    /// The remaining depth of sub keys, along with the maximum to report it in errors.
+    /// Deeper keys are rejected to bound the recursion of the deserializer.
+    #[derive(Clone, Copy)]
+    struct Depth {
+        remaining: usize,
+        max: usize,
+    }
 
-    use super::{BracketsQS, Pair};
+    impl Depth {
+        fn new(max: usize) -> Self {
+            Self {
+                remaining: max,
+                max,
+            }
+        }
+
+        /// The depth left for the sub keys, one level deeper
+        #[inline]
+        fn sub(self) -> Result<Self, Error> {
+            match self.remaining.checked_sub(1) {
+                Some(remaining) => Ok(Self { remaining, ..self }),
+                None => Err(depth_error(self.max)),
+            }
+        }
+    }
+
+    #[cold]
+    fn depth_error(max: usize) -> Error {
+        Error::new(ErrorKind::LimitExceeded)
+            .message(format!("maximum depth of {} sub keys reached", max))
+    }
+
+    /// An error for the index of a sequence which is not a decimal number fitting a `usize`,
+    /// naming the key as it's written in the querystring
+    #[cold]
+    fn index_error(key: Key, index: Key, digits: bool) -> Error {
+        let reason = if digits {
+            "the index is too large"
+        } else {
+            "the index should only have decimal digits"
+        };
+
+        let mut value = key.0.to_vec();
+        value.push(b'[');
+        value.extend_from_slice(index.0);
+        value.push(b']');
+
+        Error::new(ErrorKind::InvalidNumber)
+            .message(format!(
+                "invalid index `{}` of the key `{}`: {}",
+                String::from_utf8_lossy(index.0),
+                String::from_utf8_lossy(key.0),
+                reason
+            ))
+            .value(&value)
+    }
+
+    impl<'a> Key<'a> {
+        /// Decodes the key as an index, without the whitespace around it if trimming is enabled
+        fn index<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
+            if !self.3 {
+                return Reference::Borrowed(self.0);
+            }
+
+            match self.decode(scratch) {
+                Reference::Borrowed(b) => Reference::Borrowed(trim_whitespace(b)),
+                Reference::Copied(c) => Reference::Copied(trim_whitespace(c)),
+                Reference::Owned(o) => Reference::Owned(trim_whitespace(&o).to_vec()),
+            }
+        }
+    }
+
+    fn trim_whitespace(mut slice: &[u8]) -> &[u8] {
+        while let [first, rest @ ..] = slice {
+            if !first.is_ascii_whitespace() {
+                break;
+            }
+            slice = rest;
+        }
+        while let [rest @ .., last] = slice {
+            if !last.is_ascii_whitespace() {
+                break;
+            }
+            slice = rest;
+        }
+        slice
+    }
 
-    pub struct Pairs<'a>(Vec<Pair<'a>>);
+    impl<'a> Value<'a> {
+        fn raw_slice(&self) -> RawSlice<'a> {
+            RawSlice::new(self.0, self.1)
+        }
+    }
 
     impl<'a> BracketsQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.check_truncated()?;
+            self.check_mixed_values()?;
+            let scratch = self.scratch();
+            T::deserialize(QSDeserializer::with_scratch(self.into_iter(), scratch))
+        }
+
+        /// Deserialize the parsed slice into T without consuming the parser
+        ///
+        /// This makes it possible to deserialize different types from the same parsed querystring.
+        pub fn deserialize_ref<T: Deserialize<'a>>(&self) -> Result<T, Error> {
+            self.check_truncated()?;
+            self.check_mixed_values()?;
+            T::deserialize(QSDeserializer::with_scratch(self.iter(), self.scratch()))
+        }
+
+        /// The scratch the values are decoded into, with the settings of the parser
+        fn scratch(&self) -> Scratch {
+            let mut scratch = Scratch::default();
+            scratch.strict_percent_decoding = self.strict_percent_decoding;
+            scratch.case_insensitive_bool = self.case_insensitive_bool;
+            scratch.bare_keys_as_some = self.bare_keys_as_some;
+            scratch.gap_budget = self.gap_budget();
+            scratch
+        }
+
+        /// The number of `None` elements the gaps of all the sequences can be filled with,
+        /// growing with the number of pairs so the allocations are bounded by the input
+        pub(crate) fn gap_budget(&self) -> usize {
+            let pairs: usize = self.pairs.values().map(Vec::len).sum();
+            pairs
+                .saturating_mul(FILLED_GAPS_PER_PAIR)
+                .max(MIN_FILLED_GAPS)
+        }
+
+        /// Returns an error for the reason the parsing stopped before the end of the slice
+        pub(crate) fn check_truncated(&self) -> Result<(), Error> {
+            match self.stopped {
+                Some(stopped) => Err(stopped.into()),
+                None => Ok(()),
+            }
+        }
+
+        /// Returns an error for the first key with both direct values and sub keys,
+        /// if `deny_mixed_values` is enabled
+        pub(crate) fn check_mixed_values(&self) -> Result<(), Error> {
+            if self.deny_mixed_values {
+                Self::find_mixed_values(&self.pairs, Depth::new(self.max_depth))
+            } else {
+                Ok(())
+            }
+        }
+
+        fn find_mixed_values(
+            pairs: &BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+            depth: Depth,
+        ) -> Result<(), Error> {
+            for (key, pairs) in pairs {
+                let with_subkey = pairs.iter().filter(|p| p.0.has_subkey()).count();
+
+                if with_subkey == 0 {
+                    continue;
+                } else if with_subkey != pairs.len() {
+                    return Err(Error::new(ErrorKind::InvalidType)
+                        .message("the key is assigned both a value and sub keys".to_string())
+                        .value(key));
+                }
+
+                let depth = depth.sub()?;
+                Self::find_mixed_values(&Self::from_pairs(pairs.iter().copied()).pairs, depth)?;
+            }
+
+            Ok(())
         }
 
         pub(crate) fn into_iter(self) -> impl Iterator<Item = (DecodedSlice<'a>, Pairs<'a>)> {
+            let max_depth = self.max_depth;
             self.pairs
                 .into_iter()
-                .map(|(key, pairs)| (DecodedSlice(key), Pairs(pairs)))
+                .map(move |(key, pairs)| (DecodedSlice(key), Pairs(pairs, max_depth)))
+        }
+
+        pub(crate) fn iter(&self) -> impl Iterator<Item = (DecodedSlice<'a>, Pairs<'a>)> + '_ {
+            self.pairs.iter().map(move |(key, pairs)| {
+                (
+                    DecodedSlice(key.clone()),
+                    Pairs(pairs.clone(), self.max_depth),
+                )
+            })
         }
     }
 
     impl<'a, 's> IntoDeserializer<'a, 's> for Pairs<'a> {
         type Deserializer = PairsDeserializer<'a, 's>;
 
-        fn into_deserializer(self, scratch: &'s mut Vec<u8>) -> Self::Deserializer {
-            PairsDeserializer(self.0, scratch)
+        fn into_deserializer(self, scratch: &'s mut Scratch) -> Self::Deserializer {
+            PairsDeserializer(self.0, scratch, Depth::new(self.1))
+        }
+    }
+
+    /// The pairs of the sub keys of a key, grouped by their decoded sub key in order.
+    ///
+    /// Nested keys mostly have a single sub key at each level, ex `a[b][c]=1`, so they are
+    /// handed over as they are, instead of rebuilding a map at each level.
+    enum SubKeys<'a> {
+        Single(Option<(Cow<'a, [u8]>, Vec<Pair<'a>>)>),
+        Grouped(btree_map::IntoIter<Cow<'a, [u8]>, Vec<Pair<'a>>>),
+    }
+
+    impl<'a> SubKeys<'a> {
+        fn new(mut pairs: Vec<Pair<'a>>, scratch: &mut Vec<u8>) -> Self {
+            // The pairs of the first sub key are moved to the front of the vector, until
+            // another sub key shows up
+            let mut len = 0;
+            for index in 0..pairs.len() {
+                let value = pairs[index].1;
+                let subkey = match pairs[index].0.subkey() {
+                    Some(subkey) => subkey,
+                    None => continue,
+                };
+
+                if len != 0 && pairs[0].0 .0 != subkey.0 {
+                    let rest = pairs.split_off(index);
+                    pairs.truncate(len);
+
+                    let mut groups = BTreeMap::new();
+                    groups.insert(pairs[0].0.decode(scratch).into_cow(), pairs);
+                    BracketsQS::group_subkeys(&mut groups, rest.into_iter(), scratch);
+                    return SubKeys::Grouped(groups.into_iter());
+                }
+
+                pairs[len] = Pair::new(subkey, value);
+                len += 1;
+            }
+            pairs.truncate(len);
+
+            match pairs.first() {
+                Some(first) => SubKeys::Single(Some((first.0.decode(scratch).into_cow(), pairs))),
+                None => SubKeys::Single(None),
+            }
+        }
+    }
+
+    impl<'a> Iterator for SubKeys<'a> {
+        type Item = (DecodedSlice<'a>, Vec<Pair<'a>>);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let (key, pairs) = match self {
+                SubKeys::Single(single) => single.take()?,
+                SubKeys::Grouped(iter) => iter.next()?,
+            };
+            Some((DecodedSlice(key), pairs))
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            match self {
+                SubKeys::Single(single) => {
+                    let len = usize::from(single.is_some());
+                    (len, Some(len))
+                }
+                SubKeys::Grouped(iter) => iter.size_hint(),
+            }
         }
     }
 
-    pub struct PairsDeserializer<'a, 's>(Vec<Pair<'a>>, &'s mut Vec<u8>);
+    /// Deserializes the pairs of a key, the last field is the remaining depth of sub keys
+    pub struct PairsDeserializer<'a, 's>(Vec<Pair<'a>>, &'s mut Scratch, Depth);
 
     impl<'a, 's> PairsDeserializer<'a, 's> {
+        /// The remaining depth for the sub keys of these pairs
         #[inline]
-        fn to_seq_values(&mut self) -> Result<Vec<(usize, RawSlice<'a>)>, Error> {
-            let mut values = std::mem::take(&mut self.0)
-                .into_iter()
-                .map(|pair| {
-                    let index = match pair.0.subkey() {
-                        Some(subkey) if !subkey.is_empty() => {
-                            let (value, len) = usize::from_radix_10_checked(&subkey.0);
-                            value
-                                .and_then(|v| if len == subkey.0.len() { Some(v) } else { None })
-                                .ok_or_else(|| {
-                                    Error::new(ErrorKind::InvalidNumber).message(format!(
-                                        "invalid index: the key has non-numeric characters"
-                                    ))
-                                })?
+        fn sub_depth(&self) -> Result<Depth, Error> {
+            self.2.sub()
+        }
+
+        /// Takes the values of a sequence, ordered by their index.
+        ///
+        /// Elements with sub keys (ex `key[1][field]=value`) are grouped by their index and
+        /// deserialized like maps, while each direct value is an element of its own.
+        /// Elements without an index (ex `key[][field]=value`) are grouped until one of their
+        /// fields is assigned again, which starts a new element.
+        #[inline]
+        fn take_seq_values(&mut self) -> Result<Vec<(usize, SeqElement<'a>)>, Error> {
+            let mut values = Vec::new();
+            // Position of the grouped elements in `values`, by index
+            let mut groups = BTreeMap::new();
+            // Position of the last element without an index, and its decoded fields
+            let mut open: Option<(usize, HashSet<Cow<'a, [u8]>>)> = None;
+
+            for pair in std::mem::take(&mut self.0) {
+                let subkey = pair.0.subkey();
+                let index = match subkey {
+                    Some(subkey) if !subkey.is_empty() => {
+                        let index = subkey.index(self.1);
+                        let (value, len) = usize::from_radix_10_checked(&index);
+                        let digits = index.iter().all(u8::is_ascii_digit);
+                        value
+                            .filter(|_| len == index.len())
+                            .ok_or_else(|| index_error(pair.0, subkey, digits))?
+                    }
+                    _ => 0,
+                };
+
+                match subkey {
+                    Some(subkey) if subkey.has_subkey() => {
+                        let pair = Pair(subkey, pair.1);
+                        let position = if subkey.0.is_empty() {
+                            let field = subkey
+                                .subkey()
+                                .map(|k| k.decode(self.1).into_cow())
+                                .unwrap_or_default();
+
+                            match &mut open {
+                                Some((position, fields)) if !fields.contains(&field) => {
+                                    fields.insert(field);
+                                    Some(*position)
+                                }
+                                _ => {
+                                    open = Some((values.len(), std::iter::once(field).collect()));
+                                    None
+                                }
+                            }
+                        } else {
+                            let position = groups.get(&index).copied();
+                            if position.is_none() {
+                                groups.insert(index, values.len());
+                            }
+                            position
+                        };
+
+                        match position {
+                            Some(position) => {
+                                if let (_, SeqElement::Pairs(pairs)) = &mut values[position] {
+                                    pairs.push(pair);
+                                }
+                            }
+                            None => values.push((index, SeqElement::Pairs(vec![pair]))),
                         }
-                        _ => 0,
-                    };
-                    Ok((index, RawSlice(pair.1.unwrap_or_default().slice())))
-                })
-                .collect::<Result<Vec<(usize, RawSlice)>, Error>>()?;
+                    }
+                    _ => values.push((
+                        index,
+                        SeqElement::Value(pair.1.unwrap_or_default().raw_slice()),
+                    )),
+                }
+            }
 
             values.sort_by_key(|item| item.0);
             Ok(values)
         }
     }
 
+    /// An element of a sequence, either a direct value or a group of pairs with sub keys
+    enum SeqElement<'a> {
+        Value(RawSlice<'a>),
+        Pairs(Vec<Pair<'a>>),
+    }
+
     macro_rules! forware_to_slice_deserializer {
         ($($method:ident ,)*) => {
             $(
@@ -399,8 +1016,8 @@ mod de {
                     V: de::Visitor<'de>,
                 {
                     let scratch = self.1;
-                    let value = self.0.last().unwrap().1.unwrap_or_default().slice();
-                    RawSlice(value).into_deserializer(scratch).$method(visitor)
+                    let value = self.0.last().unwrap().1.unwrap_or_default().raw_slice();
+                    value.into_deserializer(scratch).$method(visitor)
                 }
             )*
         };
@@ -413,22 +1030,29 @@ mod de {
         where
             V: de::Visitor<'de>,
         {
-            visitor.visit_seq(PairsSeqDeserializer(
-                self.to_seq_values()?.into_iter().map(|v| v.1),
-                self.1,
-            ))
+            let depth = self.sub_depth()?;
+            visitor.visit_seq(IndexedSeqDeserializer {
+                iter: self.take_seq_values()?.into_iter(),
+                pending: None,
+                position: 0,
+                fill_gaps: None,
+                scratch: self.1,
+                depth,
+            })
         }
 
         fn deserialize_tuple<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            let values = self.to_seq_values()?;
+            let depth = self.sub_depth()?;
+            let values = self.take_seq_values()?;
 
             if values.len() == len {
                 visitor.visit_seq(PairsSeqDeserializer(
-                    values.into_iter().map(|v| v.1),
+                    values.into_iter().map(|v| Some(v.1)),
                     self.1,
+                    depth,
                 ))
             } else {
                 Err(Error::new(ErrorKind::InvalidLength))
@@ -462,23 +1086,32 @@ mod de {
         where
             V: de::Visitor<'de>,
         {
+            let depth = self.sub_depth()?;
             visitor.visit_map(PairsMapDeserializer {
-                iter: BracketsQS::from_pairs(self.0.into_iter()).into_iter(),
+                iter: SubKeys::new(self.0, self.1),
                 scratch: self.1,
                 value: None,
+                depth,
             })
         }
 
         fn deserialize_struct<V>(
             self,
             _: &'static str,
-            _: &'static [&'static str],
+            fields: &'static [&'static str],
             visitor: V,
         ) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            self.deserialize_map(visitor)
+            let depth = self.sub_depth()?;
+            let iter = SubKeys::new(self.0, self.1);
+            visitor.visit_map(PairsMapDeserializer {
+                iter: TagFirst::new(iter, fields),
+                scratch: self.1,
+                value: None,
+                depth,
+            })
         }
 
         fn deserialize_enum<V>(
@@ -493,16 +1126,38 @@ mod de {
             visitor.visit_enum(self)
         }
 
+        /// Keys with sub keys are visited as maps and the others as values, following
+        /// the last assignment like other values do.
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            if self.0.last().map_or(false, |p| p.0.has_subkey()) {
+                self.deserialize_map(visitor)
+            } else {
+                let scratch = self.1;
+                let value = self.0.last().unwrap().1.unwrap_or_default().raw_slice();
+                value.into_deserializer(scratch).deserialize_any(visitor)
+            }
+        }
+
         fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            if self.0.is_empty()
-                || (self.0.len() == 1 && !self.0[0].0.has_subkey() && self.0[0].1.is_none())
-            {
+            // A bare key is `None`, only if none of its assignments has a value or a sub key
+            let bare = self.0.iter().all(|p| !p.0.has_subkey() && p.1.is_none());
+            if bare && !self.1.bare_keys_as_some {
+                return visitor.visit_none();
+            }
+
+            let empty = self.0.last().map_or(true, |p| {
+                !p.0.has_subkey() && p.1.map_or(true, |v| v.slice().is_empty())
+            });
+            if empty && self.1.empty_as_none {
                 visitor.visit_none()
             } else {
-                visitor.visit_some(self)
+                visitor.visit_some(SomeDeserializer::new(self, empty))
             }
         }
 
@@ -512,7 +1167,7 @@ mod de {
             deserialize_f32, deserialize_f64,
             deserialize_char, deserialize_str, deserialize_string, deserialize_identifier,
             deserialize_bool, deserialize_bytes, deserialize_byte_buf, deserialize_unit,
-            deserialize_any, deserialize_ignored_any,
+            deserialize_ignored_any,
         }
 
         forward_to_deserialize_any! {
@@ -531,19 +1186,40 @@ mod de {
         {
             let last_pair = self.0.last().expect("Values iterator can't be empty");
             if let Some(subkey) = last_pair.0.subkey() {
+                let depth = self.sub_depth()?;
                 let scratch = self.1;
-                let pairs = BracketsQS::from_pairs(self.0.into_iter())
-                    .pairs
-                    .remove(subkey.0)
-                    .unwrap();
-                seed.deserialize(RawSlice(subkey.0).into_deserializer(scratch))
-                    .map(move |v| (v, Self(pairs, scratch)))
+                let variant = subkey.decode(scratch).into_cow();
+                let pairs = SubKeys::new(self.0, scratch)
+                    .find(|(key, _)| key.0 == variant)
+                    .map(|(_, pairs)| pairs)
+                    .unwrap_or_default();
+                seed.deserialize(RawSlice::new(subkey.0, subkey.2).into_deserializer(scratch))
+                    .map(move |v| (v, Self(pairs, scratch, depth)))
+                    .map_err(|e| {
+                        // Variants are chosen by name, a sequence index is likely a mistake
+                        if !subkey.0.is_empty() && subkey.0.iter().all(u8::is_ascii_digit) {
+                            Error {
+                                message: format!(
+                                    "{}, variants are chosen by name and not by a numeric index",
+                                    e.message
+                                ),
+                                ..e
+                            }
+                            .value(subkey.0)
+                        } else {
+                            e
+                        }
+                    })
             } else {
                 let scratch = self.1;
                 seed.deserialize(
-                    RawSlice(last_pair.1.unwrap_or_default().0).into_deserializer(scratch),
+                    last_pair
+                        .1
+                        .unwrap_or_default()
+                        .raw_slice()
+                        .into_deserializer(scratch),
                 )
-                .map(move |v| (v, PairsDeserializer(Vec::new(), scratch)))
+                .map(move |v| (v, PairsDeserializer(Vec::new(), scratch, Depth::new(0))))
             }
         }
     }
@@ -567,11 +1243,28 @@ mod de {
             seed.deserialize(self)
         }
 
-        fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+        /// Unlike plain tuples, tuple variants accept fewer values than expected, the missing
+        /// trailing elements are deserialized as `None` if they're optional.
+        fn tuple_variant<V>(mut self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
         where
             V: de::Visitor<'de>,
         {
-            self.deserialize_tuple(len, visitor)
+            let depth = self.sub_depth()?;
+            let values = self.take_seq_values()?;
+
+            if values.len() <= len {
+                let missing = len - values.len();
+                visitor.visit_seq(PairsSeqDeserializer(
+                    values
+                        .into_iter()
+                        .map(|v| Some(v.1))
+                        .chain(std::iter::repeat_with(|| None).take(missing)),
+                    self.1,
+                    depth,
+                ))
+            } else {
+                Err(Error::new(ErrorKind::InvalidLength))
+            }
         }
 
         fn struct_variant<V>(
@@ -586,11 +1279,11 @@ mod de {
         }
     }
 
-    struct PairsSeqDeserializer<'s, I>(I, &'s mut Vec<u8>);
+    struct PairsSeqDeserializer<'s, I>(I, &'s mut Scratch, Depth);
 
     impl<'de, 's, I> de::SeqAccess<'de> for PairsSeqDeserializer<'s, I>
     where
-        I: Iterator<Item = RawSlice<'de>>,
+        I: Iterator<Item = Option<SeqElement<'de>>>,
     {
         type Error = Error;
 
@@ -598,26 +1291,196 @@ mod de {
         where
             T: de::DeserializeSeed<'de>,
         {
-            if let Some(v) = self.0.next() {
-                seed.deserialize(v.into_deserializer(self.1)).map(Some)
-            } else {
-                Ok(None)
+            match self.0.next() {
+                Some(Some(SeqElement::Value(v))) => {
+                    seed.deserialize(v.into_deserializer(self.1)).map(Some)
+                }
+                Some(Some(SeqElement::Pairs(pairs))) => seed
+                    .deserialize(PairsDeserializer(pairs, self.1, self.2))
+                    .map(Some),
+                Some(None) => seed.deserialize(MissingValue).map(Some),
+                None => Ok(None),
             }
         }
     }
 
+    /// Deserializes the elements of a sequence by their index, the gaps between the indices are
+    /// filled with `None` if the elements are optional, otherwise the elements are compacted.
+    /// The gap budget of the scratch is shared by all the sequences, nested ones included, and
+    /// the elements after a gap longer than what's left of it are compacted in both cases.
+    struct IndexedSeqDeserializer<'de, 's> {
+        iter: std::vec::IntoIter<(usize, SeqElement<'de>)>,
+        // The element after a gap, waiting for the gap to be filled
+        pending: Option<(usize, SeqElement<'de>)>,
+        // The position of the next element in the sequence
+        position: usize,
+        // Whether the gaps are filled, unknown until the first gap
+        fill_gaps: Option<bool>,
+        scratch: &'s mut Scratch,
+        depth: Depth,
+    }
+
+    impl<'de, 's> de::SeqAccess<'de> for IndexedSeqDeserializer<'de, 's> {
+        type Error = Error;
+
+        fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+        where
+            T: de::DeserializeSeed<'de>,
+        {
+            let (index, element) = match self.pending.take().or_else(|| self.iter.next()) {
+                Some(item) => item,
+                None => return Ok(None),
+            };
+
+            if index <= self.position
+                || index - self.position > self.scratch.gap_budget
+                || self.fill_gaps == Some(false)
+            {
+                self.position = self.position.max(index).saturating_add(1);
+                return match element {
+                    SeqElement::Value(v) => seed.deserialize(v.into_deserializer(self.scratch)),
+                    SeqElement::Pairs(pairs) => {
+                        seed.deserialize(PairsDeserializer(pairs, self.scratch, self.depth))
+                    }
+                }
+                .map(Some);
+            }
+
+            let mut gap = Gap {
+                element: Some(element),
+                scratch: self.scratch,
+                depth: self.depth,
+            };
+            let value = seed.deserialize(&mut gap)?;
+
+            match gap.element {
+                // The element is optional, so the gap is filled with `None`
+                Some(element) => {
+                    self.fill_gaps = Some(true);
+                    self.pending = Some((index, element));
+                    self.position += 1;
+                    self.scratch.gap_budget -= 1;
+                }
+                None => {
+                    self.fill_gaps = Some(false);
+                    self.position = index.saturating_add(1);
+                }
+            }
+
+            Ok(Some(value))
+        }
+    }
+
+    /// The number of `None` elements the gaps can be filled with for each parsed pair
+    const FILLED_GAPS_PER_PAIR: usize = 16;
+
+    /// The number of `None` elements the gaps can be filled with, however few pairs there are
+    const MIN_FILLED_GAPS: usize = 4096;
+
+    /// Stands for a gap in the indices of a sequence, it's `None` for an optional element and
+    /// the element after the gap otherwise.
+    struct Gap<'de, 's> {
+        element: Option<SeqElement<'de>>,
+        scratch: &'s mut Scratch,
+        depth: Depth,
+    }
+
+    macro_rules! forward_to_element {
+        ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+            $(
+                fn $method<V>(self, $($arg: $ty,)* visitor: V) -> Result<V::Value, Self::Error>
+                where
+                    V: de::Visitor<'de>,
+                {
+                    match self.element.take().expect("the element after a gap is taken once") {
+                        SeqElement::Value(v) => {
+                            v.into_deserializer(self.scratch).$method($($arg,)* visitor)
+                        }
+                        SeqElement::Pairs(pairs) => {
+                            PairsDeserializer(pairs, self.scratch, self.depth)
+                                .$method($($arg,)* visitor)
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    impl<'de, 's, 'g> de::Deserializer<'de> for &'g mut Gap<'de, 's> {
+        type Error = Error;
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_none()
+        }
+
+        forward_to_element! {
+            deserialize_any()
+            deserialize_bool()
+            deserialize_i8() deserialize_i16() deserialize_i32() deserialize_i64()
+            deserialize_i128()
+            deserialize_u8() deserialize_u16() deserialize_u32() deserialize_u64()
+            deserialize_u128()
+            deserialize_f32() deserialize_f64()
+            deserialize_char() deserialize_str() deserialize_string()
+            deserialize_bytes() deserialize_byte_buf()
+            deserialize_unit()
+            deserialize_unit_struct(name: &'static str)
+            deserialize_newtype_struct(name: &'static str)
+            deserialize_seq()
+            deserialize_tuple(len: usize)
+            deserialize_tuple_struct(name: &'static str, len: usize)
+            deserialize_map()
+            deserialize_struct(name: &'static str, fields: &'static [&'static str])
+            deserialize_enum(name: &'static str, variants: &'static [&'static str])
+            deserialize_identifier()
+            deserialize_ignored_any()
+        }
+    }
+
+    /// Stands for an element of a sequence which is expected but not present in the querystring
+    struct MissingValue;
+
+    impl<'de> de::Deserializer<'de> for MissingValue {
+        type Error = Error;
+
+        fn deserialize_any<V>(self, _: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            Err(Error::new(ErrorKind::InvalidLength)
+                .message("missing value for a non-optional element".to_string()))
+        }
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            visitor.visit_none()
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf unit unit_struct newtype_struct seq tuple
+            tuple_struct map struct enum identifier ignored_any
+        }
+    }
+
     struct PairsMapDeserializer<'de, 's, I>
     where
-        I: Iterator<Item = (DecodedSlice<'de>, Pairs<'de>)>,
+        I: Iterator<Item = (DecodedSlice<'de>, Vec<Pair<'de>>)>,
     {
         iter: I,
-        scratch: &'s mut Vec<u8>,
-        value: Option<Pairs<'de>>,
+        scratch: &'s mut Scratch,
+        value: Option<Vec<Pair<'de>>>,
+        depth: Depth,
     }
 
     impl<'de, 's, I> de::MapAccess<'de> for PairsMapDeserializer<'de, 's, I>
     where
-        I: Iterator<Item = (DecodedSlice<'de>, Pairs<'de>)>,
+        I: Iterator<Item = (DecodedSlice<'de>, Vec<Pair<'de>>)>,
     {
         type Error = Error;
 
@@ -639,12 +1502,11 @@ mod de {
         where
             V: de::DeserializeSeed<'de>,
         {
-            seed.deserialize(
-                self.value
-                    .take()
-                    .expect("next_value is called before next_key")
-                    .into_deserializer(self.scratch),
-            )
+            let pairs = self
+                .value
+                .take()
+                .expect("next_value is called before next_key");
+            seed.deserialize(PairsDeserializer(pairs, self.scratch, self.depth))
         }
 
         fn size_hint(&self) -> Option<usize> {
@@ -657,7 +1519,7 @@ mod de {
 mod tests {
     use std::borrow::Cow;
 
-    use super::BracketsQS;
+    use super::{BracketsQS, ParseOptions};
 
     #[test]
     fn parse_pair() {
@@ -678,6 +1540,21 @@ mod tests {
         assert_eq!(parser.values(b"test"), None);
     }
 
+    #[test]
+    fn parse_with_options() {
+        let slice = b"a[x]=1;a[y]=2&b=3;b";
+
+        let parser = BracketsQS::parse_with(slice, ParseOptions::new().separators(b"&;"));
+
+        let sub = parser.sub_values(b"a").unwrap();
+        assert_eq!(sub.value(b"x"), Some(Some("1".as_bytes().into())));
+        assert_eq!(sub.value(b"y"), Some(Some("2".as_bytes().into())));
+        assert_eq!(
+            parser.values(b"b"),
+            Some(vec![Some("3".as_bytes().into()), None])
+        );
+    }
+
     #[test]
     fn parse_multiple_pairs() {
         let slice = b"foo=bar&foobar=baz&qux=box";
@@ -761,6 +1638,34 @@ mod tests {
         )
     }
 
+    #[test]
+    fn parse_brackets_in_values() {
+        // Brackets are only parsed in keys, values only end at `&`
+        let slice = b"note=a%5Bb%5D&raw=a[b]&mixed=%5B]=[&foo[bar]=[baz]";
+
+        let parser = BracketsQS::parse(slice);
+
+        assert_eq!(
+            parser.keys(),
+            vec![
+                &Cow::Borrowed("foo".as_bytes()),
+                &Cow::Borrowed("mixed".as_bytes()),
+                &Cow::Borrowed("note".as_bytes()),
+                &Cow::Borrowed("raw".as_bytes())
+            ]
+        );
+        assert_eq!(parser.value(b"note"), Some(Some("a[b]".as_bytes().into())));
+        assert_eq!(parser.value(b"raw"), Some(Some("a[b]".as_bytes().into())));
+        assert_eq!(parser.value(b"mixed"), Some(Some("[]=[".as_bytes().into())));
+        assert!(parser.sub_values(b"note").unwrap().keys().is_empty());
+
+        let foo_values = parser.sub_values(b"foo").unwrap();
+        assert_eq!(
+            foo_values.value(b"bar"),
+            Some(Some("[baz]".as_bytes().into()))
+        );
+    }
+
     #[test]
     fn parse_invalid() {
         // Invalid suffix of keys should be ignored
@@ -792,4 +1697,77 @@ mod tests {
             Some(vec![Some("qux".as_bytes().into())])
         )
     }
+
+    #[test]
+    fn parse_value_owned() {
+        let slice = b"foo=bar%20baz&foo[sub]=qux&foo2";
+
+        let parser = BracketsQS::parse(slice);
+
+        assert_eq!(parser.value_owned(b"foo"), Some(Some(b"bar baz".to_vec())));
+        assert_eq!(parser.value_owned(b"foo2"), Some(None));
+        assert_eq!(parser.value_owned(b"foo3"), None);
+    }
+
+    #[test]
+    fn parse_raw_value() {
+        let slice = b"foo=bar%20baz+qux&foo[sub]=quux&foo2&foo3=a&foo3=b%26";
+
+        let parser = BracketsQS::parse(slice);
+
+        assert_eq!(parser.raw_value(b"foo"), Some(Some(&b"bar%20baz+qux"[..])));
+        assert_eq!(parser.raw_value(b"foo2"), Some(None));
+        assert_eq!(parser.raw_value(b"foo3"), Some(Some(&b"b%26"[..])));
+        assert_eq!(parser.raw_value(b"foo4"), None);
+    }
+
+    #[test]
+    fn parse_value_count() {
+        let slice = b"tag=a&tag=b&tag[sub]=c&tag&nested[0]=1&nested[1]=2&empty=";
+
+        let parser = BracketsQS::parse(slice);
+
+        assert_eq!(parser.value_count(b"tag"), Some(3));
+        assert_eq!(parser.value_count(b"nested"), Some(0));
+        assert_eq!(parser.value_count(b"empty"), Some(1));
+        assert_eq!(parser.value_count(b"missing"), None);
+    }
+
+    #[test]
+    fn display_summary() {
+        // Only the top level keys are listed
+        assert_eq!(
+            BracketsQS::parse(b"foo[bar]=1&foo[baz]=2&qux[]=3").to_string(),
+            "BracketsQS(2 keys: foo, qux)"
+        );
+    }
+
+    #[test]
+    fn parsed_equality() {
+        use std::collections::HashSet;
+
+        // The order of the keys doesn't matter
+        assert!(
+            BracketsQS::parse(b"foo[bar]=1&foo[baz]=2&qux[]=3")
+                == BracketsQS::parse(b"qux[]=3&foo[bar]=1&foo[baz]=2")
+        );
+
+        // But the order of the values assigned to a key does
+        assert!(BracketsQS::parse(b"foo[]=1&foo[]=2") != BracketsQS::parse(b"foo[]=2&foo[]=1"));
+        assert!(BracketsQS::parse(b"foo[bar]=1") != BracketsQS::parse(b"foo[baz]=1"));
+        assert!(BracketsQS::parse(b"foo=%41") != BracketsQS::parse(b"foo=A"));
+
+        // The options only used when deserializing are not compared
+        assert!(
+            BracketsQS::parse(b"foo[]=1") == BracketsQS::parse(b"foo[]=1").deny_mixed_values(true)
+        );
+
+        let cache: HashSet<_> = [
+            BracketsQS::parse(b"a[x]=1&b=2"),
+            BracketsQS::parse(b"b=2&a[x]=1"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(cache.len(), 1);
+    }
 }
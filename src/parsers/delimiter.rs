@@ -1,15 +1,24 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
-use crate::decode::{parse_bytes, Reference};
+use crate::decode::{parse_bytes, parse_bytes_lenient, Reference};
 
+use super::{bound_key, fmt_summary, with_separators, ParseOptions, Separators, Stopped};
+
+#[derive(PartialEq, Eq, Hash)]
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_len: usize) -> Self {
+        let slice = bound_key(slice, max_len);
         let mut index = 0;
         while index < slice.len() {
             match slice[index] {
-                b'&' | b'=' => break,
+                c if c == kv || separators.matches(c) => break,
                 _ => index += 1,
             }
         }
@@ -21,34 +30,44 @@ impl<'a> Key<'a> {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+        strict: bool,
+    ) -> Result<Reference<'a, 's, [u8]>, usize> {
+        parse_bytes(self.0, scratch, plus_as_space, None, strict)
     }
 }
 
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+        escape: Option<u8>,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_lenient(self.0, scratch, plus_as_space, escape)
     }
 }
 
-#[derive(Default)]
+#[derive(Default, PartialEq, Eq, Hash)]
 struct Values<'a>(&'a [u8]);
 
 impl<'a> Values<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.first()? == b'&' {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S) -> Option<Self> {
+        if separators.matches(*slice.first()?) {
             return None;
         }
 
         let mut index = 1;
         while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
+            if separators.matches(slice[index]) {
+                break;
             }
+            index += 1;
         }
 
         Some(Self(&slice[1..index]))
@@ -58,21 +77,46 @@ impl<'a> Values<'a> {
         self.0.len()
     }
 
-    fn values(&self, delimiter: u8) -> impl Iterator<Item = Value<'a>> {
-        self.0.split(move |c| *c == delimiter).map(Value)
+    fn values(
+        &self,
+        delimiter: u8,
+        collapse: bool,
+        escape: Option<u8>,
+    ) -> impl Iterator<Item = Value<'a>> {
+        let mut remains = Some(self.0);
+        std::iter::from_fn(move || {
+            let slice = remains?;
+            match position_of_delimiter(slice, delimiter, escape) {
+                Some(index) => {
+                    remains = Some(&slice[index + 1..]);
+                    Some(&slice[..index])
+                }
+                None => {
+                    remains = None;
+                    Some(slice)
+                }
+            }
+        })
+        .filter(move |v| !collapse || !v.is_empty())
+        .map(Value)
     }
 
-    fn decode_to<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode_to<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_lenient(self.0, scratch, plus_as_space, None)
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
 struct Pair<'a>(Key<'a>, Option<Values<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Values::parse(&slice[key.len()..]);
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_key_len: usize) -> Self {
+        let key = Key::parse(slice, separators, kv, max_key_len);
+        let value = Values::parse(&slice[key.len()..], separators);
 
         Self(key, value)
     }
@@ -93,6 +137,14 @@ impl<'a> Pair<'a> {
 /// # Note
 /// Keys are decoded when calling the `parse` method, but values are lazily decoded when you
 /// call the `value` method for their keys.
+/// A value without any delimiters is still a valid sequence of one element when deserializing,
+/// so `key=value` can be deserialized into a `Vec` the same way `key=value1|value2` can.
+///
+/// Two parsers are equal when they have the same keys, each assigned the same last value, and
+/// split their values the same way, which is the delimiter, the key delimiters, collapsing the
+/// delimiters and the escape byte. The order of different keys doesn't matter, and pairs are
+/// compared as they appear in the querystring, so `key=%41` and `key=A` are not equal. The other
+/// options are not compared, as they only change how the pairs are decoded and deserialized.
 ///
 /// # Example
 /// ```rust
@@ -119,21 +171,91 @@ impl<'a> Pair<'a> {
 pub struct DelimiterQS<'a> {
     pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>>,
     delimiter: u8,
+    key_delimiters: &'a [(&'a [u8], u8)],
+    collapse_delimiters: bool,
+    map_delimiter: Option<u8>,
+    escape: Option<u8>,
+    plus_as_space: bool,
+    case_insensitive_bool: bool,
+    bare_keys_as_some: bool,
+    #[cfg(feature = "serde")]
+    strict_percent_decoding: bool,
+    stopped: Option<Stopped<'a>>,
 }
 
 impl<'a> DelimiterQS<'a> {
     /// Parse a slice of bytes into a `DelimiterQS`
     pub fn parse(slice: &'a [u8], delimiter: u8) -> Self {
+        Self::parse_with(slice, delimiter, ParseOptions::new())
+    }
+
+    /// Parse a slice of bytes into a `DelimiterQS`, choosing whether a `+` is decoded as a space
+    /// in keys and in values separately.
+    ///
+    /// `parse` decodes it as a space in both, like form encoding does. Otherwise `+` is kept
+    /// as is, and only `%20` is decoded as a space.
+    pub fn parse_with_plus_as_space(
+        slice: &'a [u8],
+        delimiter: u8,
+        keys: bool,
+        values: bool,
+    ) -> Self {
+        let options = ParseOptions::new()
+            .plus_as_space_in_keys(keys)
+            .plus_as_space_in_values(values);
+        Self::parse_with(slice, delimiter, options)
+    }
+
+    /// Parse a slice of bytes into a `DelimiterQS` with the given options.
+    ///
+    /// More description at ([UrlEncodedQS::parse_with](crate::UrlEncodedQS::parse_with))
+    pub fn parse_with(slice: &'a [u8], delimiter: u8, options: ParseOptions<'_>) -> Self {
+        with_separators!(options, |separators| Self::parse_separated(
+            slice, delimiter, separators, options
+        ))
+    }
+
+    fn parse_separated<S: Separators>(
+        slice: &'a [u8],
+        delimiter: u8,
+        separators: S,
+        options: ParseOptions<'_>,
+    ) -> Self {
+        let kv = options.kv_separator;
+        let mut stopped = None;
+        let mut count = 0;
         let mut pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>> = BTreeMap::new();
         let mut scratch = Vec::new();
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
+            let pair = Pair::parse(&slice[index..], separators, kv, options.max_key_len);
+            if pair.0.len() > options.max_key_len {
+                stopped = Some(Stopped::KeyLen(options.max_key_len, pair.0 .0));
+                break;
+            }
+            // Empty parts like `&&` are not counted
+            if pair.skip_len() > 1 {
+                count += 1;
+                if count > options.max_input_vars {
+                    stopped = Some(Stopped::InputVars(options.max_input_vars));
+                    break;
+                }
+            }
             index += pair.skip_len();
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let decoded_key = match pair.0.decode(
+                &mut scratch,
+                options.plus_as_space_in_keys,
+                options.strict_percent_decoding,
+            ) {
+                Ok(decoded_key) => decoded_key,
+                Err(at) => {
+                    stopped = Some(Stopped::InvalidKey(pair.0 .0, at));
+                    break;
+                }
+            };
 
             if let Some(old_pair) = pairs.get_mut(decoded_key.as_ref()) {
                 *old_pair = pair;
@@ -142,7 +264,120 @@ impl<'a> DelimiterQS<'a> {
             }
         }
 
-        Self { pairs, delimiter }
+        Self {
+            pairs,
+            delimiter,
+            key_delimiters: &[],
+            collapse_delimiters: false,
+            map_delimiter: None,
+            escape: None,
+            plus_as_space: options.plus_as_space_in_values,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: options.strict_percent_decoding,
+            stopped,
+        }
+    }
+
+    /// Uses a different delimiter for some keys, ex `ids=1,2&tags=a|b` with `(b"ids", b',')`.
+    ///
+    /// Keys are compared after percent decoding, and the other keys use the delimiter passed
+    /// to the `parse` method.
+    pub fn key_delimiters(mut self, delimiters: &'a [(&'a [u8], u8)]) -> Self {
+        self.key_delimiters = delimiters;
+        self
+    }
+
+    /// Skips the empty values between consecutive delimiters, so `a||b|` is parsed as `["a", "b"]`
+    /// instead of `["a", "", "b", ""]`. It is disabled by default.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::DelimiterQS;
+    ///
+    /// let parser = DelimiterQS::parse(b"foo=|bar||baz|", b'|').collapse_delimiters(true);
+    /// assert_eq!(
+    ///     parser.values(b"foo"),
+    ///     Some(Some(vec!["bar".as_bytes().into(), "baz".as_bytes().into()]))
+    /// );
+    /// ```
+    pub fn collapse_delimiters(mut self, enabled: bool) -> Self {
+        self.collapse_delimiters = enabled;
+        self
+    }
+
+    /// Splits the values deserialized as maps or structs into entries by the delimiter, and each
+    /// entry into a key and a value by `delimiter`, ex `config=a:1,b:2` with `b','` and `b':'`.
+    ///
+    /// Everything before the first `delimiter` of an entry is its key, and the rest its value,
+    /// an entry without it has an empty value. Empty entries are skipped, and only the values
+    /// of maps and structs are affected, sequences are still split by the delimiter.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::collections::HashMap;
+    /// use serde_querystring::DelimiterQS;
+    ///
+    /// let parser = DelimiterQS::parse(b"config=a:1,b:2", b',').map_delimiter(b':');
+    ///
+    /// let value: HashMap<String, HashMap<String, u8>> = parser.deserialize().unwrap();
+    /// assert_eq!(value["config"]["b"], 2);
+    /// ```
+    pub fn map_delimiter(mut self, delimiter: u8) -> Self {
+        self.map_delimiter = Some(delimiter);
+        self
+    }
+
+    /// Uses `escape` to escape the delimiters inside the values, ex `tags=a\,b,c` with `b'\\'`
+    /// is parsed as `["a,b", "c"]`. It is disabled by default.
+    ///
+    /// The byte after an escape is kept as is, without the escape, so it's neither a delimiter
+    /// nor percent decoded, and `\\` is a single backslash. Only the values split by the
+    /// delimiter are unescaped, the `value` method returns the whole value as is. A percent
+    /// encoded delimiter, ex `a%2Cb,c`, is never used as a delimiter even without an escape.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::DelimiterQS;
+    ///
+    /// let parser = DelimiterQS::parse(br"tags=a\,b,c\\", b',').delimiter_escape(b'\\');
+    /// assert_eq!(
+    ///     parser.values(b"tags"),
+    ///     Some(Some(vec!["a,b".as_bytes().into(), "c\\".as_bytes().into()]))
+    /// );
+    /// ```
+    pub fn delimiter_escape(mut self, escape: u8) -> Self {
+        self.escape = Some(escape);
+        self
+    }
+
+    /// Whether the parsing stopped before the end of the slice, for a limit of the options or
+    /// an invalid key with `strict_percent_decoding`. The pairs after it are not parsed and
+    /// deserializing fails.
+    pub fn is_truncated(&self) -> bool {
+        self.stopped.is_some()
+    }
+
+    /// Parses the boolean values ignoring their case when deserializing, so `True`, `FALSE`,
+    /// `On` and `oFF` are accepted too. It is disabled by default.
+    pub fn case_insensitive_bool(mut self, enabled: bool) -> Self {
+        self.case_insensitive_bool = enabled;
+        self
+    }
+
+    /// Deserializes the keys without a value, ex. `verbose` in `verbose&page=2`, as `Some` of an
+    /// empty value for the optional fields, instead of `None`. It is disabled by default.
+    ///
+    /// The rule is the same for every type, so a bare key is `Some(true)` for an `Option<bool>`
+    /// but an `InvalidNumber` error for an `Option<u32>`, like an empty value is.
+    pub fn bare_keys_as_some(mut self, enabled: bool) -> Self {
+        self.bare_keys_as_some = enabled;
+        self
+    }
+
+    fn delimiter_for(&self, key: &[u8]) -> u8 {
+        find_delimiter(self.key_delimiters, key, self.delimiter)
     }
 
     /// Returns a vector containing all the keys in querystring.
@@ -158,13 +393,15 @@ impl<'a> DelimiterQS<'a> {
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
     pub fn values(&self, key: &'a [u8]) -> Option<Option<Vec<Cow<'a, [u8]>>>> {
-        let delimiter = self.delimiter;
+        let delimiter = self.delimiter_for(key);
+        let plus_as_space = self.plus_as_space;
+        let escape = self.escape;
         let mut scratch = Vec::new();
 
         Some(self.pairs.get(key)?.1.as_ref().map(|values| {
             values
-                .values(delimiter)
-                .map(|v| v.decode(&mut scratch).into_cow())
+                .values(delimiter, self.collapse_delimiters, escape)
+                .map(|v| v.decode(&mut scratch, plus_as_space, escape).into_cow())
                 .collect()
         }))
     }
@@ -179,13 +416,74 @@ impl<'a> DelimiterQS<'a> {
     pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
 
-        Some(
-            self.pairs
-                .get(key)?
-                .1
-                .as_ref()
-                .map(|values| values.decode_to(&mut scratch).into_cow()),
-        )
+        Some(self.pairs.get(key)?.1.as_ref().map(|values| {
+            values
+                .decode_to(&mut scratch, self.plus_as_space)
+                .into_cow()
+        }))
+    }
+
+    /// Returns the last value assigned to a key without taking delimiters into account, as owned bytes.
+    ///
+    /// It works like the `value` method, but the result doesn't borrow from the input
+    /// so it can be stored independently.
+    pub fn value_owned(&self, key: &'a [u8]) -> Option<Option<Vec<u8>>> {
+        self.value(key).map(|v| v.map(Cow::into_owned))
+    }
+}
+
+/// Returns the index of the first delimiter in the slice, skipping the escaped ones
+fn position_of_delimiter(slice: &[u8], delimiter: u8, escape: Option<u8>) -> Option<usize> {
+    let escape = match escape {
+        Some(escape) => escape,
+        None => return slice.iter().position(|c| *c == delimiter),
+    };
+
+    let mut index = 0;
+    while index < slice.len() {
+        match slice[index] {
+            c if c == escape => index += 2,
+            c if c == delimiter => return Some(index),
+            _ => index += 1,
+        }
+    }
+
+    None
+}
+
+/// Returns the delimiter of a key, or the `default` one if the key doesn't have its own
+fn find_delimiter(key_delimiters: &[(&[u8], u8)], key: &[u8], default: u8) -> u8 {
+    key_delimiters
+        .iter()
+        .find(|(k, _)| *k == key)
+        .map_or(default, |(_, d)| *d)
+}
+
+impl<'a> PartialEq for DelimiterQS<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+            && self.delimiter == other.delimiter
+            && self.key_delimiters == other.key_delimiters
+            && self.collapse_delimiters == other.collapse_delimiters
+            && self.escape == other.escape
+    }
+}
+
+impl<'a> Eq for DelimiterQS<'a> {}
+
+impl<'a> Hash for DelimiterQS<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pairs.hash(state);
+        self.delimiter.hash(state);
+        self.key_delimiters.hash(state);
+        self.collapse_delimiters.hash(state);
+        self.escape.hash(state);
+    }
+}
+
+impl<'a> fmt::Display for DelimiterQS<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(f, "DelimiterQS", self.pairs.keys())
     }
 }
 
@@ -194,26 +492,88 @@ mod de {
     use _serde::Deserialize;
 
     use crate::de::{
+        __implementors::{DecodedSlice, IntoRawSlices, RawSlice, Scratch},
         Error, QSDeserializer,
-        __implementors::{DecodedSlice, IntoRawSlices, RawSlice},
     };
 
-    use super::DelimiterQS;
+    use super::{find_delimiter, position_of_delimiter, DelimiterQS};
 
     impl<'a> DelimiterQS<'a> {
         /// Deserialize the parsed slice into T
+        ///
+        /// It gives the same result as `from_bytes` with `ParseMode::Delimiter`, so a querystring
+        /// can be parsed once, inspected with the other methods and then deserialized.
+        ///
+        /// # Example
+        /// ```rust
+        /// use _serde::Deserialize;
+        /// use serde_querystring::{from_bytes, DelimiterQS, ParseMode};
+        ///
+        /// #[derive(Debug, PartialEq, Deserialize)]
+        ///# #[serde(crate = "_serde")]
+        /// struct Filter {
+        ///     tags: Vec<String>,
+        ///     page: u32,
+        /// }
+        ///
+        /// let slice = b"tags=a|b&page=2";
+        /// let parser = DelimiterQS::parse(slice, b'|');
+        ///
+        /// // Inspect the parsed querystring first
+        /// assert_eq!(parser.values(b"tags").map(|v| v.map(|v| v.len())), Some(Some(2)));
+        ///
+        /// let filter: Filter = parser.deserialize().unwrap();
+        /// assert_eq!(
+        ///     filter,
+        ///     from_bytes(slice, ParseMode::Delimiter(b'|')).unwrap()
+        /// );
+        /// ```
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.check_truncated()?;
+            let scratch = self.scratch();
+            T::deserialize(QSDeserializer::with_scratch(self.into_iter(), scratch))
+        }
+
+        /// The scratch the values are decoded into, with the settings of the parser
+        fn scratch(&self) -> Scratch {
+            let mut scratch = Scratch::default();
+            scratch.strict_percent_decoding = self.strict_percent_decoding;
+            scratch.case_insensitive_bool = self.case_insensitive_bool;
+            scratch.bare_keys_as_some = self.bare_keys_as_some;
+            scratch
+        }
+
+        /// Returns an error for the reason the parsing stopped before the end of the slice
+        pub(crate) fn check_truncated(&self) -> Result<(), Error> {
+            match self.stopped {
+                Some(stopped) => Err(stopped.into()),
+                None => Ok(()),
+            }
         }
 
         pub(crate) fn into_iter(
             self,
         ) -> impl Iterator<Item = (DecodedSlice<'a>, SeparatorValues<'a>)> {
             let delimiter = self.delimiter;
+            let key_delimiters = self.key_delimiters;
+            let collapse = self.collapse_delimiters;
+            let map_delimiter = self.map_delimiter;
+            let escape = self.escape;
+            let plus_as_space = self.plus_as_space;
             self.pairs.into_iter().map(move |(key, pair)| {
+                let delimiter = find_delimiter(key_delimiters, &key, delimiter);
+
                 (
                     DecodedSlice(key),
-                    SeparatorValues::from_slice(pair.1.map(|v| v.0).unwrap_or_default(), delimiter),
+                    SeparatorValues {
+                        bare: pair.1.is_none(),
+                        slice: pair.1.map(|v| v.0).unwrap_or_default(),
+                        delimiter,
+                        collapse,
+                        map_delimiter,
+                        escape,
+                        plus_as_space,
+                    },
                 )
             })
         }
@@ -221,12 +581,30 @@ mod de {
 
     pub(crate) struct SeparatorValues<'a> {
         slice: &'a [u8],
+        // Whether the key has no value at all
+        bare: bool,
         delimiter: u8,
+        collapse: bool,
+        map_delimiter: Option<u8>,
+        escape: Option<u8>,
+        plus_as_space: bool,
     }
 
     impl<'a> SeparatorValues<'a> {
-        fn from_slice(slice: &'a [u8], delimiter: u8) -> Self {
-            Self { slice, delimiter }
+        #[inline]
+        fn values(self, size: Option<usize>) -> SizedValuesIterator<'a> {
+            let mut iter = SizedValuesIterator {
+                slice: self.slice,
+                delimiter: self.delimiter,
+                escape: self.escape,
+                collapse: self.collapse,
+                plus_as_space: self.plus_as_space,
+                remaining: size,
+                index: 0,
+                len: 0,
+            };
+            iter.len = iter.count_values();
+            iter
         }
     }
 
@@ -235,55 +613,152 @@ mod de {
 
         type UnSizedIterator = SizedValuesIterator<'a>;
 
+        type Entries = SeparatorEntries<'a>;
+
         #[inline]
         fn into_sized_iterator(self, size: usize) -> Result<Self::SizedIterator, crate::de::Error> {
-            Ok(SizedValuesIterator::new(
-                self.slice,
-                self.delimiter,
-                Some(size),
-            ))
+            Ok(self.values(Some(size)))
         }
 
         #[inline]
         fn into_unsized_iterator(self) -> Self::UnSizedIterator {
-            SizedValuesIterator::new(self.slice, self.delimiter, None)
+            self.values(None)
         }
 
         #[inline]
         fn into_single_slice(self) -> RawSlice<'a> {
-            RawSlice(self.slice)
+            RawSlice::new(self.slice, self.plus_as_space)
+        }
+
+        #[inline]
+        fn into_entries(self) -> Result<Self::Entries, Self> {
+            match self.map_delimiter {
+                Some(map_delimiter) => Ok(SeparatorEntries {
+                    values: Self {
+                        collapse: true,
+                        ..self
+                    }
+                    .values(None),
+                    map_delimiter,
+                }),
+                None => Err(self),
+            }
+        }
+
+        #[inline]
+        fn is_empty(&self) -> bool {
+            self.slice.is_empty()
+        }
+
+        #[inline]
+        fn is_bare(&self) -> bool {
+            self.bare
+        }
+    }
+
+    /// The entries of a value split by the delimiter, and each split into a key and a value
+    /// by the map delimiter
+    pub struct SeparatorEntries<'a> {
+        values: SizedValuesIterator<'a>,
+        map_delimiter: u8,
+    }
+
+    impl<'a> Iterator for SeparatorEntries<'a> {
+        type Item = (RawSlice<'a>, RawSlice<'a>);
+
+        fn next(&mut self) -> Option<Self::Item> {
+            let entry = self.values.next()?;
+            let slice = |slice| RawSlice { slice, ..entry };
+
+            Some(
+                match position_of_delimiter(entry.slice, self.map_delimiter, entry.escape) {
+                    Some(index) => (
+                        slice(&entry.slice[..index]),
+                        slice(&entry.slice[index + 1..]),
+                    ),
+                    None => (entry, slice(&[])),
+                },
+            )
         }
     }
 
     pub struct SizedValuesIterator<'a> {
         slice: &'a [u8],
         delimiter: u8,
+        escape: Option<u8>,
+        collapse: bool,
+        plus_as_space: bool,
         remaining: Option<usize>,
         index: usize,
+        // The number of values left, counted once when the iterator is built
+        len: usize,
     }
 
     impl<'a> SizedValuesIterator<'a> {
-        fn new(slice: &'a [u8], delimiter: u8, size: Option<usize>) -> Self {
-            Self {
-                slice,
-                delimiter,
-                remaining: size,
-                index: 0,
-            }
-        }
-
         #[inline]
         fn decrease_remaining(&mut self) {
             if let Some(remaining) = self.remaining {
                 self.remaining = Some(remaining - 1);
             }
         }
+
+        /// The end of the value starting at `start`, either its delimiter or the end of the slice
+        #[inline]
+        fn value_end(&self, start: usize) -> usize {
+            position_of_delimiter(&self.slice[start..], self.delimiter, self.escape)
+                .map_or(self.slice.len(), |index| start + index)
+        }
+
+        /// The values are counted by their delimiters, a trailing delimiter doesn't make a new value
+        fn count_values(&self) -> usize {
+            let mut count = 0;
+            let mut start = self.index;
+            while start < self.slice.len() {
+                let end = self.value_end(start);
+                if !self.collapse || end > start {
+                    count += 1;
+                }
+                start = end + 1;
+            }
+
+            match self.remaining {
+                Some(remaining) => count.min(remaining),
+                None => count,
+            }
+        }
+
+        #[inline]
+        fn raw_slice(&self, start: usize, end: usize) -> RawSlice<'a> {
+            RawSlice {
+                slice: &self.slice[start..end],
+                plus_as_space: self.plus_as_space,
+                escape: self.escape,
+            }
+        }
     }
 
     impl<'a> Iterator for SizedValuesIterator<'a> {
         type Item = RawSlice<'a>;
 
         fn next(&mut self) -> Option<Self::Item> {
+            let value = self.next_value()?;
+            self.len = self.len.saturating_sub(1);
+            Some(value)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (self.len, Some(self.len))
+        }
+    }
+
+    impl<'a> SizedValuesIterator<'a> {
+        fn next_value(&mut self) -> Option<RawSlice<'a>> {
+            if self.collapse {
+                while self.slice.get(self.index) == Some(&self.delimiter) {
+                    self.index += 1;
+                }
+            }
+
             if self.index >= self.slice.len() {
                 return None;
             }
@@ -295,26 +770,32 @@ mod de {
                     }
                     1 => {
                         self.remaining = Some(0);
-                        return Some(RawSlice(&self.slice[self.index..]));
+
+                        // The last value takes the rest, without the trailing delimiters when
+                        // collapsing them
+                        let mut end = self.slice.len();
+                        if self.collapse {
+                            let mut start = self.index;
+                            while start < self.slice.len() {
+                                let value_end = self.value_end(start);
+                                if value_end > start {
+                                    end = value_end;
+                                }
+                                start = value_end + 1;
+                            }
+                        }
+                        return Some(self.raw_slice(self.index, end));
                     }
                     _ => {}
                 }
             }
 
             let start = self.index;
-            for c in &self.slice[self.index..] {
-                if *c == self.delimiter {
-                    let end = self.index;
-                    self.index += 1;
-
-                    self.decrease_remaining();
-                    return Some(RawSlice(&self.slice[start..end]));
-                }
-                self.index += 1;
-            }
+            let end = self.value_end(start);
+            self.index = end + 1;
 
             self.decrease_remaining();
-            Some(RawSlice(&self.slice[start..]))
+            Some(self.raw_slice(start, end))
         }
     }
 }
@@ -323,7 +804,7 @@ mod de {
 mod tests {
     use std::borrow::Cow;
 
-    use super::DelimiterQS;
+    use super::{position_of_delimiter, DelimiterQS, ParseOptions};
 
     #[test]
     fn parse_pair() {
@@ -344,6 +825,23 @@ mod tests {
         assert_eq!(parser.values(b"test"), None);
     }
 
+    #[test]
+    fn parse_with_options() {
+        let slice = b"a=1|2;b=3&c=4|5";
+
+        let parser = DelimiterQS::parse_with(slice, b'|', ParseOptions::new().separators(b"&;"));
+
+        assert_eq!(
+            parser.values(b"a"),
+            Some(Some(vec!["1".as_bytes().into(), "2".as_bytes().into()]))
+        );
+        assert_eq!(parser.values(b"b"), Some(Some(vec!["3".as_bytes().into()])));
+        assert_eq!(
+            parser.values(b"c"),
+            Some(Some(vec!["4".as_bytes().into(), "5".as_bytes().into()]))
+        );
+    }
+
     #[test]
     fn parse_multiple_pairs() {
         let slice = b"foo=bar&foobar=baz&qux=box";
@@ -414,4 +912,95 @@ mod tests {
             ]))
         );
     }
+
+    #[test]
+    fn parse_collapse_delimiters() {
+        let slice = b"foo=||bar|||baz|&qux=|||";
+
+        let parser = DelimiterQS::parse(slice, b'|').collapse_delimiters(true);
+
+        assert_eq!(
+            parser.values(b"foo"),
+            Some(Some(vec!["bar".as_bytes().into(), "baz".as_bytes().into()]))
+        );
+        assert_eq!(parser.values(b"qux"), Some(Some(vec![])));
+
+        // The whole value is kept as is
+        assert_eq!(
+            parser.value(b"foo"),
+            Some(Some("||bar|||baz|".as_bytes().into()))
+        );
+    }
+
+    #[test]
+    fn parse_delimiter_escape() {
+        let slice = br"foo=a\|b|c\\|\d&bar=a\|b";
+
+        let parser = DelimiterQS::parse(slice, b'|').delimiter_escape(b'\\');
+
+        assert_eq!(
+            parser.values(b"foo"),
+            Some(Some(vec![
+                "a|b".as_bytes().into(),
+                "c\\".as_bytes().into(),
+                "d".as_bytes().into()
+            ]))
+        );
+
+        // Without an escape the backslashes are kept
+        let parser = DelimiterQS::parse(slice, b'|');
+        assert_eq!(
+            parser.values(b"bar"),
+            Some(Some(vec!["a\\".as_bytes().into(), "b".as_bytes().into()]))
+        );
+
+        assert_eq!(position_of_delimiter(br"a\|b|", b'|', Some(b'\\')), Some(4));
+        assert_eq!(position_of_delimiter(br"a\|b\", b'|', Some(b'\\')), None);
+        assert_eq!(position_of_delimiter(br"a\|b", b'|', None), Some(2));
+    }
+
+    #[test]
+    fn parse_value_owned() {
+        let slice = b"foo=bar%20baz&foo2";
+
+        let parser = DelimiterQS::parse(slice, b'|');
+
+        assert_eq!(parser.value_owned(b"foo"), Some(Some(b"bar baz".to_vec())));
+        assert_eq!(parser.value_owned(b"foo2"), Some(None));
+        assert_eq!(parser.value_owned(b"foo3"), None);
+    }
+
+    #[test]
+    fn parse_key_delimiters() {
+        let slice = b"ids=1,2&tags=a|b,c";
+
+        let parser = DelimiterQS::parse(slice, b'|').key_delimiters(&[(b"ids", b',')]);
+
+        assert_eq!(
+            parser.values(b"ids"),
+            Some(Some(vec!["1".as_bytes().into(), "2".as_bytes().into()]))
+        );
+        assert_eq!(
+            parser.values(b"tags"),
+            Some(Some(vec!["a".as_bytes().into(), "b,c".as_bytes().into()]))
+        );
+    }
+
+    #[test]
+    fn parsed_equality() {
+        assert!(DelimiterQS::parse(b"a=1|2&b=3", b'|') == DelimiterQS::parse(b"b=3&a=1|2", b'|'));
+
+        // The values are split the same way only with the same delimiters
+        assert!(DelimiterQS::parse(b"a=1|2", b'|') != DelimiterQS::parse(b"a=1|2", b','));
+        assert!(
+            DelimiterQS::parse(b"a=1||2", b'|')
+                != DelimiterQS::parse(b"a=1||2", b'|').collapse_delimiters(true)
+        );
+
+        // But the options only used when deserializing are not compared
+        assert!(
+            DelimiterQS::parse(b"a=1:2", b'|')
+                == DelimiterQS::parse(b"a=1:2", b'|').map_delimiter(b':')
+        );
+    }
 }
@@ -1,15 +1,27 @@
-use std::{borrow::Cow, collections::BTreeMap};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+};
 
-use crate::decode::{parse_bytes, Reference};
+use crate::decode::{parse_bytes, parse_bytes_lenient, Reference};
 
+use super::{
+    bound_key, fmt_summary, push_normalized_pair, with_separators, ParseOptions, Separators,
+    Stopped,
+};
+
+#[derive(PartialEq, Eq, Hash)]
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_len: usize) -> Self {
+        let slice = bound_key(slice, max_len);
         let mut index = 0;
         while index < slice.len() {
             match slice[index] {
-                b'&' | b'=' => break,
+                c if c == kv || separators.matches(c) => break,
                 _ => index += 1,
             }
         }
@@ -21,25 +33,31 @@ impl<'a> Key<'a> {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+        strict: bool,
+    ) -> Result<Reference<'a, 's, [u8]>, usize> {
+        parse_bytes(self.0, scratch, plus_as_space, None, strict)
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.first()? == b'&' {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S) -> Option<Self> {
+        if separators.matches(*slice.first()?) {
             return None;
         }
 
         let mut index = 1;
         while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
+            if separators.matches(slice[index]) {
+                break;
             }
+            index += 1;
         }
 
         Some(Self(&slice[1..index]))
@@ -49,8 +67,8 @@ impl<'a> Value<'a> {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(&self, scratch: &'s mut Vec<u8>, plus_as_space: bool) -> Reference<'a, 's, [u8]> {
+        parse_bytes_lenient(self.0, scratch, plus_as_space, None)
     }
 
     fn slice(&self) -> &'a [u8] {
@@ -58,12 +76,13 @@ impl<'a> Value<'a> {
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
 struct Pair<'a>(Key<'a>, Option<Value<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Value::parse(&slice[key.len()..]);
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_key_len: usize) -> Self {
+        let key = Key::parse(slice, separators, kv, max_key_len);
+        let value = Value::parse(&slice[key.len()..], separators);
 
         Self(key, value)
     }
@@ -84,6 +103,13 @@ impl<'a> Pair<'a> {
 /// # Note
 /// Keys are decoded when calling the `parse` method, but values are lazily decoded when you
 /// call the `value` method for their keys.
+/// A key assigned only once is still a valid sequence of one element when deserializing,
+/// so `key=value` can be deserialized into a `Vec` the same way `key=value1&key=value2` can.
+///
+/// Two parsers are equal when they have the same keys, each assigned the same values in the
+/// same order. The order of different keys doesn't matter, and pairs are compared as they appear
+/// in the querystring, so `key=%41` and `key=A` are not equal. The options are not compared, as
+/// they only change how the pairs are decoded and deserialized.
 ///
 /// # Example
 /// ```rust
@@ -110,21 +136,82 @@ impl<'a> Pair<'a> {
 /// ```
 pub struct DuplicateQS<'a> {
     pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>>,
+    sort_values: bool,
+    plus_as_space: bool,
+    case_insensitive_bool: bool,
+    bare_keys_as_some: bool,
+    #[cfg(feature = "serde")]
+    strict_percent_decoding: bool,
+    stopped: Option<Stopped<'a>>,
 }
 
 impl<'a> DuplicateQS<'a> {
     /// Parse a slice of bytes into a `DuplicateQS`
     pub fn parse(slice: &'a [u8]) -> Self {
+        Self::parse_with(slice, ParseOptions::new())
+    }
+
+    /// Parse a slice of bytes into a `DuplicateQS`, choosing whether a `+` is decoded as a space
+    /// in keys and in values separately.
+    ///
+    /// `parse` decodes it as a space in both, like form encoding does. Otherwise `+` is kept
+    /// as is, and only `%20` is decoded as a space.
+    pub fn parse_with_plus_as_space(slice: &'a [u8], keys: bool, values: bool) -> Self {
+        let options = ParseOptions::new()
+            .plus_as_space_in_keys(keys)
+            .plus_as_space_in_values(values);
+        Self::parse_with(slice, options)
+    }
+
+    /// Parse a slice of bytes into a `DuplicateQS` with the given options.
+    ///
+    /// More description at ([UrlEncodedQS::parse_with](crate::UrlEncodedQS::parse_with))
+    pub fn parse_with(slice: &'a [u8], options: ParseOptions<'_>) -> Self {
+        with_separators!(options, |separators| Self::parse_separated(
+            slice, separators, options
+        ))
+    }
+
+    fn parse_separated<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        options: ParseOptions<'_>,
+    ) -> Self {
+        let kv = options.kv_separator;
+        let mut stopped = None;
+        let mut count = 0;
         let mut pairs: BTreeMap<Cow<'a, [u8]>, Vec<Pair<'a>>> = BTreeMap::new();
         let mut scratch = Vec::new();
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
+            let pair = Pair::parse(&slice[index..], separators, kv, options.max_key_len);
+            if pair.0.len() > options.max_key_len {
+                stopped = Some(Stopped::KeyLen(options.max_key_len, pair.0 .0));
+                break;
+            }
+            // Empty parts like `&&` are not counted
+            if pair.skip_len() > 1 {
+                count += 1;
+                if count > options.max_input_vars {
+                    stopped = Some(Stopped::InputVars(options.max_input_vars));
+                    break;
+                }
+            }
             index += pair.skip_len();
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let decoded_key = match pair.0.decode(
+                &mut scratch,
+                options.plus_as_space_in_keys,
+                options.strict_percent_decoding,
+            ) {
+                Ok(decoded_key) => decoded_key,
+                Err(at) => {
+                    stopped = Some(Stopped::InvalidKey(pair.0 .0, at));
+                    break;
+                }
+            };
 
             if let Some(values) = pairs.get_mut(decoded_key.as_ref()) {
                 values.push(pair);
@@ -133,7 +220,91 @@ impl<'a> DuplicateQS<'a> {
             }
         }
 
-        Self { pairs }
+        Self {
+            pairs,
+            sort_values: false,
+            plus_as_space: options.plus_as_space_in_values,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: options.strict_percent_decoding,
+            stopped,
+        }
+    }
+
+    /// Sorts the repeated values of each key by their decoded bytes when deserializing,
+    /// so `key=b&key=a` is deserialized the same as `key=a&key=b`.
+    ///
+    /// It is disabled by default, keeping the values in the order they appeared in the querystring.
+    /// The `values` and `value` methods are not affected.
+    pub fn sort_values(mut self, sort: bool) -> Self {
+        self.sort_values = sort;
+        self
+    }
+
+    /// Whether the parsing stopped before the end of the slice, for a limit of the options or
+    /// an invalid key with `strict_percent_decoding`. The pairs after it are not parsed and
+    /// deserializing fails.
+    pub fn is_truncated(&self) -> bool {
+        self.stopped.is_some()
+    }
+
+    /// Parses the boolean values ignoring their case when deserializing, so `True`, `FALSE`,
+    /// `On` and `oFF` are accepted too. It is disabled by default.
+    pub fn case_insensitive_bool(mut self, enabled: bool) -> Self {
+        self.case_insensitive_bool = enabled;
+        self
+    }
+
+    /// Deserializes the keys without a value, ex. `verbose` in `verbose&page=2`, as `Some` of an
+    /// empty value for the optional fields, instead of `None`. It is disabled by default.
+    ///
+    /// The rule is the same for every type, so a bare key is `Some(true)` for an `Option<bool>`
+    /// but an `InvalidNumber` error for an `Option<u32>`, like an empty value is.
+    pub fn bare_keys_as_some(mut self, enabled: bool) -> Self {
+        self.bare_keys_as_some = enabled;
+        self
+    }
+
+    /// Encodes the parsed querystring back into a canonical form, ex. for cache keys.
+    ///
+    /// Keys are sorted, each with all of its values in the order they appeared in the querystring,
+    /// or sorted too if `sort_values` is enabled. Keys and values are percent decoded and encoded
+    /// again so only the unreserved characters are left as is, and a space is always `%20`.
+    /// Assignments without a value are kept bare.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::DuplicateQS;
+    ///
+    /// let first = DuplicateQS::parse(b"tag=b&page=2&tag=a+c").sort_values(true);
+    /// let second = DuplicateQS::parse(b"page=2&tag=a%20c&tag=b").sort_values(true);
+    ///
+    /// assert_eq!(first.to_normalized_string(), "page=2&tag=a%20c&tag=b");
+    /// assert_eq!(first.to_normalized_string(), second.to_normalized_string());
+    /// ```
+    pub fn to_normalized_string(&self) -> String {
+        let mut out = String::new();
+        let mut scratch = Vec::new();
+
+        for (key, pairs) in &self.pairs {
+            let mut values: Vec<Option<Cow<'a, [u8]>>> = pairs
+                .iter()
+                .map(|p| {
+                    p.1.as_ref()
+                        .map(|v| v.decode(&mut scratch, self.plus_as_space).into_cow())
+                })
+                .collect();
+            if self.sort_values {
+                values.sort();
+            }
+
+            for value in &values {
+                push_normalized_pair(&mut out, key, value.as_deref());
+            }
+        }
+
+        out
     }
 
     /// Returns a vector containing all the keys in querystring.
@@ -155,7 +326,45 @@ impl<'a> DuplicateQS<'a> {
             self.pairs
                 .get(key)?
                 .iter()
-                .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
+                .map(|p| {
+                    p.1.as_ref()
+                        .map(|v| v.decode(&mut scratch, self.plus_as_space).into_cow())
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns a vector containing all the values assigned to a key in reverse order, so the last
+    /// assignment comes first.
+    ///
+    /// It returns None if the **key doesn't exist** in the querystring, like `values`.
+    ///
+    /// # Note
+    /// Percent decoding the value is done on-the-fly **every time** this function is called.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::DuplicateQS;
+    ///
+    /// let parser = DuplicateQS::parse(b"key=first&key&key=last");
+    ///
+    /// assert_eq!(
+    ///     parser.values_rev(b"key"),
+    ///     Some(vec![Some("last".as_bytes().into()), None, Some("first".as_bytes().into())])
+    /// );
+    /// ```
+    pub fn values_rev(&self, key: &'a [u8]) -> Option<Vec<Option<Cow<'a, [u8]>>>> {
+        let mut scratch = Vec::new();
+
+        Some(
+            self.pairs
+                .get(key)?
+                .iter()
+                .rev()
+                .map(|p| {
+                    p.1.as_ref()
+                        .map(|v| v.decode(&mut scratch, self.plus_as_space).into_cow())
+                })
                 .collect(),
         )
     }
@@ -170,11 +379,38 @@ impl<'a> DuplicateQS<'a> {
     pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
         let mut scratch = Vec::new();
 
-        self.pairs
-            .get(key)?
-            .iter()
-            .last()
-            .map(|p| p.1.as_ref().map(|v| v.decode(&mut scratch).into_cow()))
+        self.pairs.get(key)?.iter().last().map(|p| {
+            p.1.as_ref()
+                .map(|v| v.decode(&mut scratch, self.plus_as_space).into_cow())
+        })
+    }
+
+    /// Returns the last value assigned to a key, as owned bytes.
+    ///
+    /// It works like the `value` method, but the result doesn't borrow from the input
+    /// so it can be stored independently.
+    pub fn value_owned(&self, key: &'a [u8]) -> Option<Option<Vec<u8>>> {
+        self.value(key).map(|v| v.map(Cow::into_owned))
+    }
+}
+
+impl<'a> PartialEq for DuplicateQS<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl<'a> Eq for DuplicateQS<'a> {}
+
+impl<'a> Hash for DuplicateQS<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pairs.hash(state);
+    }
+}
+
+impl<'a> fmt::Display for DuplicateQS<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(f, "DuplicateQS", self.pairs.keys())
     }
 }
 
@@ -183,8 +419,8 @@ mod de {
     use _serde::Deserialize;
 
     use crate::de::{
+        __implementors::{DecodedSlice, IntoRawSlices, RawSlice, Scratch},
         Error, ErrorKind, QSDeserializer,
-        __implementors::{DecodedSlice, IntoRawSlices, RawSlice},
     };
 
     use super::DuplicateQS;
@@ -192,7 +428,26 @@ mod de {
     impl<'a> DuplicateQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.check_truncated()?;
+            let scratch = self.scratch();
+            T::deserialize(QSDeserializer::with_scratch(self.into_iter(), scratch))
+        }
+
+        /// The scratch the values are decoded into, with the settings of the parser
+        fn scratch(&self) -> Scratch {
+            let mut scratch = Scratch::default();
+            scratch.strict_percent_decoding = self.strict_percent_decoding;
+            scratch.case_insensitive_bool = self.case_insensitive_bool;
+            scratch.bare_keys_as_some = self.bare_keys_as_some;
+            scratch
+        }
+
+        /// Returns an error for the reason the parsing stopped before the end of the slice
+        pub(crate) fn check_truncated(&self) -> Result<(), Error> {
+            match self.stopped {
+                Some(stopped) => Err(stopped.into()),
+                None => Ok(()),
+            }
         }
 
         pub(crate) fn into_iter(
@@ -203,20 +458,45 @@ mod de {
                 DuplicateValueIter<impl Iterator<Item = RawSlice<'a>>>,
             ),
         > {
-            self.pairs.into_iter().map(|(key, pairs)| {
+            let sort_values = self.sort_values;
+            let plus_as_space = self.plus_as_space;
+            let mut scratch = Vec::new();
+
+            self.pairs.into_iter().map(move |(key, mut pairs)| {
+                if sort_values {
+                    pairs.sort_by_cached_key(|p| {
+                        p.1.as_ref()
+                            .map(|v| v.decode(&mut scratch, plus_as_space).into_cow())
+                            .unwrap_or_default()
+                    });
+                }
+
+                let empty = pairs.last().map_or(true, |p| {
+                    p.1.as_ref().map_or(true, |v| v.slice().is_empty())
+                });
+                let bare = pairs.iter().all(|p| p.1.is_none());
+
                 (
                     DecodedSlice(key),
-                    DuplicateValueIter(
-                        pairs
-                            .into_iter()
-                            .map(|v| RawSlice(v.1.map(|v| v.slice()).unwrap_or_default())),
-                    ),
+                    DuplicateValueIter {
+                        iter: pairs.into_iter().map(move |v| {
+                            RawSlice::new(v.1.map(|v| v.slice()).unwrap_or_default(), plus_as_space)
+                        }),
+                        empty,
+                        bare,
+                    },
                 )
             })
         }
     }
 
-    pub(crate) struct DuplicateValueIter<I>(I);
+    pub(crate) struct DuplicateValueIter<I> {
+        iter: I,
+        // Whether the last value is empty
+        empty: bool,
+        // Whether no assignment of the key has a value at all
+        bare: bool,
+    }
 
     impl<'a, I> IntoRawSlices<'a> for DuplicateValueIter<I>
     where
@@ -224,11 +504,12 @@ mod de {
     {
         type SizedIterator = I;
         type UnSizedIterator = I;
+        type Entries = std::iter::Empty<(RawSlice<'a>, RawSlice<'a>)>;
 
         #[inline]
         fn into_sized_iterator(self, size: usize) -> Result<I, Error> {
-            if self.0.size_hint().0 == size {
-                Ok(self.0)
+            if self.iter.size_hint().0 == size {
+                Ok(self.iter)
             } else {
                 Err(Error::new(ErrorKind::InvalidLength))
             }
@@ -236,15 +517,35 @@ mod de {
 
         #[inline]
         fn into_unsized_iterator(self) -> I {
-            self.0
+            self.iter
         }
 
         #[inline]
         fn into_single_slice(self) -> RawSlice<'a> {
-            self.0
+            self.iter
                 .last()
                 .expect("Iterator has at least one value in it")
         }
+
+        #[inline]
+        fn into_entries(self) -> Result<Self::Entries, Self> {
+            Err(self)
+        }
+
+        #[inline]
+        fn is_empty(&self) -> bool {
+            self.empty
+        }
+
+        #[inline]
+        fn is_bare(&self) -> bool {
+            self.bare
+        }
+
+        #[inline]
+        fn is_repeated(&self) -> bool {
+            self.iter.size_hint().0 > 1
+        }
     }
 }
 
@@ -252,7 +553,7 @@ mod de {
 mod tests {
     use std::borrow::Cow;
 
-    use super::DuplicateQS;
+    use super::{DuplicateQS, ParseOptions};
 
     #[test]
     fn parse_pair() {
@@ -271,6 +572,23 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_with_options() {
+        let slice = b"a=1;a=2&b=3;a";
+
+        let parser = DuplicateQS::parse_with(slice, ParseOptions::new().separators(b"&;"));
+
+        assert_eq!(
+            parser.values(b"a"),
+            Some(vec![
+                Some("1".as_bytes().into()),
+                Some("2".as_bytes().into()),
+                None
+            ])
+        );
+        assert_eq!(parser.values(b"b"), Some(vec![Some("3".as_bytes().into())]));
+    }
+
     #[test]
     fn parse_multiple_pairs() {
         let slice = b"foo=bar&foobar=baz&qux=box";
@@ -327,4 +645,76 @@ mod tests {
 
         assert_eq!(parser.value(b"foo"), Some(Some("".as_bytes().into())));
     }
+
+    #[test]
+    fn parse_multiple_values_rev() {
+        let slice = b"foo=bar&foo&foo=baz%20qux&bar=1";
+
+        let parser = DuplicateQS::parse(slice);
+
+        assert_eq!(
+            parser.values_rev(b"foo"),
+            Some(vec![
+                Some("baz qux".as_bytes().into()),
+                None,
+                Some("bar".as_bytes().into()),
+            ])
+        );
+        assert_eq!(
+            parser.values_rev(b"bar"),
+            Some(vec![Some("1".as_bytes().into())])
+        );
+        assert_eq!(parser.values_rev(b"baz"), None);
+    }
+
+    #[test]
+    fn parse_value_owned() {
+        let slice = b"foo=bar&foo=bar%20baz&foo2";
+
+        let parser = DuplicateQS::parse(slice);
+
+        assert_eq!(parser.value_owned(b"foo"), Some(Some(b"bar baz".to_vec())));
+        assert_eq!(parser.value_owned(b"foo2"), Some(None));
+        assert_eq!(parser.value_owned(b"foo3"), None);
+    }
+
+    #[test]
+    fn parsed_equality() {
+        assert!(
+            DuplicateQS::parse(b"foo=1&bar=2&foo=3") == DuplicateQS::parse(b"bar=2&foo=1&foo=3")
+        );
+
+        // Repeated values are compared in order, before being decoded
+        assert!(DuplicateQS::parse(b"foo=1&foo=3") != DuplicateQS::parse(b"foo=3&foo=1"));
+        assert!(DuplicateQS::parse(b"foo=a+b") != DuplicateQS::parse(b"foo=a%20b"));
+        assert!(DuplicateQS::parse(b"foo") != DuplicateQS::parse(b"foo="));
+
+        // The options only used when deserializing are not compared
+        assert!(DuplicateQS::parse(b"foo=1") == DuplicateQS::parse(b"foo=1").sort_values(true));
+    }
+
+    #[test]
+    fn normalized_string() {
+        // Key order and the encoding of the values don't matter
+        assert_eq!(
+            DuplicateQS::parse(b"foo=1&b%61r=a+b&foo=3&baz").to_normalized_string(),
+            "bar=a%20b&baz&foo=1&foo=3"
+        );
+        assert_eq!(
+            DuplicateQS::parse(b"baz&bar=a%20b&foo=1&foo=3").to_normalized_string(),
+            "bar=a%20b&baz&foo=1&foo=3"
+        );
+
+        // Repeated values are kept in order, unless they are sorted
+        assert_eq!(
+            DuplicateQS::parse(b"foo=3&foo&foo=1").to_normalized_string(),
+            "foo=3&foo&foo=1"
+        );
+        assert_eq!(
+            DuplicateQS::parse(b"foo=3&foo&foo=1")
+                .sort_values(true)
+                .to_normalized_string(),
+            "foo&foo=1&foo=3"
+        );
+    }
 }
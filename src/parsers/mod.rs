@@ -1,9 +1,61 @@
+use std::{borrow::Cow, fmt};
+
+use crate::decode::encode_bytes;
+
 mod brackets;
 mod delimiter;
 mod duplicate;
+mod options;
 mod urlencoded;
 
 pub use brackets::BracketsQS;
 pub use delimiter::DelimiterQS;
 pub use duplicate::DuplicateQS;
-pub use urlencoded::UrlEncodedQS;
+pub use options::ParseOptions;
+pub use urlencoded::{BorrowedPairsError, UrlEncodedQS};
+
+use options::{bound_key, with_separators, Separators, Stopped};
+
+/// The number of keys listed by the `Display` implementations before truncating
+const DISPLAYED_KEYS: usize = 5;
+
+/// Writes a summary of the parsed keys, ex `UrlEncodedQS(3 keys: bar, baz, foo)`
+///
+/// Only the keys are written, values are neither decoded nor included.
+fn fmt_summary<'k, 'a: 'k, I>(f: &mut fmt::Formatter<'_>, name: &str, keys: I) -> fmt::Result
+where
+    I: ExactSizeIterator<Item = &'k Cow<'a, [u8]>>,
+{
+    let len = keys.len();
+    write!(
+        f,
+        "{}({} {}",
+        name,
+        len,
+        if len == 1 { "key" } else { "keys" }
+    )?;
+
+    for (index, key) in keys.take(DISPLAYED_KEYS).enumerate() {
+        let separator = if index == 0 { ": " } else { ", " };
+        write!(f, "{}{}", separator, String::from_utf8_lossy(key))?;
+    }
+    if len > DISPLAYED_KEYS {
+        f.write_str(", ...")?;
+    }
+
+    f.write_str(")")
+}
+
+/// Appends a pair to a normalized querystring, percent encoding everything beside the
+/// unreserved characters of RFC 3986, so a space is always `%20`. A pair without a value is
+/// written as a bare key.
+fn push_normalized_pair(out: &mut String, key: &[u8], value: Option<&[u8]>) {
+    if !out.is_empty() {
+        out.push('&');
+    }
+    encode_bytes(key, out, false);
+    if let Some(value) = value {
+        out.push('=');
+        encode_bytes(value, out, false);
+    }
+}
@@ -0,0 +1,216 @@
+/// The options of the parsers, shared by all of them and passed to their `parse_with` method
+///
+/// The defaults are the ones of `parse`, pairs are split on `&` and a `+` is decoded as a space.
+///
+/// # Example
+/// ```rust
+///# use std::borrow::Cow;
+/// use serde_querystring::{ParseOptions, UrlEncodedQS};
+///
+/// let options = ParseOptions::new()
+///     .separators(b"&;")
+///     .plus_as_space_in_values(false);
+/// let parser = UrlEncodedQS::parse_with(b"a=1;b=x+y", options);
+///
+/// assert_eq!(parser.value(b"a"), Some(Some(Cow::Borrowed(&b"1"[..]))));
+/// assert_eq!(parser.value(b"b"), Some(Some(Cow::Borrowed(&b"x+y"[..]))));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOptions<'s> {
+    pub(crate) separators: &'s [u8],
+    pub(crate) kv_separator: u8,
+    pub(crate) plus_as_space_in_keys: bool,
+    pub(crate) plus_as_space_in_values: bool,
+    pub(crate) max_key_len: usize,
+    pub(crate) max_input_vars: usize,
+    pub(crate) strict_percent_decoding: bool,
+}
+
+impl<'s> ParseOptions<'s> {
+    /// Creates the default options
+    pub fn new() -> Self {
+        Self {
+            separators: b"&",
+            kv_separator: b'=',
+            plus_as_space_in_keys: true,
+            plus_as_space_in_values: true,
+            max_key_len: usize::MAX,
+            max_input_vars: usize::MAX,
+            strict_percent_decoding: false,
+        }
+    }
+
+    /// Splits the pairs on any of the separators, it is `&` by default.
+    ///
+    /// It makes it possible to accept `;` along with `&`, ex. `a=1;b=2`, as the old W3C
+    /// recommendation for html forms did. In `Delimiter` mode the delimiter should not be one of
+    /// the separators, as the values are split on the separators first.
+    pub fn separators(mut self, separators: &'s [u8]) -> Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Uses another separator than `=` between the keys and the values, ex. `a:1&b:2` with
+    /// `b':'` for legacy systems. It is `=` by default.
+    ///
+    /// Only the first separator of a pair ends its key, so the value may contain it. The brackets
+    /// of the keys in `Brackets` mode are not affected, ex. `a[b]:1`.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::borrow::Cow;
+    /// use serde_querystring::{ParseOptions, UrlEncodedQS};
+    ///
+    /// let parser = UrlEncodedQS::parse_with(b"a:1&b:2:3", ParseOptions::new().kv_separator(b':'));
+    ///
+    /// assert_eq!(parser.value(b"a"), Some(Some(Cow::Borrowed(&b"1"[..]))));
+    /// assert_eq!(parser.value(b"b"), Some(Some(Cow::Borrowed(&b"2:3"[..]))));
+    /// ```
+    pub fn kv_separator(mut self, separator: u8) -> Self {
+        self.kv_separator = separator;
+        self
+    }
+
+    /// Decodes a `+` in the keys as a space, like form encoding does. It is enabled by default,
+    /// when disabled `+` is kept as is and only `%20` is decoded as a space.
+    pub fn plus_as_space_in_keys(mut self, enabled: bool) -> Self {
+        self.plus_as_space_in_keys = enabled;
+        self
+    }
+
+    /// Decodes a `+` in the values as a space, like form encoding does. It is enabled by default,
+    /// when disabled `+` is kept as is and only `%20` is decoded as a space.
+    pub fn plus_as_space_in_values(mut self, enabled: bool) -> Self {
+        self.plus_as_space_in_values = enabled;
+        self
+    }
+
+    /// Limits the length of each key in bytes, the pairs are parsed up to the first longer key
+    /// and deserializing fails with a `LimitExceeded` error. There is no limit by default.
+    ///
+    /// The keys are scanned up to the limit, so a huge key is rejected before it's decoded or
+    /// stored. The length is of the key as it appears in the querystring, before percent
+    /// decoding and including the sub keys in brackets mode.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::{ParseOptions, UrlEncodedQS};
+    ///
+    /// let parser = UrlEncodedQS::parse_with(b"a=1&long_key=2", ParseOptions::new().max_key_len(4));
+    ///
+    /// assert!(parser.is_truncated());
+    /// assert_eq!(parser.keys().len(), 1);
+    /// ```
+    pub fn max_key_len(mut self, limit: usize) -> Self {
+        self.max_key_len = limit;
+        self
+    }
+
+    /// Limits the total number of pairs, like PHP's `max_input_vars`, the pairs are parsed up to
+    /// the limit and deserializing fails with a `LimitExceeded` error. There is no limit by default.
+    ///
+    /// Every non-empty part between the separators counts as a pair, including repeated keys,
+    /// each element of a sequence and each sub key in brackets mode. The values are not split,
+    /// so a delimited list counts as a single pair.
+    pub fn max_input_vars(mut self, limit: usize) -> Self {
+        self.max_input_vars = limit;
+        self
+    }
+
+    /// Rejects a `%` which is not followed by two hex digits, instead of keeping it as is. It is
+    /// disabled by default.
+    ///
+    /// The keys are checked while parsing, the pairs are parsed up to the first invalid key and
+    /// deserializing fails with an `InvalidEncoding` error. The values are checked when they're
+    /// deserialized, so the values of the ignored or unknown keys are not rejected.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::{ParseOptions, UrlEncodedQS};
+    ///
+    /// let options = ParseOptions::new().strict_percent_decoding(true);
+    ///
+    /// assert!(!UrlEncodedQS::parse_with(b"a=100%&b=1", options).is_truncated());
+    /// assert!(UrlEncodedQS::parse_with(b"a%=1&b=1", options).is_truncated());
+    /// ```
+    pub fn strict_percent_decoding(mut self, strict: bool) -> Self {
+        self.strict_percent_decoding = strict;
+        self
+    }
+}
+
+/// Why the parsing stopped before the end of the slice, the pairs after it are dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum Stopped<'a> {
+    /// The limit and the beginning of the longer key
+    KeyLen(usize, &'a [u8]),
+    /// The limit of the number of pairs
+    InputVars(usize),
+    /// The key and the index of its invalid `%`, with `strict_percent_decoding`
+    InvalidKey(&'a [u8], usize),
+}
+
+/// Scanning stops one byte past the maximum length of a key, it's enough to tell it's too long
+#[inline]
+pub(crate) fn bound_key(slice: &[u8], max_len: usize) -> &[u8] {
+    &slice[..slice.len().min(max_len.saturating_add(1))]
+}
+
+#[cfg(feature = "serde")]
+impl<'a> From<Stopped<'a>> for crate::de::Error {
+    fn from(stopped: Stopped<'a>) -> Self {
+        use crate::de::{Error, ErrorKind};
+
+        match stopped {
+            Stopped::KeyLen(limit, key) => Error::new(ErrorKind::LimitExceeded)
+                .message(format!("the key is longer than {} bytes", limit))
+                .value(&key[..limit]),
+            Stopped::InputVars(limit) => Error::new(ErrorKind::LimitExceeded)
+                .message(format!("the querystring has more than {} pairs", limit)),
+            Stopped::InvalidKey(key, index) => Error::invalid_percent(key, index),
+        }
+    }
+}
+
+impl<'s> Default for ParseOptions<'s> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The bytes ending a pair
+///
+/// The parsers are generic over it, so the usual single separator is compared directly in their
+/// loops instead of searching a slice for each byte.
+pub(crate) trait Separators: Copy {
+    fn matches(self, c: u8) -> bool;
+}
+
+impl Separators for u8 {
+    #[inline]
+    fn matches(self, c: u8) -> bool {
+        self == c
+    }
+}
+
+impl Separators for &[u8] {
+    #[inline]
+    fn matches(self, c: u8) -> bool {
+        self.contains(&c)
+    }
+}
+
+/// Calls `$parse` with the separators of the options, as a single byte when there's only one
+macro_rules! with_separators {
+    ($options:expr, |$separators:ident| $parse:expr) => {
+        match $options.separators {
+            [$separators] => {
+                let $separators = *$separators;
+                $parse
+            }
+            $separators => $parse,
+        }
+    };
+}
+
+pub(crate) use with_separators;
@@ -1,15 +1,29 @@
-use std::{borrow::Cow, collections::BTreeMap};
-
-use crate::decode::{parse_bytes, Reference};
-
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Bound,
+    str::{self, Utf8Error},
+};
+
+use crate::decode::{parse_bytes, parse_bytes_lenient, Reference};
+
+use super::{
+    bound_key, fmt_summary, push_normalized_pair, with_separators, ParseOptions, Separators,
+    Stopped,
+};
+
+#[derive(PartialEq, Eq, Hash)]
 struct Key<'a>(&'a [u8]);
 
 impl<'a> Key<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_len: usize) -> Self {
+        let slice = bound_key(slice, max_len);
         let mut index = 0;
         while index < slice.len() {
             match slice[index] {
-                b'&' | b'=' => break,
+                c if c == kv || separators.matches(c) => break,
                 _ => index += 1,
             }
         }
@@ -21,25 +35,31 @@ impl<'a> Key<'a> {
         self.0.len()
     }
 
-    fn decode<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+        strict: bool,
+    ) -> Result<Reference<'a, 's, [u8]>, usize> {
+        parse_bytes(self.0, scratch, plus_as_space, None, strict)
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
 struct Value<'a>(&'a [u8]);
 
 impl<'a> Value<'a> {
-    fn parse(slice: &'a [u8]) -> Option<Self> {
-        if *slice.first()? == b'&' {
+    fn parse<S: Separators>(slice: &'a [u8], separators: S) -> Option<Self> {
+        if separators.matches(*slice.first()?) {
             return None;
         }
 
         let mut index = 1;
         while index < slice.len() {
-            match slice[index] {
-                b'&' => break,
-                _ => index += 1,
+            if separators.matches(slice[index]) {
+                break;
             }
+            index += 1;
         }
 
         Some(Self(&slice[1..index]))
@@ -49,17 +69,22 @@ impl<'a> Value<'a> {
         self.0.len()
     }
 
-    fn decode_to<'s>(&self, scratch: &'s mut Vec<u8>) -> Reference<'a, 's, [u8]> {
-        parse_bytes(self.0, scratch)
+    fn decode_to<'s>(
+        &self,
+        scratch: &'s mut Vec<u8>,
+        plus_as_space: bool,
+    ) -> Reference<'a, 's, [u8]> {
+        parse_bytes_lenient(self.0, scratch, plus_as_space, None)
     }
 }
 
+#[derive(PartialEq, Eq, Hash)]
 struct Pair<'a>(Key<'a>, Option<Value<'a>>);
 
 impl<'a> Pair<'a> {
-    fn parse(slice: &'a [u8]) -> Self {
-        let key = Key::parse(slice);
-        let value = Value::parse(&slice[key.len()..]);
+    fn parse<S: Separators>(slice: &'a [u8], separators: S, kv: u8, max_key_len: usize) -> Self {
+        let key = Key::parse(slice, separators, kv, max_key_len);
+        let value = Value::parse(&slice[key.len()..], separators);
 
         Self(key, value)
     }
@@ -85,6 +110,11 @@ impl<'a> Pair<'a> {
 /// Keys are decoded when calling the `parse` method, but values are lazily decoded when you
 /// call the `value` method for their keys.
 ///
+/// Two parsers are equal when they have the same keys, each assigned the same last value.
+/// The order of the keys doesn't matter, and pairs are compared as they appear in the
+/// querystring, so `key=%41` and `key=A` are not equal. The options are not compared, as they
+/// only change how the pairs are decoded and deserialized.
+///
 /// # Example
 /// ```rust
 ///# use std::borrow::Cow;
@@ -102,21 +132,117 @@ impl<'a> Pair<'a> {
 /// ```
 pub struct UrlEncodedQS<'a> {
     pairs: BTreeMap<Cow<'a, [u8]>, Pair<'a>>,
+    decode: bool,
+    plus_as_space: bool,
+    case_insensitive_bool: bool,
+    bare_keys_as_some: bool,
+    #[cfg(feature = "serde")]
+    strict_percent_decoding: bool,
+    stopped: Option<Stopped<'a>>,
 }
 
 impl<'a> UrlEncodedQS<'a> {
     /// Parse a slice of bytes into a `UrlEncodedQS`
     pub fn parse(slice: &'a [u8]) -> Self {
+        Self::parse_with(slice, ParseOptions::new())
+    }
+
+    /// Parse a slice of bytes into a `UrlEncodedQS`, choosing whether a `+` is decoded as a space
+    /// in keys and in values separately.
+    ///
+    /// `parse` decodes it as a space in both, like form encoding does. Otherwise `+` is kept
+    /// as is, and only `%20` is decoded as a space.
+    pub fn parse_with_plus_as_space(slice: &'a [u8], keys: bool, values: bool) -> Self {
+        let options = ParseOptions::new()
+            .plus_as_space_in_keys(keys)
+            .plus_as_space_in_values(values);
+        Self::parse_with(slice, options)
+    }
+
+    /// Parse the matrix parameters of a path segment into a `UrlEncodedQS`, ex. `a=1;b=2` in
+    /// `/cars;color=red;year=2012`.
+    ///
+    /// Pairs are separated by `;` instead of `&`, which is a plain character in them. A `+` is
+    /// kept as is, as it's not a space in paths, and only `%20` is decoded as a space.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::borrow::Cow;
+    /// use serde_querystring::UrlEncodedQS;
+    ///
+    /// let parser = UrlEncodedQS::parse_matrix(b"color=red;name=a+b&c");
+    ///
+    /// assert_eq!(parser.value(b"color"), Some(Some(Cow::Borrowed(&b"red"[..]))));
+    /// assert_eq!(parser.value(b"name"), Some(Some(Cow::Borrowed(&b"a+b&c"[..]))));
+    /// ```
+    pub fn parse_matrix(slice: &'a [u8]) -> Self {
+        let options = ParseOptions::new()
+            .separators(b";")
+            .plus_as_space_in_keys(false)
+            .plus_as_space_in_values(false);
+        Self::parse_with(slice, options)
+    }
+
+    /// Parse a slice of bytes into a `UrlEncodedQS` with the given options, ex. to split the
+    /// pairs on `;` along with `&`.
+    ///
+    /// # Example
+    /// ```rust
+    ///# use std::borrow::Cow;
+    /// use serde_querystring::{ParseOptions, UrlEncodedQS};
+    ///
+    /// let parser = UrlEncodedQS::parse_with(b"a=1;b=2&c=3", ParseOptions::new().separators(b"&;"));
+    ///
+    /// assert_eq!(parser.value(b"a"), Some(Some(Cow::Borrowed(&b"1"[..]))));
+    /// assert_eq!(parser.value(b"b"), Some(Some(Cow::Borrowed(&b"2"[..]))));
+    /// assert_eq!(parser.value(b"c"), Some(Some(Cow::Borrowed(&b"3"[..]))));
+    /// ```
+    pub fn parse_with(slice: &'a [u8], options: ParseOptions<'_>) -> Self {
+        with_separators!(options, |separators| Self::parse_separated(
+            slice, separators, options
+        ))
+    }
+
+    fn parse_separated<S: Separators>(
+        slice: &'a [u8],
+        separators: S,
+        options: ParseOptions<'_>,
+    ) -> Self {
+        let kv = options.kv_separator;
+        let mut stopped = None;
+        let mut count = 0;
         let mut pairs = BTreeMap::new();
         let mut scratch = Vec::new();
 
         let mut index = 0;
 
         while index < slice.len() {
-            let pair = Pair::parse(&slice[index..]);
+            let pair = Pair::parse(&slice[index..], separators, kv, options.max_key_len);
+            if pair.0.len() > options.max_key_len {
+                stopped = Some(Stopped::KeyLen(options.max_key_len, pair.0 .0));
+                break;
+            }
+            // Empty parts like `&&` are not counted
+            if pair.skip_len() > 1 {
+                count += 1;
+                if count > options.max_input_vars {
+                    stopped = Some(Stopped::InputVars(options.max_input_vars));
+                    break;
+                }
+            }
             index += pair.skip_len();
 
-            let decoded_key = pair.0.decode(&mut scratch);
+            let decoded_key = match pair.0.decode(
+                &mut scratch,
+                options.plus_as_space_in_keys,
+                options.strict_percent_decoding,
+            ) {
+                Ok(decoded_key) => decoded_key,
+                Err(at) => {
+                    stopped = Some(Stopped::InvalidKey(pair.0 .0, at));
+                    break;
+                }
+            };
 
             if let Some(old_pair) = pairs.get_mut(decoded_key.as_ref()) {
                 *old_pair = pair;
@@ -125,7 +251,165 @@ impl<'a> UrlEncodedQS<'a> {
             }
         }
 
-        Self { pairs }
+        Self {
+            pairs,
+            decode: true,
+            plus_as_space: options.plus_as_space_in_values,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: options.strict_percent_decoding,
+            stopped,
+        }
+    }
+
+    /// Parse a slice of bytes into a `UrlEncodedQS` without percent decoding keys and values
+    ///
+    /// Keys and values are used exactly as they appear in the slice, so `%` and `+` are kept as is.
+    ///
+    /// # Note
+    /// This is only meant for inputs which are known to contain no percent encoded characters
+    /// nor `+`, it skips the decoding work but gives wrong results for encoded inputs.
+    pub fn parse_no_decode(slice: &'a [u8]) -> Self {
+        let mut pairs = BTreeMap::new();
+
+        let mut index = 0;
+
+        while index < slice.len() {
+            let pair = Pair::parse(&slice[index..], b'&', b'=', usize::MAX);
+            index += pair.skip_len();
+
+            pairs.insert(Cow::Borrowed(pair.0 .0), pair);
+        }
+
+        Self {
+            pairs,
+            decode: false,
+            plus_as_space: true,
+            case_insensitive_bool: false,
+            bare_keys_as_some: false,
+            #[cfg(feature = "serde")]
+            strict_percent_decoding: false,
+            stopped: None,
+        }
+    }
+
+    /// Whether the parsing stopped before the end of the slice, for a limit of the options or
+    /// an invalid key with `strict_percent_decoding`. The pairs after it are not parsed and
+    /// deserializing fails.
+    pub fn is_truncated(&self) -> bool {
+        self.stopped.is_some()
+    }
+
+    /// Parses the boolean values ignoring their case when deserializing, so `True`, `FALSE`,
+    /// `On` and `oFF` are accepted too. It is disabled by default.
+    ///
+    /// Parsers from `parse_no_decode` are not affected, as they don't parse their values.
+    pub fn case_insensitive_bool(mut self, enabled: bool) -> Self {
+        self.case_insensitive_bool = enabled;
+        self
+    }
+
+    /// Deserializes the keys without a value, ex. `verbose` in `verbose&page=2`, as `Some` of an
+    /// empty value for the optional fields, instead of `None`. It is disabled by default.
+    ///
+    /// The rule is the same for every type, so a bare key is `Some(true)` for an `Option<bool>`
+    /// but an `InvalidNumber` error for an `Option<u32>`, like an empty value is.
+    pub fn bare_keys_as_some(mut self, enabled: bool) -> Self {
+        self.bare_keys_as_some = enabled;
+        self
+    }
+
+    /// Splits a slice into borrowed pairs of keys and values, without any allocation beside the
+    /// vector itself, in the order they appear in the querystring.
+    ///
+    /// Repeated keys are all kept, and stray separators like `&&` are skipped.
+    ///
+    /// # Errors
+    /// Keys and values are returned as they appear in the slice, so it is only meant for clean
+    /// queries. A key or a value containing a `%` or a `+` is an `Encoded` error, use `parse`
+    /// when the query needs decoding. A key or a value which is not valid utf8 is a `Utf8` error.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::UrlEncodedQS;
+    ///
+    /// let pairs = UrlEncodedQS::pairs_borrowed(b"page=2&tag=a&tag=b&flag").unwrap();
+    ///
+    /// assert_eq!(
+    ///     pairs,
+    ///     vec![("page", Some("2")), ("tag", Some("a")), ("tag", Some("b")), ("flag", None)]
+    /// );
+    /// assert!(UrlEncodedQS::pairs_borrowed(b"tag=a+b").is_err());
+    /// ```
+    pub fn pairs_borrowed(
+        slice: &'a [u8],
+    ) -> Result<Vec<(&'a str, Option<&'a str>)>, BorrowedPairsError> {
+        let mut pairs = Vec::new();
+
+        let mut index = 0;
+
+        while index < slice.len() {
+            let pair = Pair::parse(&slice[index..], b'&', b'=', usize::MAX);
+            let start = index;
+            index += pair.skip_len();
+
+            if pair.0.len() == 0 && pair.1.is_none() {
+                continue;
+            }
+
+            if let Some(offset) = slice[start..index.min(slice.len())]
+                .iter()
+                .position(|c| *c == b'%' || *c == b'+')
+            {
+                return Err(BorrowedPairsError::Encoded(start + offset));
+            }
+
+            let value = match pair.1 {
+                Some(value) => Some(str::from_utf8(value.0).map_err(BorrowedPairsError::Utf8)?),
+                None => None,
+            };
+            pairs.push((
+                str::from_utf8(pair.0 .0).map_err(BorrowedPairsError::Utf8)?,
+                value,
+            ));
+        }
+
+        Ok(pairs)
+    }
+
+    /// Encodes the parsed querystring back into a canonical form, ex. for cache keys.
+    ///
+    /// Keys are sorted, each with its last value, and both are percent decoded and encoded again
+    /// so only the unreserved characters are left as is, ex. `b=x+y&a=%7e` and `a=~&b=x%20y`
+    /// are both normalized to `a=~&b=x%20y`. Keys without a value are kept bare.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde_querystring::UrlEncodedQS;
+    ///
+    /// let first = UrlEncodedQS::parse(b"page=2&sort=name+asc&flag");
+    /// let second = UrlEncodedQS::parse(b"flag&sort=name%20asc&page=1&page=2");
+    ///
+    /// assert_eq!(first.to_normalized_string(), "flag&page=2&sort=name%20asc");
+    /// assert_eq!(first.to_normalized_string(), second.to_normalized_string());
+    /// ```
+    pub fn to_normalized_string(&self) -> String {
+        let mut out = String::new();
+        let mut scratch = Vec::new();
+
+        for (key, pair) in &self.pairs {
+            let value = pair.1.as_ref().map(|v| {
+                if self.decode {
+                    v.decode_to(&mut scratch, self.plus_as_space).into_cow()
+                } else {
+                    Cow::Borrowed(v.0)
+                }
+            });
+            push_normalized_pair(&mut out, key, value.as_deref());
+        }
+
+        out
     }
 
     /// Returns a vector containing all the keys in querystring.
@@ -133,6 +417,18 @@ impl<'a> UrlEncodedQS<'a> {
         self.pairs.keys().collect()
     }
 
+    /// Returns a vector containing all the keys in querystring starting with the `prefix`.
+    ///
+    /// # Note
+    /// Keys are kept sorted, so this is a range lookup(`O(log n + k)`) and doesn't scan all the keys.
+    pub fn keys_with_prefix(&self, prefix: &[u8]) -> Vec<&Cow<'a, [u8]>> {
+        self.pairs
+            .range::<[u8], _>((Bound::Included(prefix), Bound::Unbounded))
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .collect()
+    }
+
     /// Returns the last value assigned to a key.
     ///
     /// It returns `None` if the **key doesn't exist** in the querystring,
@@ -141,20 +437,112 @@ impl<'a> UrlEncodedQS<'a> {
     /// # Note
     /// Percent decoding the value is done on-the-fly **every time** this function is called.
     pub fn value(&self, key: &'a [u8]) -> Option<Option<Cow<'a, [u8]>>> {
-        let mut scratch = Vec::new();
+        self.pairs.get(key).map(|p| self.pair_value(p))
+    }
+
+    /// Returns an iterator over all the keys in querystring, along with the last value assigned
+    /// to each of them.
+    ///
+    /// Keys are yielded in sorted order, like `keys`.
+    ///
+    /// # Note
+    /// Percent decoding the values is done on-the-fly, when each pair is yielded.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::borrow::Cow;
+    /// use serde_querystring::UrlEncodedQS;
+    ///
+    /// let parser = UrlEncodedQS::parse(b"b=2&a=1%201&flag");
+    ///
+    /// assert_eq!(
+    ///     parser.iter().collect::<Vec<_>>(),
+    ///     vec![
+    ///         (&Cow::Borrowed("a".as_bytes()), Some("1 1".as_bytes().into())),
+    ///         (&Cow::Borrowed("b".as_bytes()), Some("2".as_bytes().into())),
+    ///         (&Cow::Borrowed("flag".as_bytes()), None),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = (&Cow<'a, [u8]>, Option<Cow<'a, [u8]>>)> + '_ {
         self.pairs
-            .get(key)
-            .map(|p| p.1.as_ref().map(|v| v.decode_to(&mut scratch).into_cow()))
+            .iter()
+            .map(move |(key, pair)| (key, self.pair_value(pair)))
+    }
+
+    fn pair_value(&self, pair: &Pair<'a>) -> Option<Cow<'a, [u8]>> {
+        let mut scratch = Vec::new();
+        pair.1.as_ref().map(|v| {
+            if self.decode {
+                v.decode_to(&mut scratch, self.plus_as_space).into_cow()
+            } else {
+                Cow::Borrowed(v.0)
+            }
+        })
+    }
+
+    /// Returns the last value assigned to a key, as owned bytes.
+    ///
+    /// It works like the `value` method, but the result doesn't borrow from the input
+    /// so it can be stored independently.
+    pub fn value_owned(&self, key: &'a [u8]) -> Option<Option<Vec<u8>>> {
+        self.value(key).map(|v| v.map(Cow::into_owned))
+    }
+}
+
+impl<'a> PartialEq for UrlEncodedQS<'a> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pairs == other.pairs
+    }
+}
+
+impl<'a> Eq for UrlEncodedQS<'a> {}
+
+impl<'a> Hash for UrlEncodedQS<'a> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.pairs.hash(state);
+    }
+}
+
+impl<'a> fmt::Display for UrlEncodedQS<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_summary(f, "UrlEncodedQS", self.pairs.keys())
+    }
+}
+
+/// The error of `UrlEncodedQS::pairs_borrowed`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BorrowedPairsError {
+    /// A key or a value needs decoding, with the index of its first `%` or `+` in the slice
+    Encoded(usize),
+    /// A key or a value is not valid utf8
+    Utf8(Utf8Error),
+}
+
+impl fmt::Display for BorrowedPairsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowedPairsError::Encoded(index) => write!(
+                f,
+                "the querystring needs decoding, found an encoded character at index {}",
+                index
+            ),
+            BorrowedPairsError::Utf8(e) => e.fmt(f),
+        }
     }
 }
 
+impl std::error::Error for BorrowedPairsError {}
+
 #[cfg(feature = "serde")]
 mod de {
+    use std::borrow::Cow;
+
     use _serde::Deserialize;
 
     use crate::de::{
+        __implementors::{DecodedSlice, RawSlice, Scratch, SingleValue},
         Error, QSDeserializer,
-        __implementors::{DecodedSlice, RawSlice},
     };
 
     use super::UrlEncodedQS;
@@ -162,15 +550,55 @@ mod de {
     impl<'a> UrlEncodedQS<'a> {
         /// Deserialize the parsed slice into T
         pub fn deserialize<T: Deserialize<'a>>(self) -> Result<T, Error> {
-            T::deserialize(QSDeserializer::new(self.into_iter()))
+            self.check_truncated()?;
+            if self.decode {
+                let scratch = self.scratch();
+                T::deserialize(QSDeserializer::with_scratch(self.into_iter(), scratch))
+            } else {
+                T::deserialize(QSDeserializer::new(self.into_raw_iter()))
+            }
+        }
+
+        /// The scratch the values are decoded into, with the settings of the parser
+        fn scratch(&self) -> Scratch {
+            let mut scratch = Scratch::default();
+            scratch.strict_percent_decoding = self.strict_percent_decoding;
+            scratch.case_insensitive_bool = self.case_insensitive_bool;
+            scratch.bare_keys_as_some = self.bare_keys_as_some;
+            scratch
+        }
+
+        /// Returns an error for the reason the parsing stopped before the end of the slice
+        pub(crate) fn check_truncated(&self) -> Result<(), Error> {
+            match self.stopped {
+                Some(stopped) => Err(stopped.into()),
+                None => Ok(()),
+            }
         }
 
         pub(crate) fn into_iter(
             self,
-        ) -> impl Iterator<Item = (DecodedSlice<'a>, Option<RawSlice<'a>>)> {
-            self.pairs
-                .into_iter()
-                .map(|(key, pair)| (DecodedSlice(key), pair.1.map(|v| RawSlice(v.0))))
+        ) -> impl Iterator<Item = (DecodedSlice<'a>, SingleValue<Option<RawSlice<'a>>>)> {
+            let plus_as_space = self.plus_as_space;
+            self.pairs.into_iter().map(move |(key, pair)| {
+                (
+                    DecodedSlice(key),
+                    SingleValue(pair.1.map(|v| RawSlice::new(v.0, plus_as_space))),
+                )
+            })
+        }
+
+        /// Values are handed over as if they're already decoded, to skip the decoding
+        fn into_raw_iter(
+            self,
+        ) -> impl Iterator<Item = (DecodedSlice<'a>, SingleValue<Option<DecodedSlice<'a>>>)>
+        {
+            self.pairs.into_iter().map(|(key, pair)| {
+                (
+                    DecodedSlice(key),
+                    SingleValue(pair.1.map(|v| DecodedSlice(Cow::Borrowed(v.0)))),
+                )
+            })
         }
     }
 }
@@ -179,7 +607,7 @@ mod de {
 mod tests {
     use std::borrow::Cow;
 
-    use super::UrlEncodedQS;
+    use super::{BorrowedPairsError, ParseOptions, UrlEncodedQS};
 
     #[test]
     fn parse_pair() {
@@ -194,6 +622,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_with_options() {
+        let slice = b"a=1;b=2&c=3;d";
+
+        let parser = UrlEncodedQS::parse_with(slice, ParseOptions::new().separators(b"&;"));
+
+        assert_eq!(parser.value(b"a"), Some(Some("1".as_bytes().into())));
+        assert_eq!(parser.value(b"b"), Some(Some("2".as_bytes().into())));
+        assert_eq!(parser.value(b"c"), Some(Some("3".as_bytes().into())));
+        assert_eq!(parser.value(b"d"), Some(None));
+
+        // Only `&` by default
+        let parser = UrlEncodedQS::parse(slice);
+        assert_eq!(parser.value(b"a"), Some(Some("1;b=2".as_bytes().into())));
+        assert_eq!(parser.value(b"b"), None);
+    }
+
+    #[test]
+    fn parse_max_key_len() {
+        let options = ParseOptions::new().separators(b"&;").max_key_len(3);
+
+        let parser = UrlEncodedQS::parse_with(b"abc=1;d", options);
+        assert!(!parser.is_truncated());
+        assert_eq!(parser.keys().len(), 2);
+
+        // The pairs after the longer key are dropped
+        let parser = UrlEncodedQS::parse_with(b"a=1;abcd=2;b=3", options);
+        assert!(parser.is_truncated());
+        assert_eq!(parser.keys(), vec![&Cow::Borrowed(b"a")]);
+    }
+
+    #[test]
+    fn parse_max_input_vars() {
+        let options = ParseOptions::new().separators(b"&;").max_input_vars(2);
+
+        let parser = UrlEncodedQS::parse_with(b"a=1;;b=2&", options);
+        assert!(!parser.is_truncated());
+
+        let parser = UrlEncodedQS::parse_with(b"a=1;b=2&c=3", options);
+        assert!(parser.is_truncated());
+        assert_eq!(parser.keys().len(), 2);
+    }
+
     #[test]
     fn parse_multiple_pairs() {
         let slice = b"foo=bar&foobar=baz&qux=box";
@@ -205,6 +676,57 @@ mod tests {
         assert_eq!(parser.value(b"qux"), Some(Some("box".as_bytes().into())));
     }
 
+    #[test]
+    fn iter_pairs() {
+        let slice = b"foo=bar&baz&foo=bar%20qux&a+b=c+d";
+
+        let parser = UrlEncodedQS::parse(slice);
+
+        assert_eq!(
+            parser.iter().collect::<Vec<_>>(),
+            vec![
+                (
+                    &Cow::Borrowed("a b".as_bytes()),
+                    Some("c d".as_bytes().into())
+                ),
+                (&Cow::Borrowed("baz".as_bytes()), None),
+                (
+                    &Cow::Borrowed("foo".as_bytes()),
+                    Some("bar qux".as_bytes().into())
+                ),
+            ]
+        );
+
+        // Values are yielded as they were written when decoding is disabled
+        let parser = UrlEncodedQS::parse_no_decode(slice);
+        assert_eq!(
+            parser.iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            vec![
+                Some("c+d".as_bytes().into()),
+                None,
+                Some("bar%20qux".as_bytes().into())
+            ]
+        );
+    }
+
+    #[test]
+    fn keys_with_prefix() {
+        let slice = b"filter_type=a&page=2&filter_status=b&filter=c&filte=d&filter_z";
+
+        let parser = UrlEncodedQS::parse(slice);
+
+        assert_eq!(
+            parser.keys_with_prefix(b"filter_"),
+            vec![
+                &Cow::Borrowed("filter_status".as_bytes()),
+                &Cow::Borrowed("filter_type".as_bytes()),
+                &Cow::Borrowed("filter_z".as_bytes())
+            ]
+        );
+        assert_eq!(parser.keys_with_prefix(b"").len(), 6);
+        assert!(parser.keys_with_prefix(b"sort").is_empty());
+    }
+
     #[test]
     fn parse_no_value() {
         let slice = b"foo&foobar=&foo2";
@@ -225,4 +747,106 @@ mod tests {
 
         assert_eq!(parser.value(b"foo"), Some(Some("".as_bytes().into())));
     }
+
+    #[test]
+    fn parse_no_decode() {
+        let slice = b"f%6Fo=b+r%20&foo=baz&key";
+
+        let parser = UrlEncodedQS::parse_no_decode(slice);
+
+        assert_eq!(
+            parser.keys(),
+            vec![
+                &Cow::Borrowed("f%6Fo".as_bytes()),
+                &Cow::Borrowed("foo".as_bytes()),
+                &Cow::Borrowed("key".as_bytes())
+            ]
+        );
+        assert_eq!(
+            parser.value(b"f%6Fo"),
+            Some(Some("b+r%20".as_bytes().into()))
+        );
+        assert_eq!(parser.value(b"foo"), Some(Some("baz".as_bytes().into())));
+        assert_eq!(parser.value(b"key"), Some(None));
+    }
+
+    #[test]
+    fn pairs_borrowed() {
+        let slice = b"&foo=bar&&foo=&=baz&key";
+
+        assert_eq!(
+            UrlEncodedQS::pairs_borrowed(slice),
+            Ok(vec![
+                ("foo", Some("bar")),
+                ("foo", Some("")),
+                ("", Some("baz")),
+                ("key", None),
+            ])
+        );
+        assert_eq!(UrlEncodedQS::pairs_borrowed(b""), Ok(vec![]));
+
+        assert_eq!(
+            UrlEncodedQS::pairs_borrowed(b"foo=bar&f%6Fo=1"),
+            Err(BorrowedPairsError::Encoded(9))
+        );
+        assert_eq!(
+            UrlEncodedQS::pairs_borrowed(b"foo=b+r"),
+            Err(BorrowedPairsError::Encoded(5))
+        );
+
+        assert!(UrlEncodedQS::pairs_borrowed(b"foo=\xff").is_err());
+        assert!(UrlEncodedQS::pairs_borrowed(b"\xff=bar").is_err());
+    }
+
+    #[test]
+    fn parse_value_owned() {
+        let slice = b"foo=bar%20baz&foo2";
+
+        let parser = UrlEncodedQS::parse(slice);
+
+        assert_eq!(parser.value_owned(b"foo"), Some(Some(b"bar baz".to_vec())));
+        assert_eq!(parser.value_owned(b"foo2"), Some(None));
+        assert_eq!(parser.value_owned(b"foo3"), None);
+    }
+
+    #[test]
+    fn display_summary() {
+        assert_eq!(UrlEncodedQS::parse(b"").to_string(), "UrlEncodedQS(0 keys)");
+        assert_eq!(
+            UrlEncodedQS::parse(b"key=value").to_string(),
+            "UrlEncodedQS(1 key: key)"
+        );
+
+        // Keys are decoded and sorted, values are not included
+        assert_eq!(
+            UrlEncodedQS::parse(b"foo=1&b%61r=2&baz&foo=3").to_string(),
+            "UrlEncodedQS(3 keys: bar, baz, foo)"
+        );
+
+        // Only the first keys are listed
+        assert_eq!(
+            UrlEncodedQS::parse(b"a&b&c&d&e&f&g").to_string(),
+            "UrlEncodedQS(7 keys: a, b, c, d, e, ...)"
+        );
+    }
+
+    #[test]
+    fn normalized_string() {
+        // Key order and the encoding of the values don't matter
+        assert_eq!(
+            UrlEncodedQS::parse(b"b=x+y&a=%7e&c").to_normalized_string(),
+            "a=~&b=x%20y&c"
+        );
+        assert_eq!(
+            UrlEncodedQS::parse(b"c&a=~&b=x%20y").to_normalized_string(),
+            "a=~&b=x%20y&c"
+        );
+
+        // Only the last value is kept, and the reserved characters are encoded
+        assert_eq!(
+            UrlEncodedQS::parse(b"q=1&q=a%26b%3Dc%2B/").to_normalized_string(),
+            "q=a%26b%3Dc%2B%2F"
+        );
+        assert_eq!(UrlEncodedQS::parse(b"").to_normalized_string(), "");
+    }
 }
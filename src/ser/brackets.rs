@@ -0,0 +1,509 @@
+use _serde::ser::{self, Serialize};
+
+use crate::de::Error;
+use crate::decode::encode_bytes;
+
+use super::{top_level_error, write_key, ValueSerializer};
+
+/// Serialize an instance of type `T` into a query string, which can be deserialized in
+/// `Brackets` mode.
+///
+/// Nested structs and maps are written with their keys in brackets, ex. `foo[bar]=baz`, and
+/// sequences with the index of each element, ex. `vec[0]=1&vec[1]=2`. Enum variants other
+/// than the unit ones are written as a key too, ex. `value[Tuple][0]=1&value[Tuple][1]=2`.
+///
+/// `None` values are skipped, following the rules described in
+/// [the crate's docs](crate#empty-and-missing-values). The index of a skipped element is not
+/// reused, so a `Vec<Option<T>>` is read back with the same gaps.
+///
+/// # Note
+/// Percent encoded brackets are parsed as brackets in `Brackets` mode, so the keys containing
+/// brackets can't be read back as they are.
+///
+/// # Example
+/// ```rust
+/// use _serde::{Deserialize, Serialize};
+/// use serde_querystring::{from_str, to_string_brackets, ParseMode};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Filter {
+///     tags: Vec<String>,
+///     range: (u32, u32),
+/// }
+///
+/// let filter = Filter {
+///     tags: vec!["new".to_string(), "on sale".to_string()],
+///     range: (10, 20),
+/// };
+///
+/// let qs = to_string_brackets(&filter).unwrap();
+/// assert_eq!(qs, "tags[0]=new&tags[1]=on+sale&range[0]=10&range[1]=20");
+/// assert_eq!(from_str::<Filter>(&qs, ParseMode::Brackets), Ok(filter));
+/// ```
+pub fn to_string_brackets<T>(value: &T) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = String::new();
+    let mut key = String::new();
+    value.serialize(Serializer {
+        out: &mut out,
+        key: &mut key,
+        root: true,
+        in_some: false,
+    })?;
+    Ok(out)
+}
+
+/// Serialize an instance of type `T` into the bytes of a query string for `Brackets` mode.
+///
+/// More description at ([to_string_brackets](crate::ser::to_string_brackets))
+pub fn to_bytes_brackets<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string_brackets(value).map(String::into_bytes)
+}
+
+/// Serializes a value under the key written so far, which is empty for the querystring itself
+struct Serializer<'o> {
+    out: &'o mut String,
+    // The percent encoded key of the value, ex. `foo[bar][0]`
+    key: &'o mut String,
+    root: bool,
+    // A nested `None` is written as an empty value, ex. `Some(None)`
+    in_some: bool,
+}
+
+impl<'o> Serializer<'o> {
+    /// Writes the key and its plain value as a pair
+    fn write_value<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.root {
+            return Err(top_level_error());
+        }
+
+        if !self.out.is_empty() {
+            self.out.push('&');
+        }
+        self.out.push_str(self.key);
+        self.out.push('=');
+        value
+            .serialize(ValueSerializer {
+                out: self.out,
+                in_some: false,
+            })
+            .map(|_| ())
+    }
+
+    /// Adds the variant to the key, the returned length is where the key should be cut at the end
+    fn push_variant(&mut self, variant: &'static str) -> Result<usize, Error> {
+        if self.root {
+            return Err(top_level_error());
+        }
+
+        let key_len = self.key.len();
+        self.key.push('[');
+        encode_bytes(variant.as_bytes(), self.key, true);
+        self.key.push(']');
+        Ok(key_len)
+    }
+
+    fn compound(self, key_len: usize) -> Compound<'o> {
+        Compound {
+            prefix_len: self.key.len(),
+            out: self.out,
+            key: self.key,
+            root: self.root,
+            key_len,
+            index: 0,
+        }
+    }
+
+    fn sequence(self, key_len: usize) -> Result<Compound<'o>, Error> {
+        if self.root {
+            Err(top_level_error())
+        } else {
+            Ok(self.compound(key_len))
+        }
+    }
+}
+
+macro_rules! serialize_value {
+    ($($method:ident($ty:ty))*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.write_value(&v)
+            }
+        )*
+    };
+}
+
+impl<'o> ser::Serializer for Serializer<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'o>;
+    type SerializeTuple = Compound<'o>;
+    type SerializeTupleStruct = Compound<'o>;
+    type SerializeTupleVariant = Compound<'o>;
+    type SerializeMap = Compound<'o>;
+    type SerializeStruct = Compound<'o>;
+    type SerializeStructVariant = Compound<'o>;
+
+    serialize_value! {
+        serialize_bool(bool)
+        serialize_i8(i8)
+        serialize_i16(i16)
+        serialize_i32(i32)
+        serialize_i64(i64)
+        serialize_i128(i128)
+        serialize_u8(u8)
+        serialize_u16(u16)
+        serialize_u32(u32)
+        serialize_u64(u64)
+        serialize_u128(u128)
+        serialize_f32(f32)
+        serialize_f64(f64)
+        serialize_char(char)
+        serialize_str(&str)
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        self.write_value(&Bytes(v))
+    }
+
+    /// The key is skipped, unless it's nested in another option
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        if self.in_some && !self.root {
+            self.write_value(&())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(Serializer {
+            in_some: true,
+            ..self
+        })
+    }
+
+    /// An empty value, or an empty querystring
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        if self.root {
+            Ok(())
+        } else {
+            self.write_value(&())
+        }
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.write_value(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let key_len = self.push_variant(variant)?;
+        value.serialize(Serializer {
+            out: self.out,
+            key: self.key,
+            root: false,
+            in_some: false,
+        })?;
+        self.key.truncate(key_len);
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        let key_len = self.key.len();
+        self.sequence(key_len)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        let key_len = self.key.len();
+        self.sequence(key_len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        let key_len = self.key.len();
+        self.sequence(key_len)
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        let key_len = self.push_variant(variant)?;
+        self.sequence(key_len)
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        let key_len = self.key.len();
+        Ok(self.compound(key_len))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        let key_len = self.key.len();
+        Ok(self.compound(key_len))
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        let key_len = self.push_variant(variant)?;
+        Ok(Compound {
+            root: false,
+            ..self.compound(key_len)
+        })
+    }
+}
+
+/// Bytes are written as they are, percent encoded
+struct Bytes<'a>(&'a [u8]);
+
+impl<'a> Serialize for Bytes<'a> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Writes the elements of a sequence under their index, or the values of a map or a struct
+/// under their key
+struct Compound<'o> {
+    out: &'o mut String,
+    key: &'o mut String,
+    root: bool,
+    // Where the key of each element starts
+    prefix_len: usize,
+    // Where the key should be cut at the end, before the variant of an enum
+    key_len: usize,
+    index: usize,
+}
+
+impl<'o> Compound<'o> {
+    /// Starts the key of a value, the root keys are not in brackets
+    fn open_key(&mut self) {
+        self.key.truncate(self.prefix_len);
+        if !self.root {
+            self.key.push('[');
+        }
+    }
+
+    fn close_key(&mut self) {
+        if !self.root {
+            self.key.push(']');
+        }
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(Serializer {
+            out: self.out,
+            key: self.key,
+            root: false,
+            in_some: false,
+        })?;
+        self.key.truncate(self.prefix_len);
+        Ok(())
+    }
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        use std::fmt::Write;
+
+        self.open_key();
+        // Writing to a string can't fail
+        let _ = write!(self.key, "{}", self.index);
+        self.close_key();
+        self.index += 1;
+
+        self.serialize_value(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.key.truncate(self.key_len);
+        Ok(())
+    }
+}
+
+impl<'o> ser::SerializeSeq for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Compound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeTuple for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Compound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeTupleStruct for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Compound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeTupleVariant for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Compound::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeMap for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.open_key();
+        write_key(key, self.key)?;
+        self.close_key();
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Compound::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeStruct for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.open_key();
+        encode_bytes(key.as_bytes(), self.key, true);
+        self.close_key();
+        Compound::serialize_value(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
+
+impl<'o> ser::SerializeStructVariant for Compound<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Compound::end(self)
+    }
+}
@@ -0,0 +1,73 @@
+use _serde::ser::Serialize;
+
+use crate::de::{Error, ErrorKind};
+
+use super::{Sequences, Serializer};
+
+/// Serialize an instance of type `T` into a query string, which can be deserialized in
+/// `Delimiter` mode with the same delimiter.
+///
+/// It works like [to_string](crate::ser::to_string), beside sequences which are written as a
+/// single value with their elements joined by the delimiter, ex. `vec=1|2|3`. The delimiter is
+/// percent encoded inside the elements, so they are read back as they were. `None` elements
+/// are skipped, and empty sequences are omitted. Nested maps and sequences have no
+/// representation in `Delimiter` mode, they return an `InvalidType` error.
+///
+/// # Note
+/// The delimiter should be an ascii character other than the letters, the digits, `%`, `&`,
+/// `=`, `-` and `.`, as they can't be told apart from the delimiter once encoded, or they are
+/// a part of numbers which are not percent decoded.
+///
+/// # Example
+/// ```rust
+/// use _serde::{Deserialize, Serialize};
+/// use serde_querystring::{from_str, to_string_delimiter, ParseMode};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Filter {
+///     ids: Vec<u32>,
+///     names: Vec<String>,
+/// }
+///
+/// let filter = Filter {
+///     ids: vec![1337, 11],
+///     names: vec!["a|b".to_string(), "c".to_string()],
+/// };
+///
+/// let qs = to_string_delimiter(&filter, b'|').unwrap();
+/// assert_eq!(qs, "ids=1337|11&names=a%7Cb|c");
+/// assert_eq!(from_str::<Filter>(&qs, ParseMode::Delimiter(b'|')), Ok(filter));
+/// ```
+pub fn to_string_delimiter<T>(value: &T, delimiter: u8) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    if !delimiter.is_ascii()
+        || delimiter.is_ascii_alphanumeric()
+        || matches!(delimiter, b'%' | b'&' | b'=' | b'-' | b'.')
+    {
+        return Err(Error::new(ErrorKind::Other)
+            .message(String::from(
+                "the delimiter should be an ascii character other than letters, digits, `%`, `&`, `=`, `-` and `.`",
+            ))
+            .value(&[delimiter]));
+    }
+
+    let mut out = String::new();
+    value.serialize(Serializer {
+        out: &mut out,
+        sequences: Sequences::Delimited(delimiter),
+    })?;
+    Ok(out)
+}
+
+/// Serialize an instance of type `T` into the bytes of a query string for `Delimiter` mode.
+///
+/// More description at ([to_string_delimiter](crate::ser::to_string_delimiter))
+pub fn to_bytes_delimiter<T>(value: &T, delimiter: u8) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string_delimiter(value, delimiter).map(String::into_bytes)
+}
@@ -0,0 +1,56 @@
+use _serde::ser::Serialize;
+
+use crate::de::Error;
+
+use super::{Sequences, Serializer};
+
+/// Serialize an instance of type `T` into a query string, which can be deserialized in
+/// `Duplicate` mode.
+///
+/// It works like [to_string](crate::ser::to_string), beside sequences which are written as a
+/// repeated key for each element, ex. `vec=1&vec=2`. `None` elements are skipped, and empty
+/// sequences are omitted. Nested maps and sequences have no representation in `Duplicate` mode,
+/// they return an `InvalidType` error.
+///
+/// # Example
+/// ```rust
+/// use _serde::{Deserialize, Serialize};
+/// use serde_querystring::{from_str, to_string_duplicate, ParseMode};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Report {
+///     name: String,
+///     pages: Vec<u32>,
+/// }
+///
+/// let report = Report {
+///     name: "summary".to_string(),
+///     pages: vec![1, 2],
+/// };
+///
+/// let qs = to_string_duplicate(&report).unwrap();
+/// assert_eq!(qs, "name=summary&pages=1&pages=2");
+/// assert_eq!(from_str::<Report>(&qs, ParseMode::Duplicate), Ok(report));
+/// ```
+pub fn to_string_duplicate<T>(value: &T) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = String::new();
+    value.serialize(Serializer {
+        out: &mut out,
+        sequences: Sequences::Repeated,
+    })?;
+    Ok(out)
+}
+
+/// Serialize an instance of type `T` into the bytes of a query string for `Duplicate` mode.
+///
+/// More description at ([to_string_duplicate](crate::ser::to_string_duplicate))
+pub fn to_bytes_duplicate<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string_duplicate(value).map(String::into_bytes)
+}
@@ -0,0 +1,535 @@
+use std::fmt::{Display, Write};
+
+use _serde::ser::{self, Impossible, Serialize};
+
+use crate::de::{Error, ErrorKind};
+use crate::decode::encode_bytes;
+
+mod brackets;
+mod delimiter;
+mod duplicate;
+mod sequences;
+
+use sequences::SequenceSerializer;
+
+pub use brackets::{to_bytes_brackets, to_string_brackets};
+pub use delimiter::{to_bytes_delimiter, to_string_delimiter};
+pub use duplicate::{to_bytes_duplicate, to_string_duplicate};
+
+/// Serialize an instance of type `T` into a query string, which can be deserialized in
+/// `UrlEncoded` mode.
+///
+/// Only structs and maps of plain values are supported, keys and values are percent encoded
+/// and a space is written as `+`. `None` values are skipped, following the rules described
+/// in [the crate's docs](crate#empty-and-missing-values). Nested maps and sequences have no
+/// representation in `UrlEncoded` mode, they return an `InvalidType` error.
+///
+/// # Example
+/// ```rust
+/// use _serde::{Deserialize, Serialize};
+/// use serde_querystring::{from_str, to_string, ParseMode};
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+///# #[serde(crate = "_serde")]
+/// struct Search {
+///     query: String,
+///     page: u32,
+///     lang: Option<String>,
+/// }
+///
+/// let search = Search {
+///     query: "rust & serde".to_string(),
+///     page: 2,
+///     lang: None,
+/// };
+///
+/// let qs = to_string(&search).unwrap();
+/// assert_eq!(qs, "query=rust+%26+serde&page=2");
+/// assert_eq!(from_str::<Search>(&qs, ParseMode::UrlEncoded), Ok(search));
+/// ```
+pub fn to_string<T>(value: &T) -> Result<String, Error>
+where
+    T: ?Sized + Serialize,
+{
+    let mut out = String::new();
+    value.serialize(Serializer {
+        out: &mut out,
+        sequences: Sequences::Unsupported,
+    })?;
+    Ok(out)
+}
+
+/// Serialize an instance of type `T` into the bytes of a query string.
+///
+/// More description at ([to_string](crate::ser::to_string))
+pub fn to_bytes<T>(value: &T) -> Result<Vec<u8>, Error>
+where
+    T: ?Sized + Serialize,
+{
+    to_string(value).map(String::into_bytes)
+}
+
+#[cold]
+fn top_level_error() -> Error {
+    Error::new(ErrorKind::InvalidType).message(String::from(
+        "only structs and maps can be serialized into a querystring",
+    ))
+}
+
+#[cold]
+fn nested_error(mode: &str) -> Error {
+    Error::new(ErrorKind::InvalidType).message(format!(
+        "nested maps and sequences are not supported in {} mode",
+        mode
+    ))
+}
+
+#[cold]
+fn key_error() -> Error {
+    Error::new(ErrorKind::InvalidType).message(String::from(
+        "keys should be strings, numbers, booleans or unit variants",
+    ))
+}
+
+/// Writes a percent encoded key of a map, which should be a plain value
+fn write_key<T>(key: &T, out: &mut String) -> Result<(), Error>
+where
+    T: ?Sized + Serialize,
+{
+    match key.serialize(ValueSerializer {
+        out,
+        in_some: false,
+    }) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err(key_error()),
+        Err(e) if e.kind == ErrorKind::InvalidType => Err(key_error()),
+        Err(e) => Err(e),
+    }
+}
+
+/// How the sequences are written, depending on the mode
+#[derive(Clone, Copy)]
+enum Sequences {
+    /// They can't be written in `UrlEncoded` mode
+    Unsupported,
+    /// As a repeated key for each element in `Duplicate` mode
+    Repeated,
+    /// As a single value, joined with the delimiter in `Delimiter` mode
+    Delimited(u8),
+}
+
+impl Sequences {
+    fn mode(self) -> &'static str {
+        match self {
+            Sequences::Unsupported => "urlencoded",
+            Sequences::Repeated => "duplicate",
+            Sequences::Delimited(_) => "delimiter",
+        }
+    }
+}
+
+/// Serializes the querystring as a whole, which should be a struct or a map
+struct Serializer<'o> {
+    out: &'o mut String,
+    sequences: Sequences,
+}
+
+macro_rules! top_level_unsupported {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method(self, $(_: $ty,)*) -> Result<Self::Ok, Self::Error> {
+                Err(top_level_error())
+            }
+        )*
+    };
+}
+
+impl<'o> ser::Serializer for Serializer<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = PairsSerializer<'o>;
+    type SerializeStruct = PairsSerializer<'o>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    top_level_unsupported! {
+        serialize_bool(v: bool)
+        serialize_i8(v: i8)
+        serialize_i16(v: i16)
+        serialize_i32(v: i32)
+        serialize_i64(v: i64)
+        serialize_i128(v: i128)
+        serialize_u8(v: u8)
+        serialize_u16(v: u16)
+        serialize_u32(v: u32)
+        serialize_u64(v: u64)
+        serialize_u128(v: u128)
+        serialize_f32(v: f32)
+        serialize_f64(v: f64)
+        serialize_char(v: char)
+        serialize_str(v: &str)
+        serialize_bytes(v: &[u8])
+        serialize_unit_variant(name: &'static str, index: u32, variant: &'static str)
+    }
+
+    /// An empty querystring
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    /// An empty querystring
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(top_level_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(PairsSerializer {
+            out: self.out,
+            pair_start: 0,
+            sequences: self.sequences,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(PairsSerializer {
+            out: self.out,
+            pair_start: 0,
+            sequences: self.sequences,
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(top_level_error())
+    }
+}
+
+/// Writes the pairs of a struct or a map
+///
+/// The key is written before its value, and removed again if the value is skipped.
+struct PairsSerializer<'o> {
+    out: &'o mut String,
+    // Where the pair being written starts, including its `&`
+    pair_start: usize,
+    sequences: Sequences,
+}
+
+impl<'o> PairsSerializer<'o> {
+    fn start_pair(&mut self) {
+        self.pair_start = self.out.len();
+        if !self.out.is_empty() {
+            self.out.push('&');
+        }
+    }
+
+    fn end_pair<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.out.push('=');
+        let written = match self.sequences {
+            Sequences::Unsupported => value.serialize(ValueSerializer {
+                out: self.out,
+                in_some: false,
+            })?,
+            sequences => value.serialize(SequenceSerializer {
+                out: self.out,
+                pair_start: self.pair_start,
+                in_some: false,
+                sequences,
+            })?,
+        };
+        if !written {
+            self.out.truncate(self.pair_start);
+        }
+        Ok(())
+    }
+}
+
+impl<'o> ser::SerializeMap for PairsSerializer<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.start_pair();
+        write_key(key, self.out)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.end_pair(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+impl<'o> ser::SerializeStruct for PairsSerializer<'o> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.start_pair();
+        encode_bytes(key.as_bytes(), self.out, true);
+        self.end_pair(value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+}
+
+/// Writes a single percent encoded value, returning false if there is no value to write
+struct ValueSerializer<'o> {
+    out: &'o mut String,
+    // A nested `None` is written as an empty value, ex. `Some(None)`
+    in_some: bool,
+}
+
+impl<'o> ValueSerializer<'o> {
+    fn write_display<T: Display>(self, value: T) -> Result<bool, Error> {
+        // Writing to a string can't fail
+        let _ = write!(self.out, "{}", value);
+        Ok(true)
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty))*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<Self::Ok, Self::Error> {
+                self.write_display(v)
+            }
+        )*
+    };
+}
+
+impl<'o> ser::Serializer for ValueSerializer<'o> {
+    type Ok = bool;
+    type Error = Error;
+
+    type SerializeSeq = Impossible<bool, Error>;
+    type SerializeTuple = Impossible<bool, Error>;
+    type SerializeTupleStruct = Impossible<bool, Error>;
+    type SerializeTupleVariant = Impossible<bool, Error>;
+    type SerializeMap = Impossible<bool, Error>;
+    type SerializeStruct = Impossible<bool, Error>;
+    type SerializeStructVariant = Impossible<bool, Error>;
+
+    serialize_display! {
+        serialize_bool(bool)
+        serialize_i8(i8)
+        serialize_i16(i16)
+        serialize_i32(i32)
+        serialize_i64(i64)
+        serialize_i128(i128)
+        serialize_u8(u8)
+        serialize_u16(u16)
+        serialize_u32(u32)
+        serialize_u64(u64)
+        serialize_u128(u128)
+        serialize_f32(f32)
+        serialize_f64(f64)
+    }
+
+    fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Self::Ok, Self::Error> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
+        encode_bytes(v, self.out, true);
+        Ok(true)
+    }
+
+    /// The key is skipped, unless it's nested in another option
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(self.in_some)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(ValueSerializer {
+            out: self.out,
+            in_some: true,
+        })
+    }
+
+    /// An empty value
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(true)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(true)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(nested_error("urlencoded"))
+    }
+}
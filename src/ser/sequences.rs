@@ -0,0 +1,307 @@
+use _serde::ser::{self, Impossible, Serialize};
+
+use crate::de::{Error, ErrorKind};
+
+use super::{nested_error, Sequences, ValueSerializer};
+
+/// Writes the value of a pair whose key is already written, or its elements if it's a sequence.
+/// It returns false if there is no value to write.
+pub(super) struct SequenceSerializer<'o> {
+    pub(super) out: &'o mut String,
+    // Where the pair starts, including its `&`
+    pub(super) pair_start: usize,
+    pub(super) in_some: bool,
+    pub(super) sequences: Sequences,
+}
+
+impl<'o> SequenceSerializer<'o> {
+    #[cold]
+    fn nested_error(&self) -> Error {
+        nested_error(self.sequences.mode())
+    }
+
+    fn value(self) -> ValueSerializer<'o> {
+        ValueSerializer {
+            out: self.out,
+            in_some: self.in_some,
+        }
+    }
+
+    fn sequence(self) -> Result<Elements<'o>, Error> {
+        let delimiter = match self.sequences {
+            Sequences::Delimited(delimiter) => Some(delimiter),
+            _ => None,
+        };
+
+        // Repeated pairs take the key back out of the pair, it's written again for each element
+        let mut key = String::new();
+        if delimiter.is_none() {
+            let start = match self.out[self.pair_start..].starts_with('&') {
+                true => self.pair_start + 1,
+                false => self.pair_start,
+            };
+            // Without the `=`
+            key.push_str(&self.out[start..self.out.len() - 1]);
+            self.out.truncate(self.pair_start);
+        }
+
+        Ok(Elements {
+            out: self.out,
+            key,
+            delimiter,
+            written: false,
+            sequences: self.sequences,
+        })
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method(self, $($arg: $ty,)*) -> Result<Self::Ok, Self::Error> {
+                ser::Serializer::$method(self.value(), $($arg,)*)
+            }
+        )*
+    };
+}
+
+impl<'o> ser::Serializer for SequenceSerializer<'o> {
+    type Ok = bool;
+    type Error = Error;
+
+    type SerializeSeq = Elements<'o>;
+    type SerializeTuple = Elements<'o>;
+    type SerializeTupleStruct = Elements<'o>;
+    type SerializeTupleVariant = Impossible<bool, Error>;
+    type SerializeMap = Impossible<bool, Error>;
+    type SerializeStruct = Impossible<bool, Error>;
+    type SerializeStructVariant = Impossible<bool, Error>;
+
+    forward_to_value! {
+        serialize_bool(v: bool)
+        serialize_i8(v: i8)
+        serialize_i16(v: i16)
+        serialize_i32(v: i32)
+        serialize_i64(v: i64)
+        serialize_i128(v: i128)
+        serialize_u8(v: u8)
+        serialize_u16(v: u16)
+        serialize_u32(v: u32)
+        serialize_u64(v: u64)
+        serialize_u128(v: u128)
+        serialize_f32(v: f32)
+        serialize_f64(v: f64)
+        serialize_char(v: char)
+        serialize_str(v: &str)
+        serialize_bytes(v: &[u8])
+        serialize_none()
+        serialize_unit()
+        serialize_unit_struct(name: &'static str)
+        serialize_unit_variant(name: &'static str, variant_index: u32, variant: &'static str)
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(SequenceSerializer {
+            in_some: true,
+            ..self
+        })
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(self.nested_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        self.sequence()
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        self.sequence()
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        self.sequence()
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(self.nested_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(self.nested_error())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(self.nested_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(self.nested_error())
+    }
+}
+
+/// Writes each element of a sequence as a pair with the same key, or joins them with the
+/// delimiter into the value of the pair
+pub(super) struct Elements<'o> {
+    out: &'o mut String,
+    // The percent encoded key of the repeated pairs
+    key: String,
+    delimiter: Option<u8>,
+    written: bool,
+    sequences: Sequences,
+}
+
+impl<'o> Elements<'o> {
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let element_start = self.out.len();
+        match self.delimiter {
+            Some(delimiter) if self.written => self.out.push(delimiter as char),
+            Some(_) => {}
+            None => {
+                if !self.out.is_empty() {
+                    self.out.push('&');
+                }
+                self.out.push_str(&self.key);
+                self.out.push('=');
+            }
+        }
+
+        let value_start = self.out.len();
+        let written = value
+            .serialize(ValueSerializer {
+                out: self.out,
+                in_some: false,
+            })
+            .map_err(|e| match e.kind {
+                ErrorKind::InvalidType => nested_error(self.sequences.mode()),
+                _ => e,
+            })?;
+
+        if written {
+            self.written = true;
+            if let Some(delimiter) = self.delimiter {
+                escape_delimiter(self.out, value_start, delimiter);
+            }
+        } else {
+            self.out.truncate(element_start);
+        }
+        Ok(())
+    }
+
+    /// The pair of repeated keys is already removed, so nothing is left to be cut
+    fn end(self) -> Result<bool, Error> {
+        Ok(self.delimiter.is_none() || self.written)
+    }
+}
+
+/// Percent encodes the delimiter in the value written since `start`, if it's one of the
+/// characters `encode_bytes` leaves as they are
+fn escape_delimiter(out: &mut String, start: usize, delimiter: u8) {
+    let escaped = match delimiter {
+        // A `+` is only written for a space
+        b'+' => "%20",
+        b'_' => "%5F",
+        b'~' => "%7E",
+        _ => return,
+    };
+
+    if out[start..].contains(delimiter as char) {
+        let value = out[start..].replace(delimiter as char, escaped);
+        out.truncate(start);
+        out.push_str(&value);
+    }
+}
+
+impl<'o> ser::SerializeSeq for Elements<'o> {
+    type Ok = bool;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Elements::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Elements::end(self)
+    }
+}
+
+impl<'o> ser::SerializeTuple for Elements<'o> {
+    type Ok = bool;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Elements::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Elements::end(self)
+    }
+}
+
+impl<'o> ser::SerializeTupleStruct for Elements<'o> {
+    type Ok = bool;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        Elements::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        Elements::end(self)
+    }
+}
@@ -0,0 +1,41 @@
+//! Fixtures shared by the integration tests
+
+use _serde::Deserialize;
+
+/// Records the size hint given to a sequence visitor
+#[derive(Debug, PartialEq)]
+pub struct SizeHinted {
+    pub hint: Option<usize>,
+    pub len: usize,
+}
+
+impl<'de> Deserialize<'de> for SizeHinted {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: _serde::Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'de> _serde::de::Visitor<'de> for Visitor {
+            type Value = SizeHinted;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: _serde::de::SeqAccess<'de>,
+            {
+                let hint = seq.size_hint();
+                let mut len = 0;
+                while seq.next_element::<_serde::de::IgnoredAny>()?.is_some() {
+                    len += 1;
+                }
+                Ok(SizeHinted { hint, len })
+            }
+        }
+
+        deserializer.deserialize_seq(Visitor)
+    }
+}
@@ -1,7 +1,12 @@
 //! These tests are meant for the `BracketsQS` method
 
+use std::collections::HashMap;
+
 use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use serde_querystring::{
+    de::{from_bytes, Deserializer, ErrorKind, ParseMode},
+    BracketsQS,
+};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -63,6 +68,58 @@ fn deserialize_brackets() {
     );
 }
 
+#[test]
+fn deserialize_mixed_values() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Mixed {
+        map: std::collections::HashMap<String, u32>,
+        seq: Vec<u32>,
+        scalar: u32,
+    }
+
+    let slice = b"map=1&map[a]=2&seq[1]=3&seq=4&scalar=5&scalar[a]=6";
+
+    // Maps only use the sub keys, sequences use the direct values as index 0,
+    // and scalars use the last assignment
+    assert_eq!(
+        from_bytes(slice, ParseMode::Brackets),
+        Ok(Mixed {
+            map: map! {"a".to_string() => 2},
+            seq: vec![4, 3],
+            scalar: 6
+        })
+    );
+
+    let error = BracketsQS::parse(slice)
+        .deny_mixed_values(true)
+        .deserialize::<Mixed>()
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert_eq!(error.value, "map");
+
+    // Nested keys are checked too
+    let error = Primitive::<std::collections::HashMap<String, Vec<u32>>>::deserialize(
+        Deserializer::from_bytes(b"value[a][0]=1&value[a]=2", ParseMode::Brackets)
+            .deny_mixed_values(true),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert_eq!(error.value, "a");
+
+    // Keys with only direct values or only sub keys are fine
+    assert_eq!(
+        BracketsQS::parse(b"map[a]=2&seq[1]=3&seq[0]=4&scalar=5&scalar=6")
+            .deny_mixed_values(true)
+            .deserialize(),
+        Ok(Mixed {
+            map: map! {"a".to_string() => 2},
+            seq: vec![4, 3],
+            scalar: 6
+        })
+    );
+}
+
 #[test]
 fn deserialize_sequence() {
     // vector
@@ -88,6 +145,102 @@ fn deserialize_sequence() {
     );
 }
 
+#[test]
+fn deserialize_trimmed_indices() {
+    let slice = b"value[ 1 ]=x&value[0]=y&value[%202%09]=z";
+
+    // Indices are strict by default
+    let error = BracketsQS::parse(slice)
+        .deserialize::<Primitive<Vec<String>>>()
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidNumber);
+    assert_eq!(error.value, "value[ 1 ]");
+    assert_eq!(
+        BracketsQS::parse(b"value[1]=x&value[0]=y").deserialize(),
+        Ok(p!(vec!["y".to_string(), "x".to_string()]))
+    );
+
+    // Whitespace around the indices is trimmed, even when it's percent encoded
+    assert_eq!(
+        BracketsQS::parse(slice).trim_indices(true).deserialize(),
+        Ok(p!(vec!["y".to_string(), "x".to_string(), "z".to_string()]))
+    );
+    assert_eq!(
+        Primitive::deserialize(
+            Deserializer::from_bytes(b"value[1]=x&value[0]=y", ParseMode::Brackets)
+                .trim_indices(true)
+        ),
+        Ok(p!(vec!["y".to_string(), "x".to_string()]))
+    );
+
+    // But not inside them
+    let error = BracketsQS::parse(b"value[1 0]=x")
+        .trim_indices(true)
+        .deserialize::<Primitive<Vec<String>>>()
+        .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidNumber);
+}
+
+#[test]
+fn deserialize_sequence_gaps() {
+    // Gaps in the indices are filled with `None` for optional elements
+    assert_eq!(
+        from_bytes(b"value[0]=a&value[2]=c", ParseMode::Brackets),
+        Ok(p!(vec![Some("a"), None, Some("c")]))
+    );
+    assert_eq!(
+        from_bytes(b"value[3]=d&value[1]=b", ParseMode::Brackets),
+        Ok(p!(vec![None, Some("b"), None, Some("d")]))
+    );
+    assert_eq!(
+        from_bytes(b"value[][a]=1&value[2][a]=2", ParseMode::Brackets),
+        Ok(p!(vec![
+            Some(HashMap::from([("a", 1)])),
+            None,
+            Some(HashMap::from([("a", 2)]))
+        ]))
+    );
+
+    // Otherwise the elements are compacted
+    assert_eq!(
+        from_bytes(b"value[0]=a&value[2]=c&value[5]=f", ParseMode::Brackets),
+        Ok(p!(vec!["a", "c", "f"]))
+    );
+
+    // The gaps are filled with up to 4096 `None` elements, the elements after a longer gap are
+    // compacted
+    assert_eq!(
+        from_bytes(b"value[5000]=1", ParseMode::Brackets),
+        Ok(p!(vec![Some(1)]))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[1]=1&value[5000]=2&value[10000]=3",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![None, Some(1), Some(2), Some(3)]))
+    );
+    assert_eq!(
+        from_bytes(b"value[5000]=1", ParseMode::Brackets),
+        Ok(p!(vec![1]))
+    );
+
+    // The budget is shared by the nested sequences, instead of being given to each of them
+    let input = (0..50)
+        .map(|i| format!("value[{}][4095]=1", i * 4095))
+        .collect::<Vec<_>>()
+        .join("&");
+    let value: Primitive<Vec<Option<Vec<Option<u8>>>>> =
+        from_bytes(input.as_bytes(), ParseMode::Brackets).unwrap();
+    let elements: usize = value
+        .value
+        .iter()
+        .map(|v| 1 + v.as_ref().map_or(0, Vec::len))
+        .sum();
+    assert!(elements <= 4096 + 100);
+    assert_eq!(value.value.iter().flatten().flatten().flatten().count(), 50);
+}
+
 #[test]
 fn deserialize_optional_seq() {
     #[derive(Debug, Deserialize, PartialEq)]
@@ -251,6 +404,113 @@ fn deserialize_enums() {
         ),
         Ok(p!(Enum::Unit))
     );
+
+    // Variants are chosen by name, not by their index
+    let error = from_bytes::<Primitive<Enum>>(b"value[0]=x", ParseMode::Brackets).unwrap_err();
+    assert_eq!(
+        error.message,
+        "unknown variant `0`, expected one of `Unit`, `NewType`, `Tuple`, `Struct`, \
+        variants are chosen by name and not by a numeric index"
+    );
+    assert_eq!(error.value, "0");
+}
+
+#[test]
+fn deserialize_numeric_enum_variants() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Enum {
+        #[serde(rename = "1")]
+        Unit,
+        #[serde(rename = "2")]
+        NewType(i32),
+        #[serde(rename = "3")]
+        Struct { bee: i32 },
+    }
+
+    assert_eq!(
+        from_bytes(b"value=1", ParseMode::Brackets),
+        Ok(p!(Enum::Unit))
+    );
+    assert_eq!(
+        from_bytes(b"value[2]=2022", ParseMode::Brackets),
+        Ok(p!(Enum::NewType(2022)))
+    );
+    assert_eq!(
+        from_bytes(b"value[3][bee]=833", ParseMode::Brackets),
+        Ok(p!(Enum::Struct { bee: 833 }))
+    );
+    assert_eq!(
+        from_bytes(b"value[0]=1&value[1][3][bee]=5", ParseMode::Brackets),
+        Ok(p!(vec![Enum::Unit, Enum::Struct { bee: 5 }]))
+    );
+}
+
+#[test]
+fn deserialize_adjacently_tagged_enum() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        NewType(u32),
+        Struct { bee: u32 },
+    }
+
+    assert_eq!(
+        from_bytes(b"t=Unit", ParseMode::Brackets),
+        Ok(Adjacent::Unit)
+    );
+    assert_eq!(
+        from_bytes(b"c=5&t=NewType", ParseMode::Brackets),
+        Ok(Adjacent::NewType(5))
+    );
+    assert_eq!(
+        from_bytes(b"t=Struct&c[bee]=833", ParseMode::Brackets),
+        Ok(Adjacent::Struct { bee: 833 })
+    );
+
+    // Nested in a struct
+    assert_eq!(
+        from_bytes(b"value[t]=Unit", ParseMode::Brackets),
+        Ok(p!(Adjacent::Unit))
+    );
+    assert_eq!(
+        from_bytes(b"value[c]=5&value[t]=NewType", ParseMode::Brackets),
+        Ok(p!(Adjacent::NewType(5)))
+    );
+    assert_eq!(
+        from_bytes(b"value[t]=Struct&value[c][bee]=833", ParseMode::Brackets),
+        Ok(p!(Adjacent::Struct { bee: 833 }))
+    );
+}
+
+#[test]
+fn deserialize_enum_tuple_defaults() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Enum {
+        Tuple(i32, Option<i32>),
+    }
+
+    assert_eq!(
+        from_bytes(b"value[Tuple][0]=100", ParseMode::Brackets),
+        Ok(p!(Enum::Tuple(100, None)))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[Tuple][0]=100&value[Tuple][1]=200",
+            ParseMode::Brackets
+        ),
+        Ok(p!(Enum::Tuple(100, Some(200))))
+    );
+
+    // Only the optional elements can be missing
+    assert!(from_bytes::<Primitive<Enum>>(b"value[Tuple][1]=", ParseMode::Brackets).is_err());
+    assert!(from_bytes::<Primitive<Enum>>(
+        b"value[Tuple][0]=1&value[Tuple][1]=2&value[Tuple][2]=3",
+        ParseMode::Brackets
+    )
+    .is_err());
 }
 
 #[test]
@@ -275,6 +535,30 @@ fn deserialize_invalid_sequence() {
         ParseMode::Brackets
     )
     .is_err());
+
+    // invalid indices name the key
+    for (input, value, message) in [
+        (
+            &b"value[-1]=a"[..],
+            "value[-1]",
+            "invalid index `-1` of the key `value`: the index should only have decimal digits",
+        ),
+        (
+            b"value[0]=a&value[0x1]=b",
+            "value[0x1]",
+            "invalid index `0x1` of the key `value`: the index should only have decimal digits",
+        ),
+        (
+            b"value[99999999999999999999]=a",
+            "value[99999999999999999999]",
+            "invalid index `99999999999999999999` of the key `value`: the index is too large",
+        ),
+    ] {
+        let error = from_bytes::<Primitive<Vec<&str>>>(input, ParseMode::Brackets).unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidNumber);
+        assert_eq!(error.value, value);
+        assert_eq!(error.message, message);
+    }
 }
 
 #[test]
@@ -301,6 +585,80 @@ fn deserialize_maps_of_maps() {
     );
 }
 
+/// Numeric sub keys keep their value in maps, they are only positions for sequences
+#[test]
+fn deserialize_sparse_maps() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Page {
+        rows: BTreeMap<u32, String>,
+        cols: Vec<String>,
+    }
+
+    assert_eq!(
+        from_bytes(
+            b"rows[20]=b&rows[10]=a&rows[0]=z&cols[20]=b&cols[10]=a",
+            ParseMode::Brackets
+        ),
+        Ok(Page {
+            rows: BTreeMap::from([
+                (0, "z".to_string()),
+                (10, "a".to_string()),
+                (20, "b".to_string())
+            ]),
+            cols: vec!["a".to_string(), "b".to_string()],
+        })
+    );
+
+    // The last assignment of a key wins, like other maps
+    assert_eq!(
+        from_bytes(b"value[10]=a&value[10]=b", ParseMode::Brackets),
+        Ok(p!(BTreeMap::from([(10u32, "b")])))
+    );
+
+    // And nested values are kept under their index
+    assert_eq!(
+        from_bytes(
+            b"value[7][]=1&value[3][]=2&value[7][]=3",
+            ParseMode::Brackets
+        ),
+        Ok(p!(BTreeMap::from([(3u32, vec![2]), (7, vec![1, 3])])))
+    );
+
+    assert_eq!(
+        from_bytes::<Primitive<BTreeMap<u32, String>>>(b"value[x]=a", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidNumber
+    );
+}
+
+/// Keys are single values, they can't be deserialized as sequences
+#[test]
+fn deserialize_sequence_as_map_key() {
+    #[derive(Debug, Deserialize, PartialEq, Eq, Hash)]
+    #[serde(crate = "_serde")]
+    struct Point(u32, u32, u32);
+
+    for slice in [&b"1,1,1=1200"[..], b"1,1,1,1=1200"] {
+        let error = from_bytes::<std::collections::HashMap<Point, i32>>(slice, ParseMode::Brackets)
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidType);
+    }
+
+    // Nor in nested maps
+    for slice in [&b"value[1,1,1]=1200"[..], b"value[1,1,1,1]=1200"] {
+        let error = from_bytes::<Primitive<std::collections::HashMap<Point, i32>>>(
+            slice,
+            ParseMode::Brackets,
+        )
+        .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::InvalidType);
+    }
+}
+
 #[test]
 fn deserialize_pencoded_brackets() {
     let map = map! {
@@ -318,6 +676,67 @@ fn deserialize_pencoded_brackets() {
     );
 }
 
+#[test]
+fn deserialize_sub_keys_grouping() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    enum Shape {
+        Circle(u32),
+        Square { side: u32 },
+    }
+
+    // Sub keys are grouped by their decoded value, in any order
+    type Nested = HashMap<String, HashMap<String, HashMap<String, u32>>>;
+    let expected = map! {
+        "a".to_string() => map! {
+            "x".to_string() => map! {"y".to_string() => 1, "z".to_string() => 3},
+        },
+    };
+    for input in [
+        "a[x][y]=1&a[x][z]=3",
+        "a[x][z]=3&a[x][y]=1",
+        "a[%78][y]=1&a[x][z]=3",
+        "a[x][y]=2&a%5Bx%5D[z]=3&a[x][y]=1",
+    ] {
+        assert_eq!(
+            from_bytes::<Nested>(input.as_bytes(), ParseMode::Brackets),
+            Ok(expected.clone()),
+            "{}",
+            input
+        );
+    }
+
+    // Percent encoded variants too
+    assert_eq!(
+        from_bytes(b"value[%43ircle]=1", ParseMode::Brackets),
+        Ok(p!(Shape::Circle(1)))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[Circle]=1&value[Squar%65][side]=2",
+            ParseMode::Brackets
+        ),
+        Ok(p!(Shape::Square { side: 2 }))
+    );
+}
+
+#[test]
+fn deserialize_brackets_in_values() {
+    // Encoded or not, brackets in values are kept as they are
+    assert_eq!(
+        from_bytes(b"value=a%5Bb%5D", ParseMode::Brackets),
+        Ok(p!("a[b]".to_string()))
+    );
+    assert_eq!(
+        from_bytes(b"value=a[b]", ParseMode::Brackets),
+        Ok(p!("a[b]"))
+    );
+    assert_eq!(
+        from_bytes(b"value[note]=[a][b]", ParseMode::Brackets),
+        Ok(p!(map! {"note" => "[a][b]"}))
+    );
+}
+
 #[test]
 fn deserialize_invalid_brackets() {
     // No ending bracket
@@ -409,3 +828,301 @@ fn deserialize_option() {
         Ok(expected)
     );
 }
+
+#[test]
+fn deserialize_by_reference() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Lenient<'a> {
+        #[serde(borrow)]
+        foo: Option<&'a str>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Strict<'a> {
+        #[serde(borrow)]
+        foo: &'a str,
+        vec: Vec<u32>,
+    }
+
+    let parsed = BracketsQS::parse(b"foo=bar&vec[1]=20&vec[0]=10");
+
+    assert_eq!(parsed.deserialize_ref(), Ok(Lenient { foo: Some("bar") }));
+    assert_eq!(
+        parsed.deserialize_ref(),
+        Ok(Strict {
+            foo: "bar",
+            vec: vec![10, 20]
+        })
+    );
+
+    // The parser is still usable after deserializing
+    assert_eq!(parsed.value(b"foo"), Some(Some("bar".as_bytes().into())));
+    assert!(parsed.deserialize_ref::<Primitive<u32>>().is_err());
+}
+
+#[test]
+fn deserialize_map_of_unindexed_sequences() {
+    let map = map! {
+        String::from("tags") => vec![String::from("a"), String::from("b")],
+        String::from("names") => vec![String::from("c")],
+    };
+
+    assert_eq!(
+        from_bytes(b"tags[]=a&tags[]=b&names[]=c", ParseMode::Brackets),
+        Ok(map)
+    );
+
+    // Encoded brackets and plain repeated keys end up in the same sequence
+    let map = map! {
+        String::from("tags") => vec![String::from("a"), String::from("b"), String::from("c")],
+    };
+    assert_eq!(
+        from_bytes(b"tags%5B%5D=a&tags=b&tags[]=c", ParseMode::Brackets),
+        Ok(map)
+    );
+}
+
+/// Enums inside sequences, ordered by their index
+#[test]
+fn deserialize_sequence_of_enums() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Anum {
+        E(Vec<u32>),
+        R { y: u32 },
+        U,
+    }
+
+    assert_eq!(
+        from_bytes(
+            b"value[2][R][y]=3&value[0][E][]=1&value[0][E][]=2&value[1]=U",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![Anum::E(vec![1, 2]), Anum::U, Anum::R { y: 3 }]))
+    );
+
+    // Keyed elements are grouped, the last defined group decides the variant
+    assert_eq!(
+        from_bytes(b"value[1][E][]=1&value[1][R][y]=2", ParseMode::Brackets),
+        Ok(p!(vec![Anum::R { y: 2 }]))
+    );
+    assert_eq!(
+        from_bytes(b"value[1][R][y]=2&value[1][E][]=1", ParseMode::Brackets),
+        Ok(p!(vec![Anum::E(vec![1])]))
+    );
+
+    // Direct values with the same index are still separate elements
+    assert_eq!(
+        from_bytes(
+            b"value[1]=U&value[0][E][]=5&value[1]=U",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![Anum::E(vec![5]), Anum::U, Anum::U]))
+    );
+
+    // Elements without an index are split when a field is assigned again
+    assert_eq!(
+        from_bytes(b"value[][R][y]=1&value[][R][y]=2", ParseMode::Brackets),
+        Ok(p!(vec![Anum::R { y: 1 }, Anum::R { y: 2 }]))
+    );
+
+    assert_eq!(
+        from_bytes::<Primitive<Vec<Anum>>>(b"value[a][U]=", ParseMode::Brackets)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidNumber
+    );
+}
+
+#[test]
+fn deserialize_sequence_of_structs() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Item<'a> {
+        name: &'a str,
+        count: Option<u32>,
+    }
+
+    assert_eq!(
+        from_bytes(
+            b"value[1][name]=b&value[0][name]=a&value[1][count]=2",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![
+            Item {
+                name: "a",
+                count: None
+            },
+            Item {
+                name: "b",
+                count: Some(2)
+            }
+        ]))
+    );
+
+    // Without an index, each element ends when one of its fields is assigned again
+    assert_eq!(
+        from_bytes(
+            b"value[][name]=a&value[][count]=1&value[][name]=b&value[][count]=2&value[][name]=c",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![
+            Item {
+                name: "a",
+                count: Some(1)
+            },
+            Item {
+                name: "b",
+                count: Some(2)
+            },
+            Item {
+                name: "c",
+                count: None
+            }
+        ]))
+    );
+    assert_eq!(
+        from_bytes(
+            b"value[][count]=1&value[][na%6De]=a&value[][name]=b",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![
+            Item {
+                name: "a",
+                count: Some(1)
+            },
+            Item {
+                name: "b",
+                count: None
+            }
+        ]))
+    );
+
+    // They are kept apart from the indexed elements
+    assert_eq!(
+        from_bytes(
+            b"value[][name]=a&value[0][name]=b&value[][count]=1",
+            ParseMode::Brackets
+        ),
+        Ok(p!(vec![
+            Item {
+                name: "a",
+                count: Some(1)
+            },
+            Item {
+                name: "b",
+                count: None
+            }
+        ]))
+    );
+}
+
+/// Keys with sub keys are maps for `deserialize_any`, others are plain values
+#[test]
+fn deserialize_heterogeneous_map() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", untagged)]
+    enum Field {
+        Nested(std::collections::HashMap<String, String>),
+        Scalar(String),
+    }
+
+    assert_eq!(
+        from_bytes(b"a[x]=1&a[y]=2&b=3", ParseMode::Brackets),
+        Ok(map! {
+            String::from("a") => Field::Nested(map! {
+                String::from("x") => String::from("1"),
+                String::from("y") => String::from("2"),
+            }),
+            String::from("b") => Field::Scalar(String::from("3")),
+        })
+    );
+
+    // The last assignment decides, like it does for other values
+    assert_eq!(
+        from_bytes(b"a=1&a[x]=2", ParseMode::Brackets),
+        Ok(map! {
+            String::from("a") => Field::Nested(map! {
+                String::from("x") => String::from("2"),
+            }),
+        })
+    );
+    assert_eq!(
+        from_bytes(b"a[x]=2&a=1", ParseMode::Brackets),
+        Ok(map! { String::from("a") => Field::Scalar(String::from("1")) })
+    );
+}
+
+#[test]
+fn deserialize_depth_limit() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Node {
+        a: Option<Box<Node>>,
+        #[serde(default)]
+        v: Option<u32>,
+    }
+
+    let nested = |depth: usize| format!("a{}[v]=1", "[a]".repeat(depth));
+
+    // Deep enough to overflow the stack without a limit
+    let error = from_bytes::<Node>(nested(100_000).as_bytes(), ParseMode::Brackets).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::LimitExceeded);
+
+    let error = Node::deserialize(
+        Deserializer::from_bytes(nested(100_000).as_bytes(), ParseMode::Brackets)
+            .deny_mixed_values(true),
+    )
+    .unwrap_err();
+    assert_eq!(error.kind, ErrorKind::LimitExceeded);
+
+    // Sequences count as a level too
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Seq(Vec<Seq>);
+
+    let input = format!("value{}=1", "[]".repeat(100_000));
+    assert_eq!(
+        from_bytes::<Primitive<Seq>>(input.as_bytes(), ParseMode::Brackets).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded)
+    );
+
+    // Up to 128 sub keys are accepted, the root key is a node itself
+    let mut node: Node = from_bytes(nested(127).as_bytes(), ParseMode::Brackets).unwrap();
+    let mut depth = 0;
+    while let Some(next) = node.a {
+        node = *next;
+        depth += 1;
+    }
+    assert_eq!((depth, node.v), (128, Some(1)));
+
+    assert!(from_bytes::<Node>(nested(128).as_bytes(), ParseMode::Brackets).is_err());
+
+    // The limit can be lowered or raised
+    let de = Deserializer::from_bytes(b"a[a][a][v]=1", ParseMode::Brackets);
+    assert!(Node::deserialize(de.max_depth(3)).is_ok());
+    let error = Node::deserialize(de.max_depth(2)).unwrap_err();
+    assert_eq!(
+        (error.kind, error.message),
+        (
+            ErrorKind::LimitExceeded,
+            "maximum depth of 2 sub keys reached".to_string()
+        )
+    );
+    assert_eq!(
+        BracketsQS::parse(b"a[a][a][v]=1")
+            .deny_mixed_values(true)
+            .max_depth(2)
+            .deserialize::<Node>()
+            .map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded)
+    );
+
+    let input = nested(200);
+    assert!(BracketsQS::parse(input.as_bytes())
+        .max_depth(201)
+        .deserialize::<Node>()
+        .is_ok());
+}
@@ -0,0 +1,225 @@
+//! These tests compare our behavior with `serde_urlencoded` and `serde_qs`
+//!
+//! They document where we intentionally agree and where we diverge from them,
+//! they only run with the `conformance` feature.
+
+use std::collections::HashMap;
+
+use _serde::Deserialize;
+use serde_querystring::de::{from_str, ParseMode};
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Flat {
+    foo: String,
+    bar: u32,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Flag {
+    flag: bool,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Seq {
+    vec: Vec<u32>,
+}
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(crate = "_serde")]
+struct Nested {
+    foo: HashMap<String, String>,
+}
+
+macro_rules! map {
+    ($($k:expr => $v:expr),+ $(,)?) => {{
+        let mut map = HashMap::new();
+        $(map.insert(String::from($k), String::from($v));)+
+        map
+    }};
+}
+
+fn qs_strict<'a, T: Deserialize<'a>>(input: &'a str) -> Result<T, serde_qs::Error> {
+    serde_qs::Config::new(5, true).deserialize_str(input)
+}
+
+fn qs_lenient<'a, T: Deserialize<'a>>(input: &'a str) -> Result<T, serde_qs::Error> {
+    serde_qs::Config::new(5, false).deserialize_str(input)
+}
+
+#[test]
+fn flat_pairs_agree() {
+    let inputs = [
+        ("foo=bar&bar=13", "bar"),
+        // `+` is decoded as a space by all of us
+        ("foo=rum+rum&bar=13", "rum rum"),
+        ("foo=%2B&bar=13", "+"),
+        ("foo=%D8%A8&bar=13", "ب"),
+        // A key without a value is an empty string
+        ("foo&bar=13", ""),
+        ("foo=&bar=13", ""),
+    ];
+
+    for (input, foo) in inputs {
+        let expected = Flat {
+            foo: foo.to_string(),
+            bar: 13,
+        };
+
+        assert_eq!(
+            from_str::<Flat>(input, ParseMode::UrlEncoded).unwrap(),
+            expected
+        );
+        assert_eq!(
+            from_str::<Flat>(input, ParseMode::Duplicate).unwrap(),
+            expected
+        );
+        assert_eq!(
+            from_str::<Flat>(input, ParseMode::Brackets).unwrap(),
+            expected
+        );
+        assert_eq!(serde_urlencoded::from_str::<Flat>(input).unwrap(), expected);
+        assert_eq!(qs_strict::<Flat>(input).unwrap(), expected);
+    }
+}
+
+#[test]
+fn repeated_keys_differ() {
+    // We keep the last value, the others reject repeated keys for scalars
+    let input = "foo=first&foo=last&bar=13";
+    let expected = Flat {
+        foo: "last".to_string(),
+        bar: 13,
+    };
+
+    assert_eq!(from_str::<Flat>(input, ParseMode::Duplicate), Ok(expected));
+    assert!(serde_urlencoded::from_str::<Flat>(input).is_err());
+    assert!(qs_strict::<Flat>(input).is_err());
+
+    // Duplicate mode collects repeated keys as sequences, serde_qs needs brackets
+    let input = "vec=1&vec=2";
+    assert_eq!(
+        from_str::<Seq>(input, ParseMode::Duplicate),
+        Ok(Seq { vec: vec![1, 2] })
+    );
+    assert_eq!(
+        from_str::<Seq>(input, ParseMode::Brackets),
+        Ok(Seq { vec: vec![1, 2] })
+    );
+    assert!(qs_strict::<Seq>(input).is_err());
+}
+
+#[test]
+fn booleans_differ() {
+    // Everybody agrees on `true` and `false`
+    for (input, expected) in [("flag=true", true), ("flag=false", false)] {
+        let expected = Flag { flag: expected };
+        assert_eq!(
+            from_str::<Flag>(input, ParseMode::Duplicate).unwrap(),
+            expected
+        );
+        assert_eq!(serde_urlencoded::from_str::<Flag>(input).unwrap(), expected);
+        assert_eq!(qs_strict::<Flag>(input).unwrap(), expected);
+    }
+
+    // We also accept `on`/`off`, `1`/`0` and a bare key as true
+    for (input, expected) in [
+        ("flag=on", true),
+        ("flag=off", false),
+        ("flag=1", true),
+        ("flag=0", false),
+        ("flag", true),
+    ] {
+        let expected = Flag { flag: expected };
+        assert_eq!(
+            from_str::<Flag>(input, ParseMode::Duplicate).unwrap(),
+            expected
+        );
+        assert!(serde_urlencoded::from_str::<Flag>(input).is_err());
+        assert!(qs_strict::<Flag>(input).is_err());
+    }
+}
+
+#[test]
+fn bracket_sequences_agree() {
+    let inputs = [
+        "vec[0]=1&vec[1]=2",
+        // Sorted by index
+        "vec[1]=2&vec[0]=1",
+        // Indices are only used for ordering
+        "vec[5]=1&vec[9]=2",
+        // Empty brackets keep the order of appearance
+        "vec[]=1&vec[]=2",
+    ];
+
+    for input in inputs {
+        let expected = Seq { vec: vec![1, 2] };
+        assert_eq!(
+            from_str::<Seq>(input, ParseMode::Brackets).unwrap(),
+            expected
+        );
+        assert_eq!(qs_strict::<Seq>(input).unwrap(), expected);
+    }
+}
+
+#[test]
+fn bracket_maps_agree() {
+    let input = "foo[a]=1&foo[b]=x+y";
+    let expected = Nested {
+        foo: map! {"a" => "1", "b" => "x y"},
+    };
+
+    assert_eq!(from_str::<Nested>(input, ParseMode::Brackets), Ok(expected));
+    assert_eq!(
+        qs_strict::<Nested>(input).unwrap(),
+        Nested {
+            foo: map! {"a" => "1", "b" => "x y"},
+        }
+    );
+}
+
+#[test]
+fn lenient_brackets_differ() {
+    // Percent encoded brackets are always accepted, serde_qs only accepts them in non-strict mode
+    let input = "foo%5Ba%5D=1";
+    assert_eq!(
+        from_str::<Nested>(input, ParseMode::Brackets),
+        Ok(Nested {
+            foo: map! {"a" => "1"}
+        })
+    );
+    assert!(qs_strict::<Nested>(input).is_err());
+    assert_eq!(
+        qs_lenient::<Nested>(input).unwrap(),
+        Nested {
+            foo: map! {"a" => "1"}
+        }
+    );
+
+    // Trailing characters after a closing bracket are ignored
+    let input = "foo[a]b=1";
+    assert_eq!(
+        from_str::<Nested>(input, ParseMode::Brackets),
+        Ok(Nested {
+            foo: map! {"a" => "1"}
+        })
+    );
+    assert!(qs_strict::<Nested>(input).is_err());
+
+    // A missing closing bracket closes the subkey at the `=`
+    let input = "foo[a=1";
+    assert_eq!(
+        from_str::<Nested>(input, ParseMode::Brackets),
+        Ok(Nested {
+            foo: map! {"a" => "1"}
+        })
+    );
+    assert_eq!(
+        qs_strict::<Nested>(input).unwrap(),
+        Nested {
+            foo: map! {"a=1" => ""}
+        }
+    );
+}
@@ -1,7 +1,14 @@
 //! These tests are common between different deserialization methods
 
-use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, from_str, ErrorKind, ParseMode};
+use std::{collections::HashMap, str::FromStr, time::Duration};
+
+use _serde::{de::IgnoredAny, Deserialize};
+use rust_decimal::Decimal;
+use serde_querystring::de::{
+    field, from_bytes, from_bytes_partial, from_bytes_with_raw, from_reader, from_str,
+    from_str_collect_errors, from_str_partial, from_str_with_raw, Deserializer, Error, ErrorKind,
+    Iso8601Duration, MaybeInvalidUtf8, ParseMode,
+};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -134,6 +141,76 @@ fn deserialize_float_valid() {
     );
 }
 
+/// Integers without a decimal point are valid floats
+#[test]
+fn deserialize_float_from_integer() {
+    check_result(|mode| from_str("value=5", mode), Ok(p!(5.0_f64)));
+    check_result(|mode| from_str("value=5", mode), Ok(p!(5.0_f32)));
+    check_result(|mode| from_str("value=-5", mode), Ok(p!(-5.0_f32)));
+    check_result(|mode| from_str("value=0", mode), Ok(p!(0.0_f64)));
+    check_result(|mode| from_str("value=007", mode), Ok(p!(7.0_f64)));
+    check_result(|mode| from_str("value=5", mode), Ok(p!(Some(5.0_f64))));
+
+    // Beyond the range of u64 and i64
+    check_result(
+        |mode| from_str("value=9223372036854775808", mode),
+        Ok(p!(9_223_372_036_854_775_808_f64)),
+    );
+    check_result(
+        |mode| from_str("value=340282366920938463463374607431768211456", mode),
+        Ok(p!(3.402_823_669_209_385e38_f64)),
+    );
+
+    // And in sequences
+    assert_eq!(
+        from_str("value=1&value=2", ParseMode::Duplicate),
+        Ok(p!(vec![1.0_f64, 2.0]))
+    );
+    assert_eq!(
+        from_str("value=1|2", ParseMode::Delimiter(b'|')),
+        Ok(p!(vec![1.0_f32, 2.0]))
+    );
+    assert_eq!(
+        from_str("value[1]=2&value[0]=1", ParseMode::Brackets),
+        Ok(p!(vec![1.0_f64, 2.0]))
+    );
+}
+
+/// Decimals are given the exact digits as a string
+#[test]
+fn deserialize_decimal() {
+    check_result(
+        |mode| from_str("value=19.99", mode),
+        Ok(p!(Decimal::new(1999, 2))),
+    );
+    check_result(
+        |mode| from_str("value=-0.1000000000000000000000000001", mode),
+        Ok(p!(
+            Decimal::from_str("-0.1000000000000000000000000001").unwrap()
+        )),
+    );
+
+    // Percent encoded
+    check_result(
+        |mode| from_str("value=%2B19%2E99", mode),
+        Ok(p!(Decimal::new(1999, 2))),
+    );
+
+    check_result(
+        |mode| from_str("value=19.99", mode),
+        Ok(p!(Some(Decimal::new(1999, 2)))),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Decimal>>("value=19.9.9", mode).is_err(),
+        true,
+    );
+
+    assert_eq!(
+        from_str("value=19.99&value=0.01", ParseMode::Duplicate),
+        Ok(p!(vec![Decimal::new(1999, 2), Decimal::new(1, 2)]))
+    );
+}
+
 /// Check if different boolean idents work
 #[test]
 fn deserialize_bool() {
@@ -147,6 +224,19 @@ fn deserialize_bool() {
     check_result(|mode| from_str("value=off", mode), Ok(p!(false)));
     check_result(|mode| from_str("value=false", mode), Ok(p!(false)));
 
+    // Only the exact idents are accepted, not other numbers nor padded ones
+    for value in [
+        "2", "01", "00", "10", "11", "ON", "True", "truee", "of", "o",
+    ] {
+        check_result(
+            |mode| {
+                from_str::<Primitive<bool>>(&format!("value={}", value), mode)
+                    .map_err(|e| (e.kind, e.value))
+            },
+            Err((ErrorKind::InvalidBoolean, value.to_string())),
+        );
+    }
+
     // In keys
     let map = map! {
         true => "value1",
@@ -158,6 +248,223 @@ fn deserialize_bool() {
     );
 }
 
+#[test]
+fn deserialize_case_insensitive_bool() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Flags {
+        a: bool,
+        b: bool,
+        c: bool,
+        d: Option<bool>,
+    }
+
+    let input = "a=True&b=FALSE&c=On&d=oFF";
+    check_result(
+        |mode| Flags::deserialize(Deserializer::from_str(input, mode).case_insensitive_bool(true)),
+        Ok(Flags {
+            a: true,
+            b: false,
+            c: true,
+            d: Some(false),
+        }),
+    );
+
+    // It is disabled by default
+    check_result(
+        |mode| Flags::deserialize(Deserializer::from_str(input, mode)).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidBoolean),
+    );
+
+    // Other words are still rejected
+    for value in ["Truee", "OF", "yes"] {
+        check_result(
+            |mode| {
+                Primitive::<bool>::deserialize(
+                    Deserializer::from_str(&format!("value={}", value), mode)
+                        .case_insensitive_bool(true),
+                )
+                .map_err(|e| e.kind)
+            },
+            Err(ErrorKind::InvalidBoolean),
+        );
+    }
+
+    // Sequences of each mode
+    let de = Deserializer::from_str("value=TRUE&value=Off", ParseMode::Duplicate);
+    assert_eq!(
+        Primitive::deserialize(de.case_insensitive_bool(true)),
+        Ok(p!(vec![true, false]))
+    );
+    let de = Deserializer::from_str("value=TRUE|Off", ParseMode::Delimiter(b'|'));
+    assert_eq!(
+        Primitive::deserialize(de.case_insensitive_bool(true)),
+        Ok(p!(vec![true, false]))
+    );
+    let de = Deserializer::from_str("value[1]=Off&value[0]=TRUE", ParseMode::Brackets);
+    assert_eq!(
+        Primitive::deserialize(de.case_insensitive_bool(true)),
+        Ok(p!(vec![true, false]))
+    );
+
+    // The parsers have the option too
+    use serde_querystring::BracketsQS;
+
+    let parser = BracketsQS::parse(b"value[a]=TRUE&value[b]=oFF");
+    assert_eq!(
+        parser.case_insensitive_bool(true).deserialize(),
+        Ok(p!(HashMap::from([
+            ("a".to_string(), true),
+            ("b".to_string(), false)
+        ])))
+    );
+}
+
+#[test]
+fn deserialize_kv_separator() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Legacy {
+        a: u32,
+        b: String,
+        c: Option<String>,
+        d: Option<String>,
+    }
+
+    let input = "a:1&b:x:y=z&c&d:";
+    check_result(
+        |mode| Legacy::deserialize(Deserializer::from_str(input, mode).kv_separator(b':')),
+        Ok(Legacy {
+            a: 1,
+            b: "x:y=z".to_string(),
+            c: None,
+            d: Some(String::new()),
+        }),
+    );
+
+    // `=` is a part of the key then
+    check_result(
+        |mode| {
+            HashMap::<String, String>::deserialize(
+                Deserializer::from_str("a=1:2", mode).kv_separator(b':'),
+            )
+        },
+        Ok(HashMap::from([("a=1".to_string(), "2".to_string())])),
+    );
+
+    // Brackets and sequences are not affected
+    assert_eq!(
+        Primitive::deserialize(
+            Deserializer::from_str("value[a]:1&value[b][]:2", ParseMode::Brackets)
+                .kv_separator(b':')
+        ),
+        Ok(p!(map! {
+            "a".to_string() => vec![1],
+            "b".to_string() => vec![2]
+        }))
+    );
+    assert_eq!(
+        Primitive::deserialize(
+            Deserializer::from_str("value:1&value:2", ParseMode::Duplicate).kv_separator(b':')
+        ),
+        Ok(p!(vec![1, 2]))
+    );
+    assert_eq!(
+        Primitive::deserialize(
+            Deserializer::from_str("value:1|2", ParseMode::Delimiter(b'|')).kv_separator(b':')
+        ),
+        Ok(p!(vec![1, 2]))
+    );
+
+    // The limits use it too
+    assert_eq!(
+        Primitive::<u32>::deserialize(
+            Deserializer::from_str("value:1", ParseMode::UrlEncoded)
+                .kv_separator(b':')
+                .max_key_len(5)
+        ),
+        Ok(p!(1))
+    );
+}
+
+#[test]
+fn deserialize_bare_keys_as_some() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Flags {
+        bare: Option<bool>,
+        yes: Option<bool>,
+        no: Option<bool>,
+        absent: Option<bool>,
+        name: Option<String>,
+    }
+
+    let input = "bare&yes=true&no=false&name";
+    check_result(
+        |mode| Flags::deserialize(Deserializer::from_str(input, mode).bare_keys_as_some(true)),
+        Ok(Flags {
+            bare: Some(true),
+            yes: Some(true),
+            no: Some(false),
+            absent: None,
+            name: Some(String::new()),
+        }),
+    );
+
+    // It is disabled by default
+    check_result(
+        |mode| Flags::deserialize(Deserializer::from_str(input, mode)),
+        Ok(Flags {
+            bare: None,
+            yes: Some(true),
+            no: Some(false),
+            absent: None,
+            name: None,
+        }),
+    );
+
+    // The last assignment is still the one used
+    check_result(
+        |mode| {
+            Primitive::<Option<bool>>::deserialize(
+                Deserializer::from_str("value=false&value", mode).bare_keys_as_some(true),
+            )
+        },
+        Ok(p!(Some(true))),
+    );
+
+    // It's the same rule for all the types, so a bare number is invalid like an empty one
+    check_result(
+        |mode| {
+            Primitive::<Option<u32>>::deserialize(
+                Deserializer::from_str("value", mode).bare_keys_as_some(true),
+            )
+            .map_err(|e| e.kind)
+        },
+        Err(ErrorKind::InvalidNumber),
+    );
+    check_result(
+        |mode| Primitive::<Option<u32>>::deserialize(Deserializer::from_str("value", mode)),
+        Ok(p!(None)),
+    );
+
+    // The parsers have the option too, and still keep the bare keys without a value
+    use serde_querystring::UrlEncodedQS;
+
+    let parser = UrlEncodedQS::parse(b"bare&yes=1&no=0&name").bare_keys_as_some(true);
+    assert_eq!(parser.value(b"bare"), Some(None));
+    assert_eq!(
+        parser.deserialize(),
+        Ok(Flags {
+            bare: Some(true),
+            yes: Some(true),
+            no: Some(false),
+            absent: None,
+            name: Some(String::new()),
+        })
+    );
+}
+
 /// Check if we can directly deserialize non percent encoded values to str
 #[test]
 fn deserialize_str() {
@@ -213,6 +520,63 @@ fn deserialize_strings() {
     );
 }
 
+/// Keys are decoded when parsing, a `+` in them is a space like it is in the values
+#[test]
+fn parse_plus_in_keys() {
+    use std::borrow::Cow;
+
+    use serde_querystring::{BracketsQS, DelimiterQS, DuplicateQS, UrlEncodedQS};
+
+    let slice = b"a+b=c+d&x%2By=1&k[s+t]=2";
+    let keys = |keys: Vec<&Cow<[u8]>>| keys.into_iter().map(|k| k.to_vec()).collect::<Vec<_>>();
+    let flat = vec![b"a b".to_vec(), b"k[s t]".to_vec(), b"x+y".to_vec()];
+
+    assert_eq!(keys(UrlEncodedQS::parse(slice).keys()), flat);
+    assert_eq!(keys(DuplicateQS::parse(slice).keys()), flat);
+    assert_eq!(keys(DelimiterQS::parse(slice, b'|').keys()), flat);
+    assert_eq!(
+        keys(BracketsQS::parse(slice).keys()),
+        vec![b"a b".to_vec(), b"k".to_vec(), b"x+y".to_vec()]
+    );
+    assert_eq!(
+        keys(BracketsQS::parse(slice).sub_values(b"k").unwrap().keys()),
+        vec![b"s t".to_vec()]
+    );
+
+    // Unless it's disabled for the keys, independently of the values
+    let flat = vec![b"a+b".to_vec(), b"k[s+t]".to_vec(), b"x+y".to_vec()];
+    assert_eq!(
+        keys(UrlEncodedQS::parse_with_plus_as_space(slice, false, true).keys()),
+        flat
+    );
+    assert_eq!(
+        keys(DuplicateQS::parse_with_plus_as_space(slice, false, true).keys()),
+        flat
+    );
+    assert_eq!(
+        keys(DelimiterQS::parse_with_plus_as_space(slice, b'|', false, true).keys()),
+        flat
+    );
+    assert_eq!(
+        keys(
+            BracketsQS::parse_with_plus_as_space(slice, false, true)
+                .sub_values(b"k")
+                .unwrap()
+                .keys()
+        ),
+        vec![b"s+t".to_vec()]
+    );
+
+    // And the values are decoded the same in every mode
+    check_result(
+        |mode| from_bytes(b"a+b=c+d&x%2By=%2B", mode),
+        Ok(map! {
+            String::from("a b") => String::from("c d"),
+            String::from("x+y") => String::from("+"),
+        }),
+    );
+}
+
 #[test]
 fn deserialize_bytes() {
     use serde_bytes::Bytes;
@@ -280,6 +644,44 @@ fn deserialize_byte_vecs() {
     );
 }
 
+#[test]
+fn deserialize_byte_keys() {
+    use serde_bytes::ByteBuf;
+
+    // Keys which are not valid utf8 are kept as they are. A `Vec<u8>` key is deserialized as a
+    // sequence by serde, so `ByteBuf` should be used instead
+    let map = map! {
+        ByteBuf::from(b"\0".to_vec()) => "nul",
+        ByteBuf::from(b"\xff\xfe".to_vec()) => "invalid",
+        ByteBuf::from(b"a\xc3".to_vec()) => "truncated",
+    };
+    check_result(
+        |mode| from_bytes(b"%00=nul&%FF%fe=invalid&a%C3=truncated", mode),
+        Ok(map),
+    );
+
+    // Raw bytes in the querystring too
+    check_result(
+        |mode| from_bytes(b"\xff=1", mode),
+        Ok(map! { ByteBuf::from(b"\xff".to_vec()) => 1u32 }),
+    );
+
+    // Values of a byte keyed map can be sequences or maps
+    let map = map! {
+        ByteBuf::from(b"\xff".to_vec()) => vec![1u32, 2],
+    };
+    assert_eq!(
+        from_bytes(b"%FF=1&%FF=2", ParseMode::Duplicate),
+        Ok(map.clone())
+    );
+    assert_eq!(from_bytes(b"%FF[]=1&%FF[]=2", ParseMode::Brackets), Ok(map));
+
+    let map = map! {
+        ByteBuf::from(b"\xff".to_vec()) => map! { ByteBuf::from(b"\0".to_vec()) => 1u32 },
+    };
+    assert_eq!(from_bytes(b"%FF[%00]=1", ParseMode::Brackets), Ok(map));
+}
+
 /// Check if unit enums work as keys and values
 #[test]
 fn deserialize_unit_enum() {
@@ -309,6 +711,54 @@ fn deserialize_unit_enum() {
     );
 }
 
+/// Check if percent encoded variant names work in values
+#[test]
+fn deserialize_renamed_unit_enum() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    enum Relative {
+        #[serde(rename = "بابابزرگ")]
+        Grandfather,
+        #[serde(rename = "مادر بزرگ")]
+        Grandmother,
+    }
+
+    check_result(
+        |mode| {
+            from_str(
+                "value=%D8%A8%D8%A7%D8%A8%D8%A7%D8%A8%D8%B2%D8%B1%DA%AF",
+                mode,
+            )
+        },
+        Ok(p!(Relative::Grandfather)),
+    );
+    check_result(
+        |mode| {
+            from_str(
+                "value=%D9%85%D8%A7%D8%AF%D8%B1+%D8%A8%D8%B2%D8%B1%DA%AF",
+                mode,
+            )
+        },
+        Ok(p!(Relative::Grandmother)),
+    );
+
+    // In sequences, the variants come from values too
+    assert_eq!(
+        from_str(
+            "value=%D9%85%D8%A7%D8%AF%D8%B1%20%D8%A8%D8%B2%D8%B1%DA%AF\
+            &value=%D8%A8%D8%A7%D8%A8%D8%A7%D8%A8%D8%B2%D8%B1%DA%AF",
+            ParseMode::Duplicate
+        ),
+        Ok(p!(vec![Relative::Grandmother, Relative::Grandfather]))
+    );
+
+    // Not encoded names work as well
+    assert_eq!(
+        from_str("value=بابابزرگ", ParseMode::UrlEncoded),
+        Ok(p!(Relative::Grandfather))
+    );
+}
+
 #[test]
 fn deserialize_option() {
     check_result(
@@ -326,6 +776,63 @@ fn deserialize_option() {
     );
 }
 
+#[test]
+fn deserialize_empty_as_none() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        side: Option<Side>,
+        page: Option<u32>,
+        name: Option<String>,
+    }
+
+    let filter = |input: &'static str, mode| {
+        Filter::deserialize(Deserializer::from_str(input, mode).empty_as_none(true))
+    };
+
+    check_result(
+        |mode| filter("side=&page=&name=", mode),
+        Ok(Filter {
+            side: None,
+            page: None,
+            name: None,
+        }),
+    );
+    check_result(
+        |mode| filter("side&page", mode),
+        Ok(Filter {
+            side: None,
+            page: None,
+            name: None,
+        }),
+    );
+    check_result(
+        |mode| filter("side=Left&page=2&name=foo", mode),
+        Ok(Filter {
+            side: Some(Side::Left),
+            page: Some(2),
+            name: Some(String::from("foo")),
+        }),
+    );
+
+    // Invalid values are still errors, with their kind
+    check_result(|mode| filter("side=Up", mode).is_err(), true);
+    check_result(
+        |mode| filter("page=first", mode).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidNumber),
+    );
+
+    // It's disabled by default, empty values are errors for enums
+    check_result(
+        |mode| from_str::<Primitive<Option<Side>>>("value=", mode).is_err(),
+        true,
+    );
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(p!(Some(String::new()), Option<String>)),
+    );
+}
+
 #[test]
 fn deserialize_new_type() {
     #[derive(Debug, Deserialize, Eq, PartialEq)]
@@ -338,6 +845,71 @@ fn deserialize_new_type() {
     );
 }
 
+#[test]
+fn deserialize_empty_querystring() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Empty {}
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Unit;
+
+    check_result(|mode| from_bytes(b"", mode), Ok(()));
+    check_result(|mode| from_bytes(b"", mode), Ok(Unit));
+    check_result(|mode| from_bytes(b"", mode), Ok(Empty {}));
+
+    // Unknown keys are ignored by empty structs, but not by units
+    check_result(|mode| from_bytes(b"key=value", mode), Ok(Empty {}));
+    check_result(
+        |mode| from_bytes::<()>(b"key=value", mode).unwrap_err().kind,
+        ErrorKind::InvalidType,
+    );
+}
+
+/// An empty querystring is `None` at the root level, anything else is `Some`
+#[test]
+fn deserialize_root_option() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Page {
+        page: Option<u32>,
+    }
+
+    check_result(
+        |mode| from_bytes::<Option<HashMap<String, String>>>(b"", mode),
+        Ok(None),
+    );
+    check_result(|mode| from_bytes::<Option<Page>>(b"", mode), Ok(None));
+
+    check_result(
+        |mode| from_bytes(b"key=value", mode),
+        Ok(Some(map! { "key".to_string() => "value".to_string() })),
+    );
+    check_result(
+        |mode| from_bytes(b"page=2", mode),
+        Ok(Some(Page { page: Some(2) })),
+    );
+
+    // Keys which are not used still make it `Some`
+    check_result(
+        |mode| from_bytes(b"other=1", mode),
+        Ok(Some(Page { page: None })),
+    );
+    check_result(
+        |mode| from_str_collect_errors::<Option<Page>>("", mode).map_err(|e| e.len()),
+        Ok(None),
+    );
+    check_result(
+        |mode| {
+            Option::<Page>::deserialize(
+                Deserializer::from_str("page=1&utm=x", mode).ignore_keys(&[b"page", b"utm"]),
+            )
+        },
+        Ok(None),
+    );
+}
+
 #[test]
 fn deserialize_extra_ampersands() {
     check_result(|mode| from_str("&&value=bar", mode), Ok(p!("bar")));
@@ -555,10 +1127,102 @@ fn deserialize_invalid_type() {
     check_result(|mode| from_str::<String>("value", mode).is_err(), true);
 }
 
+/// Everything after the first `=` is a part of the value, as documented in the README
 #[test]
-fn deserialize_invalid_precent_decoding() {
-    // If the there is a percent but there aren't 2 characters after it, we ignore them
-    check_result(|mode| from_str("value=Test%8", mode), Ok(p!("Test%8")));
+fn deserialize_equal_sign_in_values() {
+    check_result(|mode| from_str("value=ab=cd", mode), Ok(p!("ab=cd")));
+    check_result(
+        |mode| from_str("value=YWJjZA==&other=1", mode),
+        Ok(p!("YWJjZA==".to_string())),
+    );
+    check_result(|mode| from_str("value==", mode), Ok(p!("=")));
+    check_result(
+        |mode| from_str("value=a%3Db=c", mode),
+        Ok(p!("a=b=c".to_string())),
+    );
+
+    // Sub keys end at the first `=` as well
+    assert_eq!(
+        from_str("value[k]=a=b&value[l]==", ParseMode::Brackets),
+        Ok(p!(map! {"k" => "a=b", "l" => "="}))
+    );
+}
+
+/// The conventions for empty and missing values documented in the README
+#[test]
+fn deserialize_empty_and_missing() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Optional {
+        value: Option<String>,
+        #[serde(default)]
+        seq: Option<Vec<String>>,
+    }
+
+    // `None` is a missing key
+    check_result(
+        |mode| from_str("", mode),
+        Ok(Optional {
+            value: None,
+            seq: None,
+        }),
+    );
+
+    // `Some("")` is an assigned but empty key
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(Optional {
+            value: Some(String::new()),
+            seq: None,
+        }),
+    );
+
+    // Only Delimiter mode has a representation for an empty sequence
+    assert_eq!(
+        from_str("seq=", ParseMode::Delimiter(b'|')),
+        Ok(Optional {
+            value: None,
+            seq: Some(vec![]),
+        })
+    );
+}
+
+/// Nested options tell apart a missing key, an empty value and a value
+#[test]
+fn deserialize_tri_state_option() {
+    check_result(|mode| from_str("", mode), Ok(p!(None, Option<Option<u32>>)));
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(p!(Some(None), Option<Option<u32>>)),
+    );
+    check_result(
+        |mode| from_str("value=5", mode),
+        Ok(p!(Some(Some(5)), Option<Option<u32>>)),
+    );
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(p!(Some(None), Option<Option<String>>)),
+    );
+    check_result(
+        |mode| from_str("value=%20", mode),
+        Ok(p!(Some(Some(" ".to_string())), Option<Option<String>>)),
+    );
+
+    // The last value is used for repeated keys
+    assert_eq!(
+        from_str("value=5&value=", ParseMode::Duplicate),
+        Ok(p!(Some(None), Option<Option<u32>>))
+    );
+    assert_eq!(
+        from_str("value[a]=5&value=", ParseMode::Brackets),
+        Ok(p!(Some(None), Option<Option<u32>>))
+    );
+}
+
+#[test]
+fn deserialize_invalid_precent_decoding() {
+    // If the there is a percent but there aren't 2 characters after it, we ignore them
+    check_result(|mode| from_str("value=Test%8", mode), Ok(p!("Test%8")));
 
     // If the there is a percent but the next 2 characters aren't hex numbers, we ignore them
     check_result(|mode| from_str("value=Test%as", mode), Ok(p!("Test%as")));
@@ -576,6 +1240,282 @@ fn deserialize_invalid_precent_decoding() {
     );
 }
 
+#[test]
+fn deserialize_strict_percent_decoding() {
+    fn strict<'de, T: Deserialize<'de>>(input: &'de str, mode: ParseMode) -> Result<T, Error> {
+        T::deserialize(Deserializer::from_str(input, mode).strict_percent_decoding(true))
+    }
+
+    // Valid percent encoded characters are decoded as usual
+    check_result(
+        |mode| strict("value=Test%20%25", mode),
+        Ok(p!("Test %".to_string())),
+    );
+
+    // But a percent without 2 hex numbers after it is an error
+    for input in [
+        "value=Test%8",
+        "value=Test%as",
+        "value=Test%%25",
+        "value=Test%",
+    ] {
+        check_result(
+            |mode| strict::<Primitive<String>>(input, mode).map_err(|e| (e.kind, e.index)),
+            Err((ErrorKind::InvalidEncoding, Some(4))),
+        );
+    }
+
+    // Keys are rejected too
+    check_result(
+        |mode| {
+            strict::<Primitive<String>>("val%ue=Test", mode).map_err(|e| (e.kind, e.value, e.index))
+        },
+        Err((ErrorKind::InvalidEncoding, "val%ue".to_string(), Some(3))),
+    );
+    assert_eq!(
+        strict::<HashMap<String, HashMap<String, u32>>>("value[a%]=1", ParseMode::Brackets)
+            .map_err(|e| (e.value, e.index)),
+        Err(("value[a%]".to_string(), Some(7)))
+    );
+
+    // But not the values of the keys we don't use
+    check_result(
+        |mode| strict("value=Test&other=%zz", mode),
+        Ok(p!("Test".to_string())),
+    );
+
+    // Pairs split on `;` are checked separately
+    use serde_querystring::{ParseOptions, UrlEncodedQS};
+
+    let options = ParseOptions::new()
+        .separators(b"&;")
+        .strict_percent_decoding(true);
+    assert_eq!(
+        UrlEncodedQS::parse_with(b"value=Test;other=%zz", options).deserialize(),
+        Ok(p!("Test".to_string()))
+    );
+    assert_eq!(
+        UrlEncodedQS::parse_with(b"other=1;value=Test%", options)
+            .deserialize::<Primitive<String>>()
+            .map_err(|e| e.value),
+        Err("Test%".to_string())
+    );
+
+    // It is lenient by default
+    check_result(
+        |mode| Primitive::<String>::deserialize(Deserializer::from_str("value=Test%8", mode)),
+        Ok(p!("Test%8".to_string())),
+    );
+}
+
+#[test]
+fn deserialize_plus_as_space() {
+    fn plus<'de, T: Deserialize<'de>>(
+        input: &'de str,
+        mode: ParseMode,
+        keys: bool,
+        values: bool,
+    ) -> Result<T, Error> {
+        T::deserialize(
+            Deserializer::from_str(input, mode)
+                .plus_as_space_in_keys(keys)
+                .plus_as_space_in_values(values),
+        )
+    }
+
+    let map = |key: &str, value: &str| -> HashMap<String, String> {
+        HashMap::from([(key.to_string(), value.to_string())])
+    };
+
+    // Both are decoded as a space by default
+    check_result(
+        |mode| plus("a+b=c+d", mode, true, true),
+        Ok(map("a b", "c d")),
+    );
+    check_result(
+        |mode| plus("a+b=c+d", mode, false, true),
+        Ok(map("a+b", "c d")),
+    );
+    check_result(
+        |mode| plus("a+b=c+d", mode, true, false),
+        Ok(map("a b", "c+d")),
+    );
+    check_result(
+        |mode| plus("a+b=c+d%20e", mode, false, false),
+        Ok(map("a+b", "c+d e")),
+    );
+
+    // Base64 values arrive intact, with their padding
+    check_result(
+        |mode| plus("token=a+b/c+%2B==", mode, true, false),
+        Ok(map("token", "a+b/c++==")),
+    );
+
+    // Sub keys and sequence values keep the setting
+    assert_eq!(
+        plus("value[a+b][]=c+d", ParseMode::Brackets, false, false),
+        Ok(p!(map! { "a+b".to_string() => vec!["c+d".to_string()] }))
+    );
+    assert_eq!(
+        plus("value=a+b|c+d", ParseMode::Delimiter(b'|'), true, false),
+        Ok(p!(vec!["a+b".to_string(), "c+d".to_string()]))
+    );
+}
+
+#[test]
+fn deserialize_max_input_vars() {
+    fn limited(input: &str, mode: ParseMode) -> Result<HashMap<String, String>, Error> {
+        HashMap::deserialize(Deserializer::from_str(input, mode).max_input_vars(3))
+    }
+
+    // At the limit
+    check_result(|mode| limited("a=1&b=2&c=3", mode).is_ok(), true);
+    // Empty parts are not counted
+    check_result(|mode| limited("&a=1&&b=2&c=3&", mode).is_ok(), true);
+
+    // Over the limit, repeated keys count as well
+    check_result(
+        |mode| limited("a=1&b=2&c=3&d=4", mode).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded),
+    );
+    check_result(
+        |mode| limited("a=1&a=2&a=3&a=4", mode).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded),
+    );
+
+    // Each pair of a nested key counts
+    let de = Deserializer::from_str("a[b]=1&a[c]=2&a[d]=3&a[e]=4", ParseMode::Brackets);
+    assert_eq!(
+        HashMap::<String, HashMap<String, u32>>::deserialize(de.max_input_vars(3))
+            .map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded)
+    );
+}
+
+#[test]
+fn deserialize_max_key_len() {
+    fn limited(input: &str, mode: ParseMode) -> Result<HashMap<String, String>, Error> {
+        HashMap::deserialize(Deserializer::from_str(input, mode).max_key_len(4))
+    }
+
+    // At the limit, values and bare keys are checked the same way
+    check_result(|mode| limited("abcd=12345678&efgh", mode).is_ok(), true);
+
+    // Over the limit, the length is counted before decoding
+    check_result(
+        |mode| limited("a=1&abcde=2", mode).map_err(|e| (e.kind, e.value)),
+        Err((ErrorKind::LimitExceeded, "abcd".to_string())),
+    );
+    check_result(
+        |mode| limited("%61bc=1", mode).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded),
+    );
+    check_result(
+        |mode| limited("abcde", mode).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded),
+    );
+
+    // A huge key is rejected without keeping it in the error
+    let key = "a".repeat(1 << 20);
+    let error = limited(&format!("{}=1", key), ParseMode::UrlEncoded).unwrap_err();
+    assert_eq!(error.value.len(), 4);
+
+    // The sub keys are part of the key in brackets mode
+    let de = Deserializer::from_str("a[bc]=1", ParseMode::Brackets).max_key_len(4);
+    assert_eq!(
+        HashMap::<String, HashMap<String, u32>>::deserialize(de).map_err(|e| (e.kind, e.value)),
+        Err((ErrorKind::LimitExceeded, "a[bc".to_string()))
+    );
+}
+
+#[test]
+fn deserialize_max_total_decoded_bytes() {
+    fn limited(input: &str, mode: ParseMode) -> Result<HashMap<String, String>, Error> {
+        HashMap::deserialize(Deserializer::from_str(input, mode).max_total_decoded_bytes(8))
+    }
+
+    // At the limit, the length is counted after decoding and without `&` and `=`
+    check_result(|mode| limited("ab=%41%42%43&cd=e", mode).is_ok(), true);
+    check_result(|mode| limited("&abcd=&efgh&", mode).is_ok(), true);
+
+    // Over the limit
+    check_result(
+        |mode| limited("ab=cd&ef=ghi", mode).map_err(|e| e.kind),
+        Err(ErrorKind::LimitExceeded),
+    );
+
+    // Only what is deserialized is counted, not the ignored keys nor the unknown fields
+    let ignored: &[&[u8]] = &[b"utm_source"];
+    check_result(
+        |mode| {
+            HashMap::<String, String>::deserialize(
+                Deserializer::from_str("utm_source=newsletter&ab=cd", mode)
+                    .ignore_keys(ignored)
+                    .max_total_decoded_bytes(4),
+            )
+        },
+        Ok(HashMap::from([("ab".to_string(), "cd".to_string())])),
+    );
+    check_result(
+        |mode| {
+            Primitive::<u32>::deserialize(
+                Deserializer::from_str("value=1&other=%41%41%41%41", mode)
+                    .max_total_decoded_bytes(11),
+            )
+        },
+        Ok(p!(1)),
+    );
+
+    // And it's not collected with the other errors
+    let errors = Deserializer::from_str("a=x&b=2&c=3", ParseMode::UrlEncoded)
+        .max_total_decoded_bytes(4)
+        .collect_errors::<HashMap<String, u32>>()
+        .unwrap_err();
+    assert_eq!(
+        errors.into_iter().map(|e| e.kind).collect::<Vec<_>>(),
+        vec![ErrorKind::InvalidNumber, ErrorKind::LimitExceeded]
+    );
+
+    // Many large values which are fine one by one, but not in total
+    let value = "%41".repeat(1 << 10);
+    let input = (0..64)
+        .map(|i| format!("key{}={}", i, value))
+        .collect::<Vec<_>>()
+        .join("&");
+    let de = Deserializer::from_str(&input, ParseMode::UrlEncoded);
+    assert!(HashMap::<String, String>::deserialize(de.max_total_decoded_bytes(1 << 17)).is_ok());
+    assert_eq!(
+        HashMap::<String, String>::deserialize(de.max_total_decoded_bytes(1 << 15))
+            .map_err(|e| (e.kind, e.message)),
+        Err((
+            ErrorKind::LimitExceeded,
+            "the decoded keys and values are longer than 32768 bytes in total".to_string()
+        ))
+    );
+}
+
+#[test]
+fn deserialize_ignoring_keys() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", deny_unknown_fields)]
+    struct Search {
+        query: String,
+        page: u32,
+    }
+
+    let input = "utm_source=news&query=rust&_csrf=token&page=2&utm%5Fmedium=email";
+    let ignored: &[&[u8]] = &[b"utm_source", b"utm_medium", b"_csrf"];
+
+    check_result(|mode| from_str::<Search>(input, mode).is_err(), true);
+    check_result(
+        |mode| Search::deserialize(Deserializer::from_str(input, mode).ignore_keys(ignored)),
+        Ok(Search {
+            query: "rust".to_string(),
+            page: 2,
+        }),
+    );
+}
+
 #[test]
 fn deserialize_error_test() {
     check_result(
@@ -605,3 +1545,580 @@ fn deserialize_error_test() {
         ErrorKind::InvalidBoolean,
     );
 }
+
+#[test]
+fn deserialize_collect_errors() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Form {
+        age: u32,
+        name: String,
+        subscribed: bool,
+        score: Option<f64>,
+    }
+
+    check_result(
+        |mode| from_str_collect_errors("age=20&name=foo&subscribed=on", mode),
+        Ok(Form {
+            age: 20,
+            name: String::from("foo"),
+            subscribed: true,
+            score: None,
+        }),
+    );
+
+    check_result(
+        |mode| {
+            from_str_collect_errors::<Form>("age=old&name=foo&subscribed=maybe&score=high", mode)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| (e.kind, e.value))
+                .collect::<Vec<_>>()
+        },
+        vec![
+            (ErrorKind::InvalidNumber, String::from("old")),
+            (ErrorKind::InvalidNumber, String::from("high")),
+            (ErrorKind::InvalidBoolean, String::from("maybe")),
+        ],
+    );
+
+    // Missing fields are reported after the value errors
+    check_result(
+        |mode| {
+            from_str_collect_errors::<Form>("age=20&subscribed=maybe", mode)
+                .unwrap_err()
+                .into_iter()
+                .map(|e| e.kind)
+                .collect::<Vec<_>>()
+        },
+        vec![ErrorKind::InvalidBoolean, ErrorKind::Other],
+    );
+    check_result(
+        |mode| {
+            from_str_collect_errors::<Form>("age=20&subscribed=maybe", mode)
+                .unwrap_err()
+                .pop()
+                .unwrap()
+                .message
+        },
+        String::from("missing field `name`"),
+    );
+
+    // The options of the deserializer are kept, and nested values are collected too
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Nested {
+        form: Form,
+        scores: Vec<u32>,
+    }
+
+    let errors = Deserializer::from_str(
+        "form[age]=old&form[name]=foo&form[subscribed]=TRUE&scores[]=1&scores[]=x",
+        ParseMode::Brackets,
+    )
+    .case_insensitive_bool(true)
+    .collect_errors::<Nested>()
+    .unwrap_err();
+    assert_eq!(
+        errors
+            .into_iter()
+            .map(|e| (e.kind, e.value))
+            .collect::<Vec<_>>(),
+        vec![
+            (ErrorKind::InvalidNumber, String::from("old")),
+            (ErrorKind::InvalidNumber, String::from("x")),
+        ]
+    );
+
+    // The errors of the elements keep their index
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Scores {
+        scores: Vec<u32>,
+    }
+
+    let errors =
+        from_str_collect_errors::<Scores>("scores=1&scores=x&scores=y", ParseMode::Duplicate)
+            .unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].message.ends_with("at index 1 of the sequence"));
+    assert!(errors[1].message.ends_with("at index 2 of the sequence"));
+}
+
+/// Two fields renamed to the same key is a programmer error, rustc warns about an unreachable
+/// pattern in the derived code and the key goes to the first of the fields
+#[test]
+#[allow(unreachable_patterns)]
+fn deserialize_colliding_renames() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Colliding {
+        #[serde(rename = "key")]
+        first: Option<u32>,
+        #[serde(rename = "key")]
+        second: Option<u32>,
+    }
+
+    check_result(
+        |mode| from_bytes(b"key=1", mode),
+        Ok(Colliding {
+            first: Some(1),
+            second: None,
+        }),
+    );
+}
+
+#[test]
+fn deserialize_from_reader() {
+    check_result(
+        |mode| from_reader(std::io::Cursor::new(b"value=hello+world"), mode),
+        Ok(p!("hello world".to_string())),
+    );
+
+    struct FailingReader;
+
+    impl std::io::Read for FailingReader {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "connection reset",
+            ))
+        }
+    }
+
+    check_result(
+        |mode| {
+            from_reader::<_, Primitive<String>>(FailingReader, mode)
+                .map_err(|e| (e.kind, e.message))
+        },
+        Err((ErrorKind::Io, "connection reset".to_string())),
+    );
+}
+
+#[test]
+fn deserialize_with_scratch() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Encoded {
+        first: String,
+        second: Option<String>,
+        number: u32,
+    }
+
+    // The leftovers of the previous values shouldn't leak into the next ones
+    let mut scratch = b"leftover".to_vec();
+    for mode in [
+        ParseMode::UrlEncoded,
+        ParseMode::Duplicate,
+        ParseMode::Delimiter(b'|'),
+        ParseMode::Brackets,
+    ] {
+        for (input, expected) in [
+            ("first=a%20b&second=%26&number=1", ("a b", Some("&"), 1)),
+            ("number=2&first=c+d", ("c d", None, 2)),
+        ] {
+            let de = Deserializer::from_str(input, mode);
+            assert_eq!(
+                Encoded::deserialize(de.with_scratch(&mut scratch)),
+                Ok(Encoded {
+                    first: expected.0.to_string(),
+                    second: expected.1.map(str::to_string),
+                    number: expected.2,
+                })
+            );
+        }
+    }
+
+    // The whole querystring can be optional too
+    let de = Deserializer::from_str("", ParseMode::UrlEncoded);
+    assert_eq!(
+        Option::<Encoded>::deserialize(de.with_scratch(&mut scratch)),
+        Ok(None)
+    );
+}
+
+#[test]
+fn deserialize_numeric_enums() {
+    /// Deserialized from its discriminant, the same way `serde_repr` does
+    #[derive(Debug, PartialEq, Eq, Hash)]
+    enum Status {
+        Active = 1,
+        Banned = 2,
+    }
+
+    impl<'de> Deserialize<'de> for Status {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: _serde::Deserializer<'de>,
+        {
+            match u8::deserialize(deserializer)? {
+                1 => Ok(Status::Active),
+                2 => Ok(Status::Banned),
+                other => Err(_serde::de::Error::custom(format_args!(
+                    "invalid discriminant {}",
+                    other
+                ))),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    enum Renamed {
+        #[serde(rename = "1")]
+        Active,
+        #[serde(rename = "2")]
+        Banned,
+    }
+
+    check_result(|mode| from_str("value=2", mode), Ok(p!(Status::Banned)));
+    check_result(
+        |mode| from_str("value=1", mode),
+        Ok(p!(Some(Status::Active))),
+    );
+    check_result(
+        |mode| from_str("2=a", mode),
+        Ok(HashMap::from([(Status::Banned, "a")])),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Status>>("value=3", mode).map_err(|e| e.kind),
+        Err(ErrorKind::Other),
+    );
+    check_result(
+        |mode| from_str::<Primitive<Status>>("value=x", mode).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidNumber),
+    );
+
+    check_result(|mode| from_str("value=2", mode), Ok(p!(Renamed::Banned)));
+    check_result(
+        |mode| from_str::<Primitive<Renamed>>("value=3", mode).is_err(),
+        true,
+    );
+
+    // Internally tagged enums with numeric tags
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde", tag = "kind")]
+    enum Event {
+        #[serde(rename = "1")]
+        Login { user: String },
+        #[serde(rename = "2")]
+        Logout,
+    }
+
+    check_result(
+        |mode| from_str("kind=1&user=pouya", mode),
+        Ok(Event::Login {
+            user: "pouya".to_string(),
+        }),
+    );
+    check_result(|mode| from_str("kind=2", mode), Ok(Event::Logout));
+}
+
+#[test]
+fn deserialize_iso8601_duration() {
+    let secs = |secs| Ok(p!(Iso8601Duration(Duration::from_secs(secs))));
+
+    check_result(|mode| from_str("value=PT1H30M", mode), secs(5400));
+    check_result(|mode| from_str("value=PT45S", mode), secs(45));
+    check_result(|mode| from_str("value=P2D", mode), secs(2 * 86400));
+    check_result(|mode| from_str("value=P1W", mode), secs(7 * 86400));
+    check_result(
+        |mode| from_str("value=P1W1DT1H1M1S", mode),
+        secs(8 * 86400 + 3661),
+    );
+    check_result(|mode| from_str("value=PT0S", mode), secs(0));
+    check_result(|mode| from_str("value=PT90M", mode), secs(5400));
+
+    // The last component can have a fraction
+    check_result(|mode| from_str("value=PT1.5H", mode), secs(5400));
+    check_result(
+        |mode| from_str("value=PT0,25S", mode),
+        Ok(p!(Iso8601Duration(Duration::from_millis(250)))),
+    );
+    check_result(
+        |mode| from_str("value=PT1M0.000000001S", mode),
+        Ok(p!(Iso8601Duration(Duration::new(60, 1)))),
+    );
+
+    // Works with options and the decoded value
+    check_result(
+        |mode| from_str("value=PT1H%33%30M", mode),
+        Ok(p!(Some(Iso8601Duration(Duration::from_secs(5400))))),
+    );
+
+    for invalid in [
+        "", "P", "PT", "1H", "PT1", "P1H", "PT1D", "P1Y", "P1M", "P1DT", "PT1M1H", "PT1H1H",
+        "PT1.5H1M", "PT.5S", "PT1.S", "PT-1S", "P1D1W", "pt1h", "PT1H ",
+    ] {
+        check_result(
+            |mode| {
+                from_str::<Primitive<Iso8601Duration>>(&format!("value={}", invalid), mode)
+                    .map_err(|e| e.message.starts_with("invalid ISO 8601 duration"))
+            },
+            Err(true),
+        );
+    }
+
+    // Too long to fit in a `Duration`
+    check_result(
+        |mode| {
+            from_str::<Primitive<Iso8601Duration>>("value=P99999999999999999999W", mode).is_err()
+        },
+        true,
+    );
+}
+
+#[test]
+fn deserialize_single_field() {
+    check_result(
+        |mode| field::<u32>(b"page=2&size=x", mode, "page"),
+        Ok(Some(2)),
+    );
+
+    // The key is compared after decoding, and the other values are never deserialized
+    check_result(
+        |mode| field::<u32>(b"page=x&si%7Ae=10", mode, "size"),
+        Ok(Some(10)),
+    );
+    check_result(|mode| field::<u32>(b"page=2", mode, "size"), Ok(None));
+    check_result(
+        |mode| field::<u32>(b"page=x", mode, "page").map_err(|e| e.kind),
+        Err(ErrorKind::InvalidNumber),
+    );
+
+    // Sequences are read the way each mode reads them
+    assert_eq!(
+        field::<Vec<u8>>(b"bytes=1&page=2&bytes=2", ParseMode::Duplicate, "bytes"),
+        Ok(Some(vec![1, 2]))
+    );
+    assert_eq!(
+        field::<Vec<u8>>(b"bytes=1|2|3", ParseMode::Delimiter(b'|'), "bytes"),
+        Ok(Some(vec![1, 2, 3]))
+    );
+    assert_eq!(
+        field::<Vec<u8>>(b"bytes[1]=2&bytes[0]=1", ParseMode::Brackets, "bytes"),
+        Ok(Some(vec![1, 2]))
+    );
+    assert_eq!(
+        field::<HashMap<String, u8>>(b"limits[min]=1", ParseMode::Brackets, "limits"),
+        Ok(Some(map! {"min".to_string() => 1}))
+    );
+}
+
+#[test]
+fn deserialize_with_raw() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Webhook {
+        event: String,
+    }
+
+    check_result(
+        |mode| {
+            from_str_with_raw::<Webhook>("event=pu%73h&sig=a%2Bb+c", mode)
+                .map(|(webhook, raw)| (webhook.event, raw.as_str().map(str::to_string)))
+        },
+        Ok((
+            "push".to_string(),
+            Ok("event=pu%73h&sig=a%2Bb+c".to_string()),
+        )),
+    );
+
+    // The raw query is returned as is, even when it's not valid utf8
+    let (_, raw) = from_bytes_with_raw::<IgnoredAny>(b"a=1&b=\xff", ParseMode::UrlEncoded).unwrap();
+    assert_eq!(raw.as_bytes(), b"a=1&b=\xff");
+    assert_eq!(
+        raw.as_str().map_err(|e| (e.kind, e.index)),
+        Err((ErrorKind::InvalidEncoding, Some(6)))
+    );
+
+    // Nothing is returned when deserializing fails
+    assert!(from_str_with_raw::<Webhook>("sig=x", ParseMode::UrlEncoded).is_err());
+}
+
+#[test]
+fn deserialize_partial() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Pair {
+        a: u32,
+        b: u32,
+    }
+
+    check_result(
+        |mode| from_str_partial::<Pair>("a=1&b=2|trailing", mode, '|'),
+        Ok((Pair { a: 1, b: 2 }, "trailing")),
+    );
+
+    // Only the first boundary ends the querystring, an encoded one is a part of it
+    check_result(
+        |mode| from_bytes_partial::<HashMap<String, String>>(b"a=%7C#b=2#c", mode, b'#'),
+        Ok((map! {"a".to_string() => "|".to_string()}, &b"b=2#c"[..])),
+    );
+
+    // Without a boundary, the whole input is parsed
+    check_result(
+        |mode| from_str_partial::<Pair>("a=1&b=2", mode, '|'),
+        Ok((Pair { a: 1, b: 2 }, "")),
+    );
+}
+
+#[test]
+fn parse_mode_round_trip() {
+    let modes = [
+        (ParseMode::UrlEncoded, r#""urlencoded""#),
+        (ParseMode::Duplicate, r#""duplicate""#),
+        (ParseMode::Delimiter(b'|'), r#"{"delimiter":"|"}"#),
+        (
+            ParseMode::DelimiterMap {
+                entry: b',',
+                kv: b':',
+            },
+            r#"{"delimiter_map":{"entry":",","kv":":"}}"#,
+        ),
+        (ParseMode::Brackets, r#""brackets""#),
+        // Bytes which are not ascii are numbers
+        (ParseMode::Delimiter(0xff), r#"{"delimiter":255}"#),
+    ];
+
+    for (mode, json) in modes {
+        assert_eq!(serde_json::to_string(&mode).unwrap(), json);
+        assert_eq!(serde_json::from_str::<ParseMode>(json).unwrap(), mode);
+    }
+
+    // The delimiters can also be given as numbers
+    assert_eq!(
+        serde_json::from_str::<ParseMode>(r#"{"delimiter_map":{"kv":58,"entry":","}}"#).unwrap(),
+        ParseMode::DelimiterMap {
+            entry: b',',
+            kv: b':',
+        }
+    );
+
+    // Even from a query string
+    assert_eq!(
+        from_str::<Primitive<ParseMode>>("value[delimiter]=%7C", ParseMode::Brackets),
+        Ok(p!(ParseMode::Delimiter(b'|')))
+    );
+
+    for invalid in [
+        r#""Brackets""#,
+        r#""delimiter""#,
+        r#"{"delimiter":"||"}"#,
+        r#"{"delimiter":256}"#,
+        r#"{"delimiter_map":{"entry":","}}"#,
+        r#"{"delimiter_map":{"entry":",","kv":":","other":"-"}}"#,
+    ] {
+        assert!(
+            serde_json::from_str::<ParseMode>(invalid).is_err(),
+            "{}",
+            invalid
+        );
+    }
+}
+
+#[test]
+fn deserialize_bare_keys_as_none() {
+    // A bare key has no value at all, while an empty value is still a value
+    check_result(
+        |mode| from_str::<HashMap<String, Option<String>>>("a&b=2&c=", mode),
+        Ok(map! {
+            "a".to_string() => None,
+            "b".to_string() => Some("2".to_string()),
+            "c".to_string() => Some(String::new()),
+        }),
+    );
+    check_result(
+        |mode| from_str::<HashMap<String, Option<u32>>>("a&b=2", mode),
+        Ok(map! {"a".to_string() => None, "b".to_string() => Some(2)}),
+    );
+
+    // A repeated key is bare only if none of its assignments has a value
+    check_result(
+        |mode| from_str::<HashMap<String, Option<String>>>("a&a", mode),
+        Ok(map! {"a".to_string() => None}),
+    );
+    assert_eq!(
+        from_str::<HashMap<String, Option<String>>>("a=1&a", ParseMode::UrlEncoded),
+        Ok(map! {"a".to_string() => None})
+    );
+    assert_eq!(
+        from_str::<HashMap<String, Option<Vec<String>>>>("a=1&a", ParseMode::Duplicate),
+        Ok(map! {"a".to_string() => Some(vec!["1".to_string(), String::new()])})
+    );
+    assert_eq!(
+        from_str::<HashMap<String, Option<Vec<String>>>>("a[0]=1&a", ParseMode::Brackets),
+        Ok(map! {"a".to_string() => Some(vec!["1".to_string(), String::new()])})
+    );
+}
+
+#[test]
+fn deserialize_maybe_invalid_utf8() {
+    check_result(
+        |mode| from_str("value=caf%C3%A9+au+lait", mode),
+        Ok(p!(MaybeInvalidUtf8("café au lait".to_string(), false))),
+    );
+    check_result(
+        |mode| from_str("value=", mode),
+        Ok(p!(MaybeInvalidUtf8(String::new(), false))),
+    );
+
+    // Invalid sequences are replaced and flagged, instead of failing like `String` does
+    check_result(
+        |mode| from_str("value=caf%E9%20%C3", mode),
+        Ok(p!(MaybeInvalidUtf8(
+            "caf\u{FFFD} \u{FFFD}".to_string(),
+            true
+        ))),
+    );
+    check_result(
+        |mode| from_bytes("value=\u{FFFD}%FF".as_bytes(), mode),
+        Ok(p!(MaybeInvalidUtf8("\u{FFFD}\u{FFFD}".to_string(), true))),
+    );
+    check_result(
+        |mode| from_str::<Primitive<String>>("value=caf%E9", mode).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidEncoding),
+    );
+
+    // In sequences
+    assert_eq!(
+        from_bytes(b"value=a&value=%FF", ParseMode::Duplicate),
+        Ok(p!(vec![
+            MaybeInvalidUtf8("a".to_string(), false),
+            MaybeInvalidUtf8("\u{FFFD}".to_string(), true)
+        ]))
+    );
+}
+
+#[test]
+fn deserialize_path() {
+    use std::path::{Path, PathBuf};
+
+    // Encoded slashes are decoded into the path, and don't split the value
+    check_result(
+        |mode| from_str("value=%2Fhome%2Fuser%2Fmy%20file.txt", mode),
+        Ok(p!(PathBuf::from("/home/user/my file.txt"))),
+    );
+    check_result(
+        |mode| from_str("value=/tmp/a%2Fb", mode),
+        Ok(p!(PathBuf::from("/tmp/a/b"))),
+    );
+    check_result(
+        |mode| from_str("value=docs%2Freadme.md", mode),
+        Ok(p!(Some(PathBuf::from("docs/readme.md")))),
+    );
+
+    let path = from_str::<Primitive<PathBuf>>("value=%2Fvar%2Flog", ParseMode::UrlEncoded)
+        .unwrap()
+        .value;
+    assert_eq!(
+        path.components().collect::<Vec<_>>(),
+        Path::new("/var/log").components().collect::<Vec<_>>()
+    );
+
+    // Borrowed paths are only possible without decoding
+    check_result(
+        |mode| from_str::<Primitive<&Path>>("value=/var/log", mode).map(|p| p.value.to_path_buf()),
+        Ok(PathBuf::from("/var/log")),
+    );
+}
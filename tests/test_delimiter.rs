@@ -2,8 +2,16 @@
 
 use std::collections::HashMap;
 
-use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    de::{from_bytes, Deserializer, ErrorKind, ParseMode},
+    to_string_delimiter,
+};
+use smallvec::SmallVec;
+
+mod common;
+
+use common::SizeHinted;
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -28,7 +36,7 @@ macro_rules! p {
     };
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(crate = "_serde")]
 struct Delimiter<'a> {
     #[serde(borrow)]
@@ -54,6 +62,23 @@ fn deserialize_delimiter() {
     )
 }
 
+#[test]
+fn serialize_delimiter() {
+    let value = Delimiter {
+        foo: "baz",
+        foobar: 1337,
+        bar: Some(13),
+        vec: vec![1337, 11],
+    };
+
+    let qs = to_string_delimiter(&value, b'|').unwrap();
+    assert_eq!(qs, "foo=baz&foobar=1337&bar=13&vec=1337|11");
+    assert_eq!(
+        from_bytes(qs.as_bytes(), ParseMode::Delimiter(b'|')),
+        Ok(value)
+    );
+}
+
 #[test]
 fn deserialize_string_with_delimiter() {
     assert_eq!(
@@ -62,6 +87,30 @@ fn deserialize_string_with_delimiter() {
     )
 }
 
+#[test]
+fn deserialize_whole_and_split_values() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Mixed {
+        whole: String,
+        split: Vec<u32>,
+        encoded: String,
+    }
+
+    // Only the sequences are split by the delimiter
+    assert_eq!(
+        from_bytes(
+            b"whole=1337|11|&split=1337|11&encoded=a+b%7Cc|d",
+            ParseMode::Delimiter(b'|')
+        ),
+        Ok(Mixed {
+            whole: String::from("1337|11|"),
+            split: vec![1337, 11],
+            encoded: String::from("a b|c|d"),
+        })
+    )
+}
+
 #[test]
 fn deserialize_repeated_keys() {
     // vector
@@ -115,6 +164,105 @@ fn deserialize_sequence() {
     );
 }
 
+#[test]
+fn deserialize_single_value_sequence() {
+    // A single value is accepted as a sequence of one element
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Delimiter(b'|')),
+        Ok(p!(vec![5u64]))
+    );
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Delimiter(b'|')),
+        Ok(p!(Some(vec![5u64])))
+    );
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Delimiter(b'|')),
+        Ok(p!((5u64,)))
+    );
+
+    // But an empty value is an empty sequence
+    assert_eq!(
+        from_bytes(b"value=", ParseMode::Delimiter(b'|')),
+        Ok(p!(Vec::<String>::new()))
+    );
+
+    // The same field still accepts multiple values
+    assert_eq!(
+        from_bytes(b"value=5|7", ParseMode::Delimiter(b'|')),
+        Ok(p!(vec![5u64, 7]))
+    );
+}
+
+#[test]
+fn deserialize_custom_sequence() {
+    let res: Primitive<SmallVec<[u8; 4]>> =
+        from_bytes(b"value=1,2,3", ParseMode::Delimiter(b',')).unwrap();
+    assert_eq!(res.value.as_slice(), &[1, 2, 3]);
+    assert!(!res.value.spilled());
+
+    let res: Primitive<SmallVec<[u8; 4]>> =
+        from_bytes(b"value=1,2,3,4,5", ParseMode::Delimiter(b',')).unwrap();
+    assert_eq!(res.value.as_slice(), &[1, 2, 3, 4, 5]);
+    assert!(res.value.spilled());
+}
+
+#[test]
+fn deserialize_long_sequence() {
+    let input = format!(
+        "value={}",
+        (0..10_000)
+            .map(|i| i.to_string())
+            .collect::<Vec<_>>()
+            .join("|")
+    );
+    let res: Primitive<Vec<u32>> =
+        from_bytes(input.as_bytes(), ParseMode::Delimiter(b'|')).unwrap();
+    assert_eq!(res.value, (0..10_000).collect::<Vec<_>>());
+
+    assert_eq!(
+        from_bytes(input.as_bytes(), ParseMode::Delimiter(b'|')),
+        Ok(p!(SizeHinted {
+            hint: Some(10_000),
+            len: 10_000
+        }))
+    );
+
+    // A trailing delimiter doesn't add a value
+    assert_eq!(
+        from_bytes(b"value=1|2|", ParseMode::Delimiter(b'|')),
+        Ok(p!(SizeHinted {
+            hint: Some(2),
+            len: 2
+        }))
+    );
+    assert_eq!(
+        from_bytes(b"value=||", ParseMode::Delimiter(b'|')),
+        Ok(p!(SizeHinted {
+            hint: Some(2),
+            len: 2
+        }))
+    );
+}
+
+#[test]
+fn deserialize_bool_sequence() {
+    // All the boolean tokens are accepted in sequences too, a trailing delimiter doesn't add a value
+    assert_eq!(
+        from_bytes(b"value=false,true,off,on,0,1,", ParseMode::Delimiter(b',')),
+        Ok(p!(vec![false, true, false, true, false, true]))
+    );
+
+    // An empty value is true, like a bare key
+    assert_eq!(
+        from_bytes(b"value=1||0", ParseMode::Delimiter(b'|')),
+        Ok(p!(vec![true, true, false]))
+    );
+
+    assert!(
+        from_bytes::<Primitive<Vec<bool>>>(b"value=1|yes", ParseMode::Delimiter(b'|')).is_err()
+    );
+}
+
 #[test]
 fn deserialize_optional_seq() {
     #[derive(Debug, Deserialize, PartialEq)]
@@ -184,6 +332,19 @@ fn deserialize_unit_enums() {
             value: vec![Side::God, Side::Left, Side::Right]
         })
     );
+
+    // An invalid variant is reported with its index in the sequence
+    let error =
+        from_bytes::<VecEnum>(b"value=God|Unknown", ParseMode::Delimiter(b'|')).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::Other);
+    assert_eq!(
+        error.message,
+        "unknown variant `Unknown`, expected one of `Left`, `Right`, `God` at index 1 of the sequence"
+    );
+    assert_eq!(error.value, "Unknown");
+
+    let error = from_bytes::<VecEnum>(b"value=|Left", ParseMode::Delimiter(b'|')).unwrap_err();
+    assert!(error.message.ends_with("at index 0 of the sequence"));
 }
 
 #[test]
@@ -208,3 +369,238 @@ fn deserialize_invalid_sequence() {
     )
     .is_err());
 }
+
+#[test]
+fn deserialize_key_delimiters() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Filter<'a> {
+        ids: Vec<u32>,
+        #[serde(borrow)]
+        tags: Vec<&'a str>,
+    }
+
+    let de = Deserializer::from_str("ids=1,2,3&tags=a|b,c", ParseMode::Delimiter(b'|'));
+
+    // Other keys use the delimiter of the mode
+    assert_eq!(
+        Filter::deserialize(de.key_delimiters(&[(b"ids", b',')])),
+        Ok(Filter {
+            ids: vec![1, 2, 3],
+            tags: vec!["a", "b,c"],
+        })
+    );
+    assert!(Filter::deserialize(de).is_err());
+
+    // Keys are compared after decoding
+    let de = Deserializer::from_str("i%64s=1,2&tags=a,b", ParseMode::Delimiter(b'|'));
+    assert_eq!(
+        Filter::deserialize(de.key_delimiters(&[(b"ids", b','), (b"tags", b',')])),
+        Ok(Filter {
+            ids: vec![1, 2],
+            tags: vec!["a", "b"],
+        })
+    );
+}
+
+#[test]
+fn deserialize_float_sequence() {
+    // The decimal point doesn't interfere with a comma delimiter
+    assert_eq!(
+        from_bytes(b"value=1.0,2.0,3.0", ParseMode::Delimiter(b',')),
+        Ok(p!([1.0f32, 2.0, 3.0]))
+    );
+    assert_eq!(
+        from_bytes(b"value=-1.5,2e3,.25,4", ParseMode::Delimiter(b',')),
+        Ok(p!(vec![-1.5f64, 2000.0, 0.25, 4.0]))
+    );
+    assert_eq!(
+        from_bytes(b"value=1.5", ParseMode::Delimiter(b',')),
+        Ok(p!((1.5f32,)))
+    );
+
+    // Neither does a dot delimiter with integers
+    assert_eq!(
+        from_bytes(b"value=1.2.3", ParseMode::Delimiter(b'.')),
+        Ok(p!([1.0f32, 2.0, 3.0]))
+    );
+
+    // A comma is never a decimal separator
+    assert!(from_bytes::<Primitive<[f32; 2]>>(b"value=1,5", ParseMode::Delimiter(b'|')).is_err());
+    assert!(
+        from_bytes::<Primitive<[f32; 3]>>(b"value=1.0,2.0", ParseMode::Delimiter(b',')).is_err()
+    );
+}
+
+#[test]
+fn deserialize_escaped_delimiters() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Filter {
+        tags: Vec<String>,
+        pair: (String, String),
+    }
+
+    let de = Deserializer::from_str(r"tags=a\,b,c&pair=x\\,y", ParseMode::Delimiter(b','));
+
+    // Escaping is disabled by default
+    assert_eq!(
+        Filter::deserialize(de),
+        Ok(Filter {
+            tags: vec![r"a\".to_string(), "b".to_string(), "c".to_string()],
+            pair: (r"x\\".to_string(), "y".to_string()),
+        })
+    );
+
+    assert_eq!(
+        Filter::deserialize(de.delimiter_escape(b'\\')),
+        Ok(Filter {
+            tags: vec!["a,b".to_string(), "c".to_string()],
+            pair: (r"x\".to_string(), "y".to_string()),
+        })
+    );
+
+    // A percent encoded delimiter is never a delimiter
+    let de = Deserializer::from_str("tags=a%2Cb,c&pair=%5C,y", ParseMode::Delimiter(b','));
+    assert_eq!(
+        Filter::deserialize(de.delimiter_escape(b'\\')),
+        Ok(Filter {
+            tags: vec!["a,b".to_string(), "c".to_string()],
+            pair: (r"\".to_string(), "y".to_string()),
+        })
+    );
+
+    // Both delimiters of a map can be escaped
+    let de = Deserializer::from_str(
+        r"value=name:a\:b,tag:x\,y",
+        ParseMode::DelimiterMap {
+            entry: b',',
+            kv: b':',
+        },
+    );
+    assert_eq!(
+        Primitive::deserialize(de.delimiter_escape(b'\\')),
+        Ok(p!(HashMap::from([
+            ("name".to_string(), "a:b".to_string()),
+            ("tag".to_string(), "x,y".to_string())
+        ])))
+    );
+}
+
+#[test]
+fn deserialize_collapse_delimiters() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Filter<'a> {
+        #[serde(borrow)]
+        tags: Vec<&'a str>,
+        ids: Option<Vec<u32>>,
+        pair: Option<(u32, &'a str)>,
+    }
+
+    let de = Deserializer::from_str("tags=a,,b,&ids=,1,,,2", ParseMode::Delimiter(b','));
+
+    // By default every delimiter starts a new element, except a trailing one
+    assert_eq!(
+        from_bytes(b"value=a,,b,", ParseMode::Delimiter(b',')),
+        Ok(p!(vec!["a", "", "b"]))
+    );
+    assert!(Filter::deserialize(de).is_err());
+
+    assert_eq!(
+        Filter::deserialize(de.collapse_delimiters(true)),
+        Ok(Filter {
+            tags: vec!["a", "b"],
+            ids: Some(vec![1, 2]),
+            pair: None,
+        })
+    );
+
+    // Sized sequences don't count the empty elements either
+    let de = Deserializer::from_str("tags=,,&pair=,,1,,x,,", ParseMode::Delimiter(b','));
+    assert_eq!(
+        Filter::deserialize(de.collapse_delimiters(true)),
+        Ok(Filter {
+            tags: vec![],
+            ids: None,
+            pair: Some((1, "x")),
+        })
+    );
+}
+
+#[test]
+fn deserialize_delimiter_map() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Limits {
+        min: u32,
+        max: Option<u32>,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Query<'a> {
+        config: HashMap<String, u8>,
+        limits: Limits,
+        #[serde(borrow)]
+        tags: Vec<&'a str>,
+    }
+
+    let mode = ParseMode::DelimiterMap {
+        entry: b',',
+        kv: b':',
+    };
+
+    // Sequences are still split by the entry delimiter
+    assert_eq!(
+        from_bytes(b"config=a:1,b:2&limits=min:5&tags=x,y", mode),
+        Ok(Query {
+            config: HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+            limits: Limits { min: 5, max: None },
+            tags: vec!["x", "y"],
+        })
+    );
+
+    // Keys and values are decoded, only the first `kv` splits an entry, and empty entries
+    // are skipped
+    assert_eq!(
+        from_bytes(b"value=a%2Cb:1:2,,c+d:%3A,e,:f,", mode),
+        Ok(p!(BTreeMap::from([
+            ("a,b".to_string(), "1:2".to_string()),
+            ("c d".to_string(), ":".to_string()),
+            ("e".to_string(), String::new()),
+            (String::new(), "f".to_string())
+        ])))
+    );
+
+    // Structs check their fields
+    assert_eq!(
+        from_bytes::<Primitive<Limits>>(b"value=min:x", mode)
+            .unwrap_err()
+            .kind,
+        ErrorKind::InvalidNumber
+    );
+    assert!(from_bytes::<Primitive<Limits>>(b"value=max:5", mode).is_err());
+
+    // Other modes don't split the values of maps
+    assert!(
+        from_bytes::<Primitive<HashMap<String, u8>>>(b"value=a:1", ParseMode::Delimiter(b','))
+            .is_err()
+    );
+
+    // Keys can use their own entry delimiter
+    let de = Deserializer::from_str("config=a:1|b:2&limits=min:1,max:2&tags=x", mode);
+    assert_eq!(
+        Query::deserialize(de.key_delimiters(&[(b"config", b'|')])),
+        Ok(Query {
+            config: HashMap::from([("a".to_string(), 1), ("b".to_string(), 2)]),
+            limits: Limits {
+                min: 1,
+                max: Some(2)
+            },
+            tags: vec!["x"],
+        })
+    );
+}
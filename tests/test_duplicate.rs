@@ -1,7 +1,16 @@
 //! These tests are meant for the `DuplicateQS` method
 
-use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ParseMode};
+use std::collections::HashMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    de::{from_bytes, Deserializer, ErrorKind, ParseMode},
+    to_string_duplicate, DuplicateQS,
+};
+
+mod common;
+
+use common::SizeHinted;
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -26,7 +35,7 @@ macro_rules! p {
     };
 }
 
-#[derive(Debug, Deserialize, PartialEq)]
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
 #[serde(crate = "_serde")]
 struct Duplicate<'a> {
     #[serde(borrow)]
@@ -52,6 +61,87 @@ fn deserialize_duplicate() {
     )
 }
 
+#[test]
+fn serialize_duplicate() {
+    let value = Duplicate {
+        foo: "baz",
+        foobar: 1337,
+        bar: None,
+        vec: vec![1337, 11],
+    };
+
+    let qs = to_string_duplicate(&value).unwrap();
+    assert_eq!(qs, "foo=baz&foobar=1337&vec=1337&vec=11");
+    assert_eq!(from_bytes(qs.as_bytes(), ParseMode::Duplicate), Ok(value));
+}
+
+#[test]
+fn deserialize_single_value_sequence() {
+    // A single value is accepted as a sequence of one element
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Duplicate),
+        Ok(p!(vec![5u64]))
+    );
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Duplicate),
+        Ok(p!(Some(vec![5u64])))
+    );
+    assert_eq!(
+        from_bytes(b"value=5", ParseMode::Duplicate),
+        Ok(p!((5u64,)))
+    );
+    assert_eq!(
+        from_bytes(b"value=", ParseMode::Duplicate),
+        Ok(p!(vec![String::new()]))
+    );
+
+    // The same field still accepts multiple values
+    assert_eq!(
+        from_bytes(b"value=5&value=7", ParseMode::Duplicate),
+        Ok(p!(vec![5u64, 7]))
+    );
+}
+
+#[test]
+fn deserialize_long_sequence() {
+    let input = (0..10_000)
+        .map(|i| format!("value={}", i))
+        .collect::<Vec<_>>()
+        .join("&");
+    let res: Primitive<Vec<u32>> = from_bytes(input.as_bytes(), ParseMode::Duplicate).unwrap();
+    assert_eq!(res.value, (0..10_000).collect::<Vec<_>>());
+
+    assert_eq!(
+        from_bytes(input.as_bytes(), ParseMode::Duplicate),
+        Ok(p!(SizeHinted {
+            hint: Some(10_000),
+            len: 10_000
+        }))
+    );
+}
+
+#[test]
+fn deserialize_bool_sequence() {
+    // All the boolean tokens are accepted in sequences too, a bare key is true
+    assert_eq!(
+        from_bytes(
+            b"value=false&value=true&value=off&value=on&value=0&value=1&value",
+            ParseMode::Duplicate
+        ),
+        Ok(p!(vec![false, true, false, true, false, true, true]))
+    );
+
+    // An empty value is true as well
+    assert_eq!(
+        from_bytes(b"value=1&value=&value=0", ParseMode::Duplicate),
+        Ok(p!(vec![true, true, false]))
+    );
+
+    assert!(
+        from_bytes::<Primitive<Vec<bool>>>(b"value=1&value=yes", ParseMode::Duplicate).is_err()
+    );
+}
+
 #[test]
 fn deserialize_optional_seq() {
     #[derive(Debug, Deserialize, PartialEq)]
@@ -143,6 +233,49 @@ fn deserialize_sequence() {
     );
 }
 
+#[test]
+fn deserialize_sorted_sequence() {
+    // The order is kept by default
+    assert_eq!(
+        from_bytes(b"value=b&value=c&value=a", ParseMode::Duplicate),
+        Ok(p!(vec!["b", "c", "a"]))
+    );
+
+    assert_eq!(
+        DuplicateQS::parse(b"value=b&value=c&value=a")
+            .sort_values(true)
+            .deserialize(),
+        Ok(p!(vec!["a", "b", "c"]))
+    );
+
+    // Sorted by the decoded values
+    assert_eq!(
+        Primitive::deserialize(
+            Deserializer::from_bytes(
+                b"value=b&value=%61&value=%20&value=+b",
+                ParseMode::Duplicate
+            )
+            .sort_values(true)
+        ),
+        Ok(p!(vec![
+            " ".to_string(),
+            " b".to_string(),
+            "a".to_string(),
+            "b".to_string()
+        ]))
+    );
+
+    // Same values in a different order are deserialized the same
+    assert_eq!(
+        DuplicateQS::parse(b"value=3&value=1&value=2")
+            .sort_values(true)
+            .deserialize::<Primitive<Vec<u32>>>(),
+        DuplicateQS::parse(b"value=2&value=3&value=1")
+            .sort_values(true)
+            .deserialize::<Primitive<Vec<u32>>>(),
+    );
+}
+
 #[test]
 fn deserialize_decoded_keys() {
     // having different encoded kinds of the string `value` for key
@@ -176,3 +309,171 @@ fn deserialize_invalid_sequence() {
     )
     .is_err());
 }
+
+#[test]
+fn deserialize_sequence_of_structs() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Item {
+        id: u32,
+    }
+
+    // Nested structs can't be expressed by repeating a key
+    let error =
+        from_bytes::<Primitive<Vec<Item>>>(b"value=1&value=2", ParseMode::Duplicate).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert!(error.message.contains("brackets mode"));
+    assert_eq!(error.value, "1");
+
+    assert!(
+        from_bytes::<Primitive<Vec<HashMap<String, u32>>>>(b"value=1", ParseMode::Duplicate)
+            .unwrap_err()
+            .message
+            .contains("brackets mode")
+    );
+
+    // The same goes for delimiter mode
+    assert!(
+        from_bytes::<Primitive<Vec<Item>>>(b"value=1|2", ParseMode::Delimiter(b'|'))
+            .unwrap_err()
+            .message
+            .contains("brackets mode")
+    );
+}
+
+#[test]
+fn deserialize_adjacently_tagged_enum() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        NewType(u32),
+        Tuple(u32, String),
+    }
+
+    assert_eq!(
+        from_bytes(b"t=Unit", ParseMode::Duplicate),
+        Ok(Adjacent::Unit)
+    );
+    // The content is visited after the tag, even if it comes first
+    assert_eq!(
+        from_bytes(b"c=5&t=NewType", ParseMode::Duplicate),
+        Ok(Adjacent::NewType(5))
+    );
+    assert_eq!(
+        from_bytes(b"t=Tuple&c=5&c=five", ParseMode::Duplicate),
+        Ok(Adjacent::Tuple(5, "five".to_string()))
+    );
+    assert!(from_bytes::<Adjacent>(b"t=NewType&c=five", ParseMode::Duplicate).is_err());
+}
+
+#[test]
+fn deserialize_flatten_pairs() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Proxy {
+        page: u32,
+        #[serde(flatten, deserialize_with = "serde_querystring::de::pairs")]
+        extra: Vec<(String, String)>,
+    }
+
+    let pair = |k: &str, v: &str| (k.to_string(), v.to_string());
+
+    assert_eq!(
+        from_bytes(
+            b"utm=x&tag=b&page=2&tag=a&tag=b&empty=&sp+ace=%3D",
+            ParseMode::Duplicate
+        ),
+        Ok(Proxy {
+            page: 2,
+            extra: vec![
+                pair("empty", ""),
+                pair("sp ace", "="),
+                pair("tag", "b"),
+                pair("tag", "a"),
+                pair("tag", "b"),
+                pair("utm", "x"),
+            ]
+        })
+    );
+
+    // The pairs are ordered by key, interleaved keys are not kept in the querystring's order
+    assert_eq!(
+        from_bytes(b"b=1&page=3&a=2&b=3", ParseMode::Duplicate),
+        Ok(Proxy {
+            page: 3,
+            extra: vec![pair("a", "2"), pair("b", "1"), pair("b", "3")]
+        })
+    );
+
+    assert_eq!(
+        from_bytes(b"page=1", ParseMode::Duplicate),
+        Ok(Proxy {
+            page: 1,
+            extra: vec![]
+        })
+    );
+
+    // Known keys are still deserialized from their repeated values
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde")]
+    struct Tags {
+        tag: Vec<u32>,
+        #[serde(flatten, deserialize_with = "serde_querystring::de::pairs")]
+        extra: Vec<(String, u32)>,
+    }
+
+    assert_eq!(
+        from_bytes(b"tag=1&tag=2&n=3&n=4", ParseMode::Duplicate),
+        Ok(Tags {
+            tag: vec![1, 2],
+            extra: vec![("n".to_string(), 3), ("n".to_string(), 4)]
+        })
+    );
+}
+
+/// Repeated keys are visited as sequences by untagged enums, and other keys as single values
+#[test]
+fn deserialize_untagged_one_or_many() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(crate = "_serde", untagged)]
+    enum ManyOrOne {
+        Many(Vec<String>),
+        One(String),
+    }
+
+    assert_eq!(
+        from_bytes(b"value=1&value=2", ParseMode::Duplicate),
+        Ok(p!(OneOrMany::Many(vec!["1".to_string(), "2".to_string()])))
+    );
+    assert_eq!(
+        from_bytes(b"value=1", ParseMode::Duplicate),
+        Ok(p!(OneOrMany::One("1".to_string())))
+    );
+
+    // The order of the variants doesn't matter
+    assert_eq!(
+        from_bytes(b"value=a+b&value=%26", ParseMode::Duplicate),
+        Ok(p!(ManyOrOne::Many(vec![
+            "a b".to_string(),
+            "&".to_string()
+        ])))
+    );
+    assert_eq!(
+        from_bytes(b"value=a+b", ParseMode::Duplicate),
+        Ok(p!(ManyOrOne::One("a b".to_string())))
+    );
+
+    // Empty values are kept
+    assert_eq!(
+        from_bytes(b"value&value=", ParseMode::Duplicate),
+        Ok(p!(OneOrMany::Many(vec![String::new(), String::new()])))
+    );
+}
@@ -0,0 +1,69 @@
+//! These tests check percent decoding against the `percent-encoding` crate with random input,
+//! run them with `cargo test --features proptest`
+
+use percent_encoding::{percent_decode, percent_encode, NON_ALPHANUMERIC};
+use proptest::prelude::*;
+use serde_querystring::UrlEncodedQS;
+
+/// Decodes the bytes as the value of a pair, the way the parsers do
+fn decode(value: &[u8]) -> Vec<u8> {
+    let mut input = b"v=".to_vec();
+    input.extend_from_slice(value);
+
+    UrlEncodedQS::parse(&input)
+        .value_owned(b"v")
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Lowercases the hex digits of the escapes at the chosen positions
+fn lowercase_escapes(encoded: &str, mask: &[bool]) -> String {
+    let mut out = String::with_capacity(encoded.len());
+    let mut escapes = mask.iter().cycle();
+    let mut chars = encoded.chars();
+
+    while let Some(c) = chars.next() {
+        out.push(c);
+        if c == '%' {
+            let lower = *escapes.next().unwrap_or(&false);
+            for h in chars.by_ref().take(2) {
+                out.push(if lower { h.to_ascii_lowercase() } else { h });
+            }
+        }
+    }
+
+    out
+}
+
+proptest! {
+    #[test]
+    fn decodes_encoded_bytes(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+        let encoded = percent_encode(&bytes, NON_ALPHANUMERIC).to_string();
+        prop_assert_eq!(decode(encoded.as_bytes()), bytes);
+    }
+
+    #[test]
+    fn decodes_plus_as_space(bytes in proptest::collection::vec(any::<u8>(), 0..64)) {
+        let encoded = percent_encode(&bytes, NON_ALPHANUMERIC)
+            .to_string()
+            .replace("%20", "+");
+        prop_assert_eq!(decode(encoded.as_bytes()), bytes);
+    }
+
+    #[test]
+    fn decodes_mixed_case_hex(
+        bytes in proptest::collection::vec(any::<u8>(), 0..64),
+        mask in proptest::collection::vec(any::<bool>(), 1..8),
+    ) {
+        let encoded = percent_encode(&bytes, NON_ALPHANUMERIC).to_string();
+        let encoded = lowercase_escapes(&encoded, &mask);
+        prop_assert_eq!(decode(encoded.as_bytes()), bytes);
+    }
+
+    /// Incomplete or invalid escapes are kept as they are
+    #[test]
+    fn decodes_like_percent_encoding(value in "[a-f0-9%+= \u{e9}-]{0,32}") {
+        let expected: Vec<u8> = percent_decode(value.replace('+', " ").as_bytes()).collect();
+        prop_assert_eq!(decode(value.as_bytes()), expected);
+    }
+}
@@ -0,0 +1,242 @@
+//! These tests keep a corpus of malformed querystrings as regression tests, they should
+//! either deserialize cleanly or return a typed error, but never panic
+
+use std::collections::HashMap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use _serde::{de::IgnoredAny, Deserialize};
+use serde_querystring::de::{from_bytes, Error, ErrorKind, ParseMode};
+
+const MODES: [ParseMode; 5] = [
+    ParseMode::UrlEncoded,
+    ParseMode::Duplicate,
+    ParseMode::Delimiter(b','),
+    ParseMode::DelimiterMap {
+        entry: b',',
+        kv: b':',
+    },
+    ParseMode::Brackets,
+];
+
+const CORPUS: &[&[u8]] = &[
+    // Stray separators
+    b"",
+    b"&",
+    b"=",
+    b"==",
+    b"&=&",
+    b"=a",
+    b"a==b",
+    b"a=b=c",
+    b"&&&deal_type=x&&",
+    b"deal_type=x&=&page=2",
+    b"deal_type&deal_type=",
+    // Incomplete or invalid percent escapes
+    b"%",
+    b"%2",
+    b"%zz=1",
+    b"a=%",
+    b"a=%4",
+    b"a=%%41",
+    b"%ff=%fe",
+    b"a=%C3",
+    b"\xff=\xfe",
+    b"+=+",
+    // Malformed brackets
+    b"[",
+    b"]",
+    b"[]",
+    b"[]=1",
+    b"a[",
+    b"a]=1",
+    b"a[=1",
+    b"a[b=1",
+    b"a[]]=1",
+    b"a[[b]]=1",
+    b"a[b][=1",
+    b"a[][]=1",
+    b"a%5B0%5D=1",
+    b"deal_type[x]=1",
+    b"ids=1&ids[0]=2",
+    b"ids[0]=1&ids=2",
+    b"ids[0][a]=1&ids[0]=2",
+    // Huge or invalid indices and numbers
+    b"ids[99999999999999999999]=1",
+    b"ids[18446744073709551615]=1",
+    b"ids[-1]=1",
+    b"ids[1e3]=1",
+    b"ids[ 1]=1",
+    b"ids[4294967296]=1&ids[0]=2",
+    b"page=99999999999999999999",
+    b"page=-0",
+    b"page=+1",
+    b"ids=,,,",
+    b"ids=1,,2",
+];
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(crate = "_serde")]
+struct Deal {
+    deal_type: Option<String>,
+    ids: Option<Vec<u32>>,
+    page: Option<u32>,
+}
+
+/// Deserializes the input, turning a panic into a failed assertion naming the input
+fn check<'de, T>(input: &'de [u8], mode: ParseMode) -> Result<T, Error>
+where
+    T: Deserialize<'de>,
+{
+    catch_unwind(AssertUnwindSafe(|| from_bytes::<T>(input, mode))).unwrap_or_else(|_| {
+        panic!(
+            "deserializing {:?} into {} panicked",
+            String::from_utf8_lossy(input),
+            std::any::type_name::<T>()
+        )
+    })
+}
+
+#[test]
+fn corpus_never_panics() {
+    for input in CORPUS {
+        for mode in MODES {
+            let _ = check::<HashMap<String, String>>(input, mode);
+            let _ = check::<HashMap<String, Vec<String>>>(input, mode);
+            let _ = check::<HashMap<String, HashMap<String, String>>>(input, mode);
+            let _ = check::<Deal>(input, mode);
+            let _ = check::<Option<Deal>>(input, mode);
+            let _ = check::<IgnoredAny>(input, mode);
+        }
+    }
+}
+
+#[test]
+fn corpus_stray_separators() {
+    for mode in MODES {
+        let deal = check::<Deal>(b"&&&deal_type=x&&", mode).unwrap();
+        assert_eq!(deal.deal_type.as_deref(), Some("x"));
+
+        let deal = check::<Deal>(b"deal_type=x&=&page=2", mode).unwrap();
+        assert_eq!(deal.deal_type.as_deref(), Some("x"));
+        assert_eq!(deal.page, Some(2));
+
+        // The last assignment wins, even when it's empty
+        let deal = check::<Deal>(b"deal_type&deal_type=", mode).unwrap();
+        assert_eq!(deal.deal_type.as_deref(), Some(""));
+
+        // Everything after the first `=` belongs to the value
+        assert_eq!(
+            check(b"a=b=c", mode),
+            Ok(HashMap::from([("a".to_string(), "b=c".to_string())]))
+        );
+        assert_eq!(
+            check(b"=a", mode),
+            Ok(HashMap::from([(String::new(), "a".to_string())]))
+        );
+    }
+}
+
+#[test]
+fn corpus_percent_escapes() {
+    for mode in MODES {
+        // Incomplete escapes are kept as they are
+        for (input, value) in [
+            (&b"a=%"[..], "%"),
+            (b"a=%4", "%4"),
+            (b"a=%%41", "%A"),
+            (b"a=%zz", "%zz"),
+        ] {
+            assert_eq!(
+                check(input, mode),
+                Ok(HashMap::from([("a".to_string(), value.to_string())]))
+            );
+        }
+
+        // Invalid utf8 is an error for strings, but not for the fields which are not used
+        for input in [&b"%ff=%fe"[..], b"a=%C3", b"\xff=\xfe"] {
+            assert_eq!(
+                check::<HashMap<String, String>>(input, mode).map_err(|e| e.kind),
+                Err(ErrorKind::InvalidEncoding)
+            );
+            assert!(check::<Deal>(input, mode).is_ok());
+        }
+    }
+}
+
+#[test]
+fn corpus_malformed_brackets() {
+    let mode = ParseMode::Brackets;
+
+    // An unclosed bracket ends the key
+    for input in [&b"a[=1"[..], b"a[b=1", b"a[b][=1"] {
+        assert_eq!(
+            check::<HashMap<String, IgnoredAny>>(input, mode).map(|m| m.len()),
+            Ok(1)
+        );
+    }
+
+    // Closing brackets without an opening one are a part of the key
+    assert_eq!(
+        check(b"a]=1", mode),
+        Ok(HashMap::from([("a]".to_string(), "1".to_string())]))
+    );
+
+    // A scalar field takes the last value, ignoring sub keys
+    let deal = check::<Deal>(b"deal_type[x]=1", mode).unwrap();
+    assert_eq!(deal.deal_type.as_deref(), Some("1"));
+
+    // Direct values and indexed values are elements of the same sequence
+    assert_eq!(
+        check::<Deal>(b"ids=1&ids[0]=2", mode).unwrap().ids,
+        Some(vec![1, 2])
+    );
+    assert_eq!(
+        check::<Deal>(b"ids[0]=1&ids=2", mode).unwrap().ids,
+        Some(vec![1, 2])
+    );
+}
+
+#[test]
+fn corpus_huge_numbers() {
+    let mode = ParseMode::Brackets;
+
+    for input in [
+        &b"ids[99999999999999999999]=1"[..],
+        b"ids[-1]=1",
+        b"ids[1e3]=1",
+        b"ids[ 1]=1",
+    ] {
+        assert_eq!(
+            check::<Deal>(input, mode).map_err(|e| e.kind),
+            Err(ErrorKind::InvalidNumber)
+        );
+    }
+
+    // Huge indices only order the elements, nothing is allocated for the gaps
+    assert_eq!(
+        check::<Deal>(b"ids[18446744073709551615]=1", mode)
+            .unwrap()
+            .ids,
+        Some(vec![1])
+    );
+    assert_eq!(
+        check::<Deal>(b"ids[4294967296]=1&ids[0]=2", mode)
+            .unwrap()
+            .ids,
+        Some(vec![2, 1])
+    );
+
+    for mode in MODES {
+        assert_eq!(
+            check::<Deal>(b"page=99999999999999999999", mode).map_err(|e| e.kind),
+            Err(ErrorKind::InvalidNumber)
+        );
+        assert_eq!(check::<Deal>(b"page=-0", mode).unwrap().page, Some(0));
+    }
+
+    // Empty elements of a delimited sequence are not numbers
+    assert_eq!(
+        check::<Deal>(b"ids=1,,2", ParseMode::Delimiter(b',')).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidNumber)
+    );
+}
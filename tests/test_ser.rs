@@ -0,0 +1,357 @@
+//! These tests are for the serializer, mostly checking the round trips through the deserializer
+
+use std::collections::BTreeMap;
+
+use _serde::{Deserialize, Serialize};
+use serde_querystring::{
+    from_bytes, from_str, to_bytes, to_bytes_brackets, to_string, to_string_brackets,
+    to_string_delimiter, to_string_duplicate, ErrorKind, ParseMode,
+};
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct Flat {
+    name: String,
+    count: u32,
+    delta: i64,
+    ratio: f64,
+    enabled: bool,
+    initial: char,
+    side: Side,
+    note: Option<String>,
+    empty: Option<Option<u8>>,
+}
+
+#[test]
+fn serialize_urlencoded_round_trip() {
+    let flat = Flat {
+        name: "Tom & Jerry = 100% fun?".to_string(),
+        count: 42,
+        delta: -7,
+        ratio: 1.5,
+        enabled: true,
+        initial: 'ü',
+        side: Side::Right,
+        note: None,
+        empty: Some(None),
+    };
+
+    let qs = to_string(&flat).unwrap();
+    assert_eq!(
+        qs,
+        "name=Tom+%26+Jerry+%3D+100%25+fun%3F&count=42&delta=-7&ratio=1.5&enabled=true\
+        &initial=%C3%BC&side=Right&empty="
+    );
+    assert_eq!(from_str::<Flat>(&qs, ParseMode::UrlEncoded), Ok(flat));
+    assert_eq!(
+        to_bytes(&Side::Left).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+}
+
+#[test]
+fn serialize_urlencoded_maps() {
+    let map = BTreeMap::from([
+        ("a b".to_string(), Some("x+y".to_string())),
+        ("c/d".to_string(), None),
+        ("e".to_string(), Some(String::new())),
+    ]);
+
+    let qs = to_string(&map).unwrap();
+    assert_eq!(qs, "a+b=x%2By&e=");
+    assert_eq!(
+        from_str::<BTreeMap<String, Option<String>>>(&qs, ParseMode::UrlEncoded),
+        Ok(BTreeMap::from([
+            ("a b".to_string(), Some("x+y".to_string())),
+            ("e".to_string(), Some(String::new())),
+        ]))
+    );
+
+    // Numbers and unit variants are valid keys
+    assert_eq!(
+        to_string(&BTreeMap::from([(1, "a"), (2, "b")])),
+        Ok("1=a&2=b".to_string())
+    );
+    assert_eq!(
+        to_string(&BTreeMap::from([(Side::Left, 1)])),
+        Ok("Left=1".to_string())
+    );
+
+    // An empty or missing value is an empty querystring
+    assert_eq!(to_string(&BTreeMap::<String, u8>::new()), Ok(String::new()));
+    assert_eq!(to_string(&None::<Flat>), Ok(String::new()));
+}
+
+#[test]
+fn serialize_urlencoded_errors() {
+    #[derive(Serialize)]
+    #[serde(crate = "_serde")]
+    struct Nested {
+        ids: Vec<u32>,
+    }
+
+    let error = to_string(&Nested { ids: vec![1, 2] }).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert_eq!(
+        error.message,
+        "nested maps and sequences are not supported in urlencoded mode"
+    );
+    assert_eq!(
+        to_string(&BTreeMap::from([("inner", BTreeMap::from([("a", 1)]))])).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+
+    // Only structs and maps can be the querystring
+    assert_eq!(
+        to_string(&vec![("a", 1)]).map_err(|e| e.message),
+        Err("only structs and maps can be serialized into a querystring".to_string())
+    );
+    assert_eq!(
+        to_string("a=1").map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+
+    // Keys can't be missing or nested
+    assert_eq!(
+        to_string(&BTreeMap::from([(None::<u8>, 1)])).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+    assert_eq!(
+        to_string(&BTreeMap::from([(vec![1], 1)])).map_err(|e| e.message),
+        Err("keys should be strings, numbers, booleans or unit variants".to_string())
+    );
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+enum Weather {
+    Sunny,
+    Rainy(u32),
+    Missed(u32, u32),
+    Storm { wind: u32, name: String },
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(crate = "_serde")]
+struct City {
+    name: String,
+    weather: Weather,
+    forecast: Vec<Weather>,
+    population: Option<u64>,
+    districts: BTreeMap<String, Vec<u32>>,
+    location: (f64, f64),
+    sensors: Vec<Option<String>>,
+}
+
+#[test]
+fn serialize_brackets_round_trip() {
+    let city = City {
+        name: "New York".to_string(),
+        weather: Weather::Missed(1200, 2400),
+        forecast: vec![
+            Weather::Sunny,
+            Weather::Rainy(3),
+            Weather::Storm {
+                wind: 90,
+                name: "Ana [1]".to_string(),
+            },
+        ],
+        population: None,
+        districts: BTreeMap::from([
+            ("Bronx".to_string(), vec![10451, 10452]),
+            ("Queens".to_string(), vec![11004]),
+        ]),
+        location: (40.71, -74.0),
+        sensors: vec![Some("a".to_string()), None, Some("c".to_string())],
+    };
+
+    let qs = to_bytes_brackets(&city).unwrap();
+    assert_eq!(
+        String::from_utf8(qs.clone()).unwrap(),
+        "name=New+York&weather[Missed][0]=1200&weather[Missed][1]=2400\
+        &forecast[0]=Sunny&forecast[1][Rainy]=3\
+        &forecast[2][Storm][wind]=90&forecast[2][Storm][name]=Ana+%5B1%5D\
+        &districts[Bronx][0]=10451&districts[Bronx][1]=10452&districts[Queens][0]=11004\
+        &location[0]=40.71&location[1]=-74&sensors[0]=a&sensors[2]=c"
+    );
+    assert_eq!(from_bytes(&qs, ParseMode::Brackets), Ok(city));
+}
+
+#[test]
+fn serialize_brackets_values() {
+    // Maps with non-string keys, and options
+    let map = BTreeMap::from([(1, Some(Some("x".to_string()))), (2, Some(None)), (3, None)]);
+    let qs = to_string_brackets(&BTreeMap::from([("value", &map)])).unwrap();
+    assert_eq!(qs, "value[1]=x&value[2]=");
+    assert_eq!(
+        from_str::<BTreeMap<String, BTreeMap<u32, Option<Option<String>>>>>(
+            &qs,
+            ParseMode::Brackets
+        ),
+        Ok(BTreeMap::from([(
+            "value".to_string(),
+            BTreeMap::from([(1, Some(Some("x".to_string()))), (2, Some(None))])
+        )]))
+    );
+
+    // Empty sequences are omitted, and so are the values
+    assert_eq!(
+        to_string_brackets(&BTreeMap::from([("a", vec![0u8; 0])])),
+        Ok(String::new())
+    );
+
+    // Only structs and maps can be the querystring
+    assert_eq!(
+        to_string_brackets(&vec![1, 2]).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+    assert_eq!(
+        to_string_brackets(&Weather::Rainy(1)).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+    assert_eq!(
+        to_string_brackets(&BTreeMap::from([(vec![1], 1)])).map_err(|e| e.kind),
+        Err(ErrorKind::InvalidType)
+    );
+}
+
+#[test]
+fn serialize_duplicate_values() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(crate = "_serde")]
+    struct Links {
+        pages: Vec<u32>,
+        sides: Option<Vec<Side>>,
+        pair: (String, bool),
+        tags: Vec<Option<String>>,
+        empty: Vec<u32>,
+        title: String,
+    }
+
+    let links = Links {
+        pages: vec![1, 2],
+        sides: Some(vec![Side::Right, Side::Left]),
+        pair: ("a b".to_string(), true),
+        tags: vec![None, Some("x".to_string()), None],
+        empty: vec![],
+        title: "all".to_string(),
+    };
+
+    // None elements and empty sequences are skipped
+    let qs = to_string_duplicate(&links).unwrap();
+    assert_eq!(
+        qs,
+        "pages=1&pages=2&sides=Right&sides=Left&pair=a+b&pair=true&tags=x&title=all"
+    );
+    assert_eq!(
+        from_str::<BTreeMap<String, Vec<String>>>(&qs, ParseMode::Duplicate),
+        Ok(BTreeMap::from([
+            ("pages".to_string(), vec!["1".to_string(), "2".to_string()]),
+            (
+                "sides".to_string(),
+                vec!["Right".to_string(), "Left".to_string()]
+            ),
+            (
+                "pair".to_string(),
+                vec!["a b".to_string(), "true".to_string()]
+            ),
+            ("tags".to_string(), vec!["x".to_string()]),
+            ("title".to_string(), vec!["all".to_string()]),
+        ]))
+    );
+
+    // Maps are written the same way
+    assert_eq!(
+        to_string_duplicate(&BTreeMap::from([
+            ("a", vec![1]),
+            ("b", vec![]),
+            ("c", vec![2, 3])
+        ])),
+        Ok("a=1&c=2&c=3".to_string())
+    );
+
+    // Depth can't be expressed
+    for result in [
+        to_string_duplicate(&BTreeMap::from([("a", vec![vec![1]])])),
+        to_string_duplicate(&BTreeMap::from([("a", BTreeMap::from([("b", 1)]))])),
+        to_string_duplicate(&BTreeMap::from([("a", Weather::Rainy(1))])),
+    ] {
+        assert_eq!(
+            result.map_err(|e| (e.kind, e.message)),
+            Err((
+                ErrorKind::InvalidType,
+                "nested maps and sequences are not supported in duplicate mode".to_string()
+            ))
+        );
+    }
+}
+
+#[test]
+fn serialize_delimiter_values() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    #[serde(crate = "_serde")]
+    struct Lists {
+        names: Vec<String>,
+        sides: Option<Vec<Side>>,
+        pair: (String, f64),
+        empty: Option<Vec<u32>>,
+        title: String,
+    }
+
+    // The delimiter is encoded in the elements, and a scalar containing it too
+    for delimiter in [b'|', b',', b';', b'+', b'~', b'_'] {
+        let lists = Lists {
+            names: vec![
+                "a|b,c-d".to_string(),
+                "e.f+g h".to_string(),
+                "~_".to_string(),
+            ],
+            sides: Some(vec![Side::Right, Side::Left]),
+            pair: ("x y".to_string(), -1.5),
+            empty: None,
+            title: "a,b|c d".to_string(),
+        };
+
+        let qs = to_string_delimiter(&lists, delimiter).unwrap();
+        assert_eq!(
+            from_str::<Lists>(&qs, ParseMode::Delimiter(delimiter)),
+            Ok(lists),
+            "{}",
+            qs
+        );
+    }
+
+    // None elements and empty sequences are skipped
+    assert_eq!(
+        to_string_delimiter(
+            &BTreeMap::from([
+                ("a", vec![None, Some(1), None, Some(2)]),
+                ("b", vec![]),
+                ("c", vec![None])
+            ]),
+            b','
+        ),
+        Ok("a=1,2".to_string())
+    );
+
+    // Depth can't be expressed
+    assert_eq!(
+        to_string_delimiter(&BTreeMap::from([("a", vec![vec![1]])]), b'|').map_err(|e| e.message),
+        Err("nested maps and sequences are not supported in delimiter mode".to_string())
+    );
+
+    // Nor delimiters which can't be told apart once encoded
+    for delimiter in [b'a', b'0', b'%', b'&', b'=', b'-', b'.', 0xC3] {
+        assert_eq!(
+            to_string_delimiter(&BTreeMap::from([("a", 1)]), delimiter).map_err(|e| e.kind),
+            Err(ErrorKind::Other)
+        );
+    }
+}
@@ -0,0 +1,43 @@
+//! These tests check the events emitted with the `tracing` feature
+
+use _serde::Deserialize;
+use serde_querystring::de::{from_str, from_str_collect_errors, ParseMode};
+use tracing_test::traced_test;
+
+#[derive(Debug, Deserialize)]
+#[serde(crate = "_serde")]
+#[allow(dead_code)]
+struct Person {
+    age: u8,
+    subscribed: bool,
+}
+
+#[traced_test]
+#[test]
+fn error_emits_event() {
+    assert!(from_str::<Person>("age=twenty&subscribed=1", ParseMode::UrlEncoded).is_err());
+
+    assert!(logs_contain("failed to deserialize the querystring"));
+    assert!(logs_contain("kind=InvalidNumber"));
+    assert!(logs_contain("value=twenty"));
+}
+
+#[traced_test]
+#[test]
+fn collected_errors_emit_events() {
+    assert!(
+        from_str_collect_errors::<Person>("age=twenty&subscribed=maybe", ParseMode::Duplicate)
+            .is_err()
+    );
+
+    assert!(logs_contain("kind=InvalidNumber"));
+    assert!(logs_contain("kind=InvalidBoolean"));
+}
+
+#[traced_test]
+#[test]
+fn success_emits_nothing() {
+    assert!(from_str::<Person>("age=20&subscribed=1", ParseMode::UrlEncoded).is_ok());
+
+    assert!(!logs_contain("failed to deserialize the querystring"));
+}
@@ -1,7 +1,10 @@
 //! These tests are meant for the `UrlEncodedQS` method
 
 use _serde::Deserialize;
-use serde_querystring::de::{from_bytes, ErrorKind, ParseMode};
+use serde_querystring::{
+    de::{from_bytes, from_matrix_str, ErrorKind, ParseMode},
+    UrlEncodedQS,
+};
 
 /// It is a helper struct we use to test primitive types
 /// as we don't support anything beside maps/structs at the root level
@@ -66,6 +69,24 @@ fn deserialize_decoded_keys() {
     );
 }
 
+#[test]
+fn deserialize_no_decode() {
+    // Keys and values are passed through as they are
+    assert_eq!(
+        UrlEncodedQS::parse_no_decode(b"foo=b+r%20&foobar=1337&bar=13").deserialize(),
+        Ok(UrlEncoded {
+            foo: "b+r%20",
+            foobar: 1337,
+            bar: Some(13)
+        })
+    );
+
+    assert_eq!(
+        UrlEncodedQS::parse_no_decode(b"f%6Fo=bar&value=baz").deserialize(),
+        Ok(p!("baz"))
+    );
+}
+
 #[test]
 fn deserialize_error_type() {
     // we don't support sequences in this mode
@@ -89,9 +110,24 @@ fn deserialize_error_type() {
         ErrorKind::InvalidType
     );
 
+    // With a hint to use another mode
+    let message = "sequences are not supported in urlencoded mode, \
+        use duplicate, delimiter or brackets mode";
+    let error =
+        from_bytes::<Primitive<Vec<usize>>>(b"value=1&value=3", ParseMode::UrlEncoded).unwrap_err();
+    assert_eq!(error.kind, ErrorKind::InvalidType);
+    assert_eq!(error.message, message);
+    assert_eq!(
+        from_bytes::<Primitive<Option<Vec<usize>>>>(b"value=1", ParseMode::UrlEncoded)
+            .unwrap_err()
+            .message,
+        message
+    );
+
     // We don't support non-unit enums
     #[derive(Debug, Deserialize)]
     #[serde(crate = "_serde")]
+    #[allow(dead_code)]
     enum ValueEnum {
         A(i32, i32),
         B(i32),
@@ -117,3 +153,43 @@ fn deserialize_error_type() {
         ErrorKind::InvalidType
     );
 }
+
+#[test]
+fn deserialize_matrix_params() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    #[serde(crate = "_serde")]
+    struct Params {
+        a: u32,
+        b: u32,
+        c: Option<String>,
+    }
+
+    assert_eq!(
+        from_matrix_str("a=1;b=2"),
+        Ok(Params {
+            a: 1,
+            b: 2,
+            c: None
+        })
+    );
+
+    // A leading `;` is skipped, `&` is a plain character and `+` is not a space
+    assert_eq!(
+        from_matrix_str(";a=1;;b=2;c=x+y%20z&w=1"),
+        Ok(Params {
+            a: 1,
+            b: 2,
+            c: Some("x+y z&w=1".to_string())
+        })
+    );
+
+    // The last value of a repeated key is used
+    assert_eq!(
+        from_matrix_str("a=1;b=2;a=3"),
+        Ok(Params {
+            a: 3,
+            b: 2,
+            c: None
+        })
+    );
+}